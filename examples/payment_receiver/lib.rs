@@ -0,0 +1,182 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// A minimal payee contract that implements the payment splitter's `on_payment_received` hook.
+#[ink::contract]
+pub mod payment_receiver {
+    #[ink(storage)]
+    #[derive(Default)]
+    pub struct PaymentReceiver {
+        /// Total amount received through the hook.
+        received: Balance,
+        /// Number of times the hook was called.
+        notifications: u32,
+        /// Whether the hook burns all the gas it is given instead of recording the payment.
+        burn_gas: bool,
+    }
+
+    impl PaymentReceiver {
+        /// Creates a receiver that has not been paid yet.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Creates a receiver whose hook never returns, consuming all the gas it is given.
+        #[ink(constructor)]
+        pub fn new_gas_guzzler() -> Self {
+            Self { burn_gas: true, ..Self::default() }
+        }
+
+        /// Called by the payment splitter with the paid `amount` attached.
+        #[ink(message, payable)]
+        pub fn on_payment_received(&mut self, amount: Balance) {
+            while self.burn_gas {
+                self.received = self.received.wrapping_add(1);
+            }
+            self.received = self.received.saturating_add(amount);
+            self.notifications = self.notifications.saturating_add(1);
+        }
+
+        /// Returns the total amount received through the hook.
+        #[ink(message)]
+        pub fn received(&self) -> Balance {
+            self.received
+        }
+
+        /// Returns the number of times the hook was called.
+        #[ink(message)]
+        pub fn notifications(&self) -> u32 {
+            self.notifications
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::{ AccountKeyring, ChainBackend, ContractsBackend };
+        use payment_splitter::payment_splitter::{ PaymentSplitter, PaymentSplitterRef };
+        use payment_splitter::PaymentSplit;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn hook_fires_and_value_arrives<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            // Arrange - a splitter paying the receiver contract and Charlie
+            let alice = ink_e2e::account_id(AccountKeyring::Alice);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+
+            let mut receiver_constructor = PaymentReceiverRef::new();
+            let receiver = client
+                .instantiate("payment_receiver", &ink_e2e::alice(), &mut receiver_constructor)
+                .submit().await
+                .expect("receiver instantiate failed");
+            let receiver_call = receiver.call_builder::<PaymentReceiver>();
+
+            let mut splitter_constructor = PaymentSplitterRef::new(vec![receiver.account_id, charlie], alice);
+            let splitter = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut splitter_constructor)
+                .submit().await
+                .expect("splitter instantiate failed");
+            let mut splitter_call = splitter.call_builder::<PaymentSplitter>();
+
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_payment_hook(receiver.account_id, true))
+                .submit().await
+                .expect("set_payment_hook failed");
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_reserve(0))
+                .submit().await
+                .expect("set_reserve failed");
+            client
+                .call(&ink_e2e::bob(), &splitter_call.deposit())
+                .value(2_000_000_000)
+                .submit().await
+                .expect("deposit failed");
+            let receiver_before = client.free_balance(receiver.account_id).await?;
+
+            // Act
+            client
+                .call(&ink_e2e::alice(), &splitter_call.trigger_payout())
+                .submit().await
+                .expect("trigger_payout failed");
+
+            // Assert - the hook recorded the amount that arrived with it
+            let received = client
+                .call(&ink_e2e::alice(), &receiver_call.received())
+                .dry_run().await?
+                .return_value();
+            let notifications = client
+                .call(&ink_e2e::alice(), &receiver_call.notifications())
+                .dry_run().await?
+                .return_value();
+            let receiver_after = client.free_balance(receiver.account_id).await?;
+            assert_eq!(notifications, 1);
+            assert!(received >= 1_000_000_000);
+            assert_eq!(receiver_after - receiver_before, received);
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn gas_stipend_bounds_a_guzzling_hook<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            // Arrange - a splitter paying a gas-guzzling receiver contract and Charlie
+            let alice = ink_e2e::account_id(AccountKeyring::Alice);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+
+            let mut receiver_constructor = PaymentReceiverRef::new_gas_guzzler();
+            let receiver = client
+                .instantiate("payment_receiver", &ink_e2e::alice(), &mut receiver_constructor)
+                .submit().await
+                .expect("receiver instantiate failed");
+            let receiver_call = receiver.call_builder::<PaymentReceiver>();
+
+            let mut splitter_constructor = PaymentSplitterRef::new(vec![receiver.account_id, charlie], alice);
+            let splitter = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut splitter_constructor)
+                .submit().await
+                .expect("splitter instantiate failed");
+            let mut splitter_call = splitter.call_builder::<PaymentSplitter>();
+
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_payment_hook(receiver.account_id, true))
+                .submit().await
+                .expect("set_payment_hook failed");
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_reserve(0))
+                .submit().await
+                .expect("set_reserve failed");
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_payment_hook_gas_limit(1_000_000_000))
+                .submit().await
+                .expect("set_payment_hook_gas_limit failed");
+            client
+                .call(&ink_e2e::bob(), &splitter_call.deposit())
+                .value(2_000_000_000)
+                .submit().await
+                .expect("deposit failed");
+            let receiver_before = client.free_balance(receiver.account_id).await?;
+            let charlie_before = client.free_balance(charlie).await?;
+
+            // Act
+            let payout = client
+                .call(&ink_e2e::alice(), &splitter_call.trigger_payout())
+                .submit().await
+                .expect("trigger_payout failed");
+
+            // Assert - the hook ran out of gas, its share arrived as a plain transfer and
+            // Charlie was paid as well
+            assert_eq!(payout.return_value().map(|executed| executed.len()), Ok(2));
+            let notifications = client
+                .call(&ink_e2e::alice(), &receiver_call.notifications())
+                .dry_run().await?
+                .return_value();
+            assert_eq!(notifications, 0);
+            assert_eq!(client.free_balance(receiver.account_id).await? - receiver_before, 1_000_000_000);
+            assert_eq!(client.free_balance(charlie).await? - charlie_before, 1_000_000_000);
+            Ok(())
+        }
+    }
+}
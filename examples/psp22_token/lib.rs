@@ -0,0 +1,176 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+use ink::prelude::{ string::String, vec::Vec };
+use ink::primitives::AccountId;
+
+/// The balance type of the token, the same as the chain's native balance.
+pub type Balance = <ink::env::DefaultEnvironment as ink::env::Environment>::Balance;
+
+/// Errors returned by PSP22 token contracts, as defined by the PSP22 standard.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PSP22Error {
+    Custom(String),
+    InsufficientBalance,
+    InsufficientAllowance,
+    ZeroRecipientAddress,
+    ZeroSenderAddress,
+    SafeTransferCheckFailed(String),
+}
+
+/// The part of the PSP22 standard the payment splitter uses, with the standard's selectors.
+#[ink::trait_definition]
+pub trait PSP22 {
+    /// Returns the total supply of the token.
+    #[ink(message)]
+    fn total_supply(&self) -> Balance;
+
+    /// Returns the balance of `owner`.
+    #[ink(message)]
+    fn balance_of(&self, owner: AccountId) -> Balance;
+
+    /// Returns the amount `spender` may still transfer on behalf of `owner`.
+    #[ink(message)]
+    fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance;
+
+    /// Transfers `value` from the caller to `to`.
+    #[ink(message)]
+    fn transfer(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error>;
+
+    /// Transfers `value` from `from` to `to`, using the allowance `from` gave the caller.
+    #[ink(message)]
+    fn transfer_from(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        value: Balance,
+        data: Vec<u8>
+    ) -> Result<(), PSP22Error>;
+
+    /// Allows `spender` to transfer up to `value` on behalf of the caller.
+    #[ink(message)]
+    fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error>;
+}
+
+/// A minimal PSP22 token for testing the payment splitter's token payouts.
+///
+/// The whole supply goes to the deployer. Transfers to an account the deployer blocked fail, so
+/// tests can exercise the splitter's handling of rejected token transfers.
+#[ink::contract]
+pub mod psp22_token {
+    use super::{ PSP22Error, PSP22 };
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    #[ink(storage)]
+    pub struct Psp22Token {
+        /// The total supply, minted to the deployer.
+        total_supply: Balance,
+        /// The balance of each account.
+        balances: Mapping<AccountId, Balance>,
+        /// The allowance of each `(owner, spender)` pair.
+        allowances: Mapping<(AccountId, AccountId), Balance>,
+        /// Accounts that cannot receive tokens.
+        blocked: Mapping<AccountId, ()>,
+        /// The deployer, who may block accounts.
+        owner: AccountId,
+    }
+
+    impl Psp22Token {
+        /// Creates a token whose `total_supply` belongs to the caller.
+        #[ink(constructor)]
+        pub fn new(total_supply: Balance) -> Self {
+            let owner = Self::env().caller();
+            let mut balances = Mapping::default();
+            balances.insert(owner, &total_supply);
+            Self {
+                total_supply,
+                balances,
+                allowances: Mapping::default(),
+                blocked: Mapping::default(),
+                owner,
+            }
+        }
+
+        /// Makes transfers to `account` fail while `blocked` is true. Only the deployer may
+        /// call it.
+        #[ink(message)]
+        pub fn set_blocked(&mut self, account: AccountId, blocked: bool) -> Result<(), PSP22Error> {
+            if self.env().caller() != self.owner {
+                return Err(PSP22Error::Custom("not the owner".into()));
+            }
+            if blocked {
+                self.blocked.insert(account, &());
+            } else {
+                self.blocked.remove(account);
+            }
+            Ok(())
+        }
+
+        /// Helper function to move `value` from `from` to `to`.
+        fn move_tokens(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            if to == AccountId::from([0; 32]) {
+                return Err(PSP22Error::ZeroRecipientAddress);
+            }
+            if self.blocked.contains(to) {
+                return Err(PSP22Error::Custom("recipient blocked".into()));
+            }
+            let from_balance = self.balances.get(from).unwrap_or(0);
+            if from_balance < value {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            self.balances.insert(from, &(from_balance - value));
+            let to_balance = self.balances.get(to).unwrap_or(0);
+            self.balances.insert(to, &to_balance.saturating_add(value));
+            Ok(())
+        }
+    }
+
+    impl PSP22 for Psp22Token {
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get((owner, spender)).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, _data: Vec<u8>) -> Result<(), PSP22Error> {
+            let from = self.env().caller();
+            self.move_tokens(from, to, value)
+        }
+
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>
+        ) -> Result<(), PSP22Error> {
+            let spender = self.env().caller();
+            let allowance = self.allowances.get((from, spender)).unwrap_or(0);
+            if allowance < value {
+                return Err(PSP22Error::InsufficientAllowance);
+            }
+            self.move_tokens(from, to, value)?;
+            self.allowances.insert((from, spender), &(allowance - value));
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            self.allowances.insert((owner, spender), &value);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,152 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// A minimal contract that forwards revenue to any payment splitter, talking to it only through
+/// the `PaymentSplit` trait.
+#[ink::contract]
+pub mod revenue_router {
+    use ink::codegen::TraitCallBuilder;
+    use ink::prelude::vec::Vec;
+    use payment_splitter::{ payment_splitter::Error, PaymentSplit };
+
+    #[ink(storage)]
+    pub struct RevenueRouter {
+        /// The splitter that receives the routed revenue.
+        splitter: AccountId,
+    }
+
+    impl RevenueRouter {
+        /// Creates a router forwarding to `splitter`.
+        #[ink(constructor)]
+        pub fn new(splitter: AccountId) -> Self {
+            Self { splitter }
+        }
+
+        /// Deposits the transferred value into the splitter.
+        #[ink(message, payable)]
+        pub fn route(&mut self) -> Result<(), Error> {
+            let mut splitter: ink::contract_ref!(PaymentSplit) = self.splitter.into();
+            splitter
+                .call_mut()
+                .deposit()
+                .transferred_value(self.env().transferred_value())
+                .invoke()
+        }
+
+        /// Triggers a payout on the splitter and returns the total it transferred.
+        ///
+        /// The router must be allowed to trigger payouts, for example as the splitter's operator.
+        #[ink(message)]
+        pub fn payout(&mut self) -> Result<Balance, Error> {
+            let mut splitter: ink::contract_ref!(PaymentSplit) = self.splitter.into();
+            let executed = splitter.trigger_payout()?;
+            Ok(executed.iter().map(|payout| payout.amount).sum())
+        }
+
+        /// Returns the payees of the splitter.
+        #[ink(message)]
+        pub fn payees(&self) -> Vec<AccountId> {
+            let splitter: ink::contract_ref!(PaymentSplit) = self.splitter.into();
+            splitter.get_payees()
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::{ AccountKeyring, ChainBackend, ContractsBackend };
+        use payment_splitter::payment_splitter::PaymentSplitterRef;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn routes_revenue_through_the_trait<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            // Arrange - a splitter paying Charlie and Dave, and a router in front of it
+            let alice = ink_e2e::account_id(AccountKeyring::Alice);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let dave = ink_e2e::account_id(AccountKeyring::Dave);
+
+            let mut splitter_constructor = PaymentSplitterRef::new(vec![charlie, dave], alice);
+            let splitter = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut splitter_constructor)
+                .submit().await
+                .expect("splitter instantiate failed");
+
+            let mut router_constructor = RevenueRouterRef::new(splitter.account_id);
+            let router = client
+                .instantiate("revenue_router", &ink_e2e::alice(), &mut router_constructor)
+                .submit().await
+                .expect("router instantiate failed");
+            let mut router_call = router.call_builder::<RevenueRouter>();
+            let splitter_before = client.free_balance(splitter.account_id).await?;
+
+            // Act
+            let routed = client
+                .call(&ink_e2e::bob(), &router_call.route())
+                .value(1_000_000_000)
+                .submit().await
+                .expect("route failed");
+            let payees = client
+                .call(&ink_e2e::bob(), &router_call.payees())
+                .dry_run().await?;
+
+            // Assert
+            assert_eq!(routed.return_value(), Ok(()));
+            assert_eq!(payees.return_value(), vec![charlie, dave]);
+            let splitter_after = client.free_balance(splitter.account_id).await?;
+            assert_eq!(splitter_after - splitter_before, 1_000_000_000);
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn reports_the_executed_payout<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            // Arrange - a funded splitter whose operator is the router
+            let alice = ink_e2e::account_id(AccountKeyring::Alice);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let dave = ink_e2e::account_id(AccountKeyring::Dave);
+
+            let mut splitter_constructor = PaymentSplitterRef::new(vec![charlie, dave], alice);
+            let splitter = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut splitter_constructor)
+                .submit().await
+                .expect("splitter instantiate failed");
+            let mut splitter_call = splitter.call_builder::<payment_splitter::payment_splitter::PaymentSplitter>();
+
+            let mut router_constructor = RevenueRouterRef::new(splitter.account_id);
+            let router = client
+                .instantiate("revenue_router", &ink_e2e::alice(), &mut router_constructor)
+                .submit().await
+                .expect("router instantiate failed");
+            let mut router_call = router.call_builder::<RevenueRouter>();
+
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_operator(Some(router.account_id)))
+                .submit().await
+                .expect("set_operator failed");
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_reserve(0))
+                .submit().await
+                .expect("set_reserve failed");
+            client
+                .call(&ink_e2e::bob(), &router_call.route())
+                .value(2_000_000_000)
+                .submit().await
+                .expect("route failed");
+            let charlie_before = client.free_balance(charlie).await?;
+
+            // Act
+            let payout = client
+                .call(&ink_e2e::bob(), &router_call.payout())
+                .submit().await
+                .expect("payout failed");
+
+            // Assert
+            assert_eq!(payout.return_value(), Ok(2_000_000_000));
+            assert_eq!(client.free_balance(charlie).await? - charlie_before, 1_000_000_000);
+            Ok(())
+        }
+    }
+}
@@ -1,11 +1,106 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+use ink::env::{ DefaultEnvironment, Environment };
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The interface every payment splitter exposes, for contracts that call a splitter generically
+/// through `ink::contract_ref!(PaymentSplit)`.
+///
+/// The selectors are fixed to those of the original inherent messages, so existing callers keep
+/// working.
+#[ink::trait_definition]
+pub trait PaymentSplit {
+    /// Deposits the transferred value into the splitter.
+    #[ink(message, payable, selector = 0x2d10c9bd)]
+    fn deposit(&mut self) -> Result<(), payment_splitter::Error>;
+
+    /// Distributes the splitter's balance to its payees and returns the transfers that went
+    /// through, in payee order.
+    #[ink(message, selector = 0x0e549a4f)]
+    fn trigger_payout(&mut self) -> Result<Vec<payment_splitter::PayoutInfo>, payment_splitter::Error>;
+
+    /// Returns the registered payees.
+    #[ink(message, selector = 0xf334a6d7)]
+    fn get_payees(&self) -> Vec<AccountId>;
+
+    /// Returns what `payee` would receive if the payout ran now.
+    #[ink(message, selector = 0x2c9b2010)]
+    fn payee_share(&self, payee: AccountId) -> Result<Balance, payment_splitter::Error>;
+}
+
+// The generated constructor dispatch enum carries the `SplitterConfig` of `new_with_config`
+// by value, which makes it much larger than the `new` variant.
+#[allow(clippy::large_enum_variant)]
 #[ink::contract]
-mod payment_splitter {
-    use ink::prelude::vec::Vec;
+pub mod payment_splitter {
+    use super::distribution::{
+        compute_contribution_distribution,
+        compute_distribution,
+        compute_owed_distribution,
+        compute_tiered_distribution,
+        compute_weighted_distribution,
+    };
+    use super::PaymentSplit;
+    use ink::env::call::{ build_call, ExecutionInput, Selector };
+    use ink::env::hash::{ Blake2x256, HashOutput };
+    use ink::prelude::{ string::String, vec::Vec };
+    use ink::storage::traits::{ ManualKey, Packed, StorageKey };
+    use ink::storage::{ Lazy, Mapping };
+
+    /// Version of the contract logic and storage layout, reported by `contract_info`.
+    pub const VERSION: u32 = 1;
+
+    /// The storage key of the storage version. It lives outside the root struct, so `migrate`
+    /// reads it without decoding a layout that an upgrade may have changed.
+    pub const STORAGE_VERSION_KEY: u32 = 0x5645_5253;
+
+    /// Maximum number of payees that can be added after deployment, keeping payouts within the
+    /// block weight limit.
+    pub const MAX_PAYEES: u32 = 100;
+
+    /// Default reference-time limit of a single `on_payment_received` hook call, so one misbehaving
+    /// payee cannot consume the gas of the whole payout.
+    pub const PAYMENT_HOOK_GAS_LIMIT: u64 = 5_000_000_000;
+
+    /// Maximum number of receipts returned by a single `deposits_of` call.
+    pub const MAX_RECEIPTS_PER_PAGE: u32 = 50;
+
+    /// Minimum number of distinct payees whose votes a designated payee proposal needs besides
+    /// the share majority, so a single payee cannot take over the admin role on its own.
+    pub const MIN_RECOVERY_VOTERS: u32 = 2;
+
+    /// The sum the payee shares passed to `new_basis_points` must have (100%).
+    pub const TOTAL_BASIS_POINTS: u32 = 10_000;
+
+    /// Default number of blocks between scheduling and executing an emergency withdrawal:
+    /// 30 days of 6-second blocks.
+    pub const DEFAULT_EMERGENCY_DELAY: BlockNumber = 432_000;
+
+    /// The shortest emergency delay `set_emergency_delay` accepts: 1 day of 6-second blocks.
+    pub const MIN_EMERGENCY_DELAY: BlockNumber = 14_400;
+
+    /// Chain extension function returning the chain's random seed for a 32-byte subject
+    /// (extension 666, function 1101), used by `RemainderPolicy::RandomPayee`. When it reports
+    /// an error the remainder goes to the first payee, and `RemainderRecipientDrawn` says so.
+    pub const FETCH_RANDOM: u32 = (666 << 16) | 1101;
+
+    /// The status code of the `FETCH_RANDOM` chain extension function.
+    struct RandomUnavailable;
+
+    impl ink::env::chain_extension::FromStatusCode for RandomUnavailable {
+        fn from_status_code(status_code: u32) -> Result<(), Self> {
+            match status_code {
+                0 => Ok(()),
+                _ => Err(Self),
+            }
+        }
+    }
 
     /// Represents the possible errors that can occur within the PaymentSplitter contract.
-    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
         /// Indicates that the caller is not authorized to perform the requested action.
@@ -18,195 +113,5909 @@ mod payment_splitter {
         ZeroShare = 3,
         /// Reentrancy guard is locked.
         ReentrancyGuardLocked = 4,
+        /// The supplied nonce does not match the current payout nonce.
+        NonceMismatch = 5,
+        /// A payout was attempted before the cooldown since the last payout elapsed, or a second
+        /// time in the same payout period.
+        CooldownActive = 6,
+        /// The token is not in the list of registered assets.
+        TokenNotRegistered = 7,
+        /// The token is already in the list of registered assets.
+        TokenAlreadyRegistered = 8,
+        /// A cross-contract call to a token contract failed.
+        TokenCallFailed = 9,
+        /// The account is not a registered payee.
+        PayeeNotFound = 10,
+        /// In owed mode, every payee has already been paid what they are owed.
+        NothingOwed = 11,
+        /// No payout pool exists with the given id.
+        PoolNotFound = 12,
+        /// The payee's claims are locked until their unlock block.
+        ClaimLocked = 13,
+        /// The deposit deadline has passed.
+        DepositsClosed = 14,
+        /// The payout has not been approved by enough approvers yet.
+        InsufficientApprovals = 15,
+        /// The approval threshold is larger than the number of approvers.
+        InvalidThreshold = 16,
+        /// The signature was not produced by the configured payout signer.
+        InvalidSignature = 17,
+        /// The account is already a registered payee.
+        DuplicatePayee = 18,
+        /// The operation would exceed `MAX_PAYEES`.
+        TooManyPayees = 19,
+        /// The account is not a valid beneficiary (the all-zero account).
+        InvalidAccount = 20,
+        /// The deposit exceeds the configured maximum deposit.
+        DepositTooLarge = 21,
+        /// The amount is below the chain's minimum balance (existential deposit).
+        BelowMinimumBalance = 22,
+        /// The contract's transferable balance cannot cover the amount.
+        InsufficientBalance = 23,
+        /// No payout plan exists with the given id.
+        PlanNotFound = 24,
+        /// The payout plan's dispute window has not ended yet.
+        DisputeWindowActive = 25,
+        /// Nothing is left to distribute once the encumbered funds are set aside.
+        NothingDistributable = 26,
+        /// The deposit is below the configured minimum deposit.
+        BelowMinimumDeposit = 27,
+        /// Less value was transferred than the expected deposit.
+        InsufficientDeposit = 28,
+        /// The payee is frozen and may not receive funds.
+        PayeeFrozen = 29,
+        /// The contract balance dropped below the amount the payout was calculated from.
+        BalanceChanged = 30,
+        /// The designated payee proposal does not exist or is no longer open.
+        ProposalNotFound = 31,
+        /// The designated payee proposal is older than the recovery period.
+        ProposalExpired = 32,
+        /// The basis points of the payees do not add up to `TOTAL_BASIS_POINTS`.
+        SharesMismatch = 33,
+        /// `release` is only available in pull-payment mode.
+        PullPaymentsDisabled = 34,
+        /// The token is a registered asset split among the payees, so it cannot be rescued.
+        AssetManaged = 35,
+        /// The contract is paused: deposits and payouts are suspended.
+        ContractPaused = 36,
+        /// The emergency withdrawal's delay has not elapsed yet.
+        TimelockActive = 37,
+        /// No emergency withdrawal is scheduled.
+        NoEmergencyWithdrawal = 38,
+        /// The chain rejected the new code hash, e.g. because no code is uploaded for it.
+        UpgradeFailed = 39,
+        /// The storage layout is already at the current `VERSION`.
+        AlreadyMigrated = 40,
+        /// A batched payout round is in progress and must be completed first.
+        PayoutInProgress = 41,
+        /// The batch size is zero.
+        InvalidBatchSize = 42,
+        /// The payout needs more transfers than `max_transfers_per_call`; use
+        /// `trigger_payout_batch` instead.
+        TooManyTransfers = 43,
+        /// A payee's share of the payout would be below `min_payout_per_payee`.
+        BelowThreshold = 44,
+        /// The basis points exceed `TOTAL_BASIS_POINTS`.
+        InvalidBasisPoints = 45,
+        /// The payee's share was credited to their vesting schedule instead of being transferred.
+        PayeeVesting = 46,
+        /// A payout stream is still running and must end before the next one starts.
+        StreamActive = 47,
+        /// The emergency delay is below `MIN_EMERGENCY_DELAY`.
+        DelayTooShort = 48,
+        /// Funds other than the reserve are still set aside for the payees.
+        FundsEncumbered = 49,
+        /// Tokens deposited through `deposit_token` or parked as token claims are not
+        /// distributed yet.
+        TokenUndistributed = 50,
+        /// The payee has not withdrawn everything credited to their vesting schedule yet.
+        VestingUnreleased = 51,
     }
 
-    /// Struct to hold the amount to be transferred for each payee.
+    /// Errors returned by PSP22 token contracts, as defined by the PSP22 standard.
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
+    /// Struct to hold the amount to be transferred for each payee.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct PayoutInfo {
         pub payee: AccountId,
         pub amount: Balance,
     }
 
+    /// How the contract balance is divided among the payees.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum SplitMode {
+        /// The balance is split according to the payee tiers (equal split by default).
+        #[default]
+        Proportional,
+        /// Each payee is paid what they are owed, in list order, until the balance runs out.
+        Owed,
+        /// Depositors become payees and are paid pro-rata to what they deposited since the last
+        /// payout.
+        Contributions,
+    }
+
+    /// Where the rounding remainder of a payout goes.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum RemainderPolicy {
+        /// Added to the share of the first payee (the default).
+        #[default]
+        FirstPayee,
+        /// Paid to the designated payee.
+        DesignatedPayee,
+        /// Paid to the given account, which does not need to be a payee.
+        Treasury(AccountId),
+        /// Kept in the contract and split with the next payout.
+        CarryOver,
+        /// Paid to a payee drawn with the chain's randomness source each round or pool payout;
+        /// the draw, or the fallback to the first payee without randomness, is announced in a
+        /// `RemainderRecipientDrawn` event.
+        RandomPayee,
+    }
+
+    /// A permission that can be granted to accounts besides the designated payee, who holds
+    /// every role.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Role {
+        /// Changes the configuration and grants or revokes roles.
+        Admin,
+        /// Triggers payouts.
+        PayoutTrigger,
+        /// Adds, removes and reweighs payees.
+        PayeeManager,
+    }
+
+    /// An independent payout pool with its own payees and accounted balance.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Pool {
+        /// The payees sharing this pool's balance equally.
+        pub payees: Vec<AccountId>,
+        /// The part of the contract balance deposited into this pool and not yet paid out.
+        pub balance: Balance,
+        /// The block of the pool's last payout, for the payout cooldown.
+        pub last_payout_block: Option<BlockNumber>,
+    }
+
+    /// A payout calculated by `commit_payout` and waiting for `execute_payout`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PayoutPlan {
+        /// The amounts to transfer.
+        pub payouts: Vec<PayoutInfo>,
+        /// The block the plan was committed in.
+        pub committed_at: BlockNumber,
+        /// The remainder the plan leaves for the next round under `RemainderPolicy::CarryOver`.
+        pub carried_dust: Balance,
+    }
+
+    /// A payout round processed in batches by `trigger_payout_batch`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PayoutBatch {
+        /// The amounts of the round, calculated when its first batch ran.
+        pub payouts: Vec<PayoutInfo>,
+        /// The index in `payouts` the next batch starts from.
+        pub cursor: u32,
+        /// The amount transferred by the batches so far.
+        pub total: Balance,
+    }
+
+    /// A proposal by the payees to replace the designated payee.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct DesignatedPayeeProposal {
+        /// The account that becomes the designated payee once a majority voted for it.
+        pub candidate: AccountId,
+        /// The block the proposal was opened in.
+        pub proposed_at: BlockNumber,
+        /// The combined shares of the current payees that voted for the proposal so far.
+        pub votes: u128,
+    }
+
+    /// An emergency withdrawal of the distributable balance, scheduled by the admin.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct EmergencyWithdrawal {
+        /// The account that receives the balance.
+        pub to: AccountId,
+        /// The first block in which the withdrawal can be executed.
+        pub executable_at: BlockNumber,
+    }
+
+    /// A payee's vesting schedule: the shares credited to the payee vest linearly over
+    /// `duration` blocks from `start`, and nothing vests before the cliff.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct VestingSchedule {
+        /// The block vesting starts in.
+        pub start: BlockNumber,
+        /// Number of blocks after `start` before anything vests.
+        pub cliff: BlockNumber,
+        /// Number of blocks after `start` until everything has vested.
+        pub duration: BlockNumber,
+    }
+
+    /// An amount streamed to the payees over a number of blocks, started by `start_stream`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Stream {
+        /// What each account receives over the whole stream.
+        pub allocations: Vec<PayoutInfo>,
+        /// The block the stream started in.
+        pub start: BlockNumber,
+        /// Number of blocks over which the allocations accrue.
+        pub duration: BlockNumber,
+    }
+
+    /// A deposit recorded for the account it is attributed to.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Receipt {
+        /// Position of the deposit among the account's deposits, starting at 0.
+        pub deposit_id: u32,
+        /// The block the deposit was made in.
+        pub block: BlockNumber,
+        /// The deposited amount.
+        pub amount: Balance,
+    }
+
+    /// Outcome of each attempted transfer of a payout round, in payee order.
+    pub type PayoutReport = Vec<(AccountId, Result<Balance, Error>)>;
+
+    /// The priority tier of a payee.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PayeeTier {
+        /// Tier 1: paid a fixed amount before anything else is distributed.
+        Fixed(Balance),
+        /// Tier 2: shares whatever is left after the fixed amounts equally (the default).
+        Residual,
+    }
+
+    /// Outcome of the payout of a single asset in `trigger_payout_all_assets`, or of its
+    /// withdrawal in `release_all`.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AssetPayoutResult {
+        /// The PSP22 token contract, or `None` for the native balance.
+        pub token: Option<AccountId>,
+        /// The total amount distributed, or the error that stopped this asset's payout.
+        pub result: Result<Balance, Error>,
+    }
+
+    /// A supported asset and how much of it has been distributed, returned by `list_assets`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AssetInfo {
+        /// The PSP22 token contract, or `None` for the native balance.
+        pub token: Option<AccountId>,
+        /// The total amount of this asset deposited into the contract.
+        pub total_received: Balance,
+        /// The total amount of this asset distributed to the payees.
+        pub total_distributed: Balance,
+    }
+
+    /// Summary of the contract's version and configuration, returned by `contract_info`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractInfo {
+        /// The `VERSION` of the deployed contract.
+        pub version: u32,
+        /// The admin, authorized to configure the contract and trigger payouts.
+        pub designated_payee: AccountId,
+        /// The operator, authorized to trigger payouts only.
+        pub operator: Option<AccountId>,
+        /// The number of registered payees.
+        pub payee_count: u32,
+        /// The contract's total native balance.
+        pub balance: Balance,
+        /// The part of the balance the next `trigger_payout` would distribute.
+        pub distributable: Balance,
+        /// How the balance is divided among the payees.
+        pub split_mode: SplitMode,
+        /// The number of completed payout rounds.
+        pub payout_nonce: u64,
+        /// Minimum number of blocks between two payouts.
+        pub payout_cooldown: BlockNumber,
+        /// The last block in which deposits are accepted, if any.
+        pub deposit_deadline: Option<BlockNumber>,
+        /// The number of approvals required per payout round.
+        pub approval_threshold: u32,
+        /// Whether deposits and payouts are suspended.
+        pub paused: bool,
+        /// The part of the balance kept in the contract by every payout.
+        pub reserve: Balance,
+    }
+
+    /// A payee's entry in a `StateSnapshot`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PayeeSnapshot {
+        /// The payee.
+        pub account: AccountId,
+        /// The payee's tier.
+        pub tier: PayeeTier,
+        /// The payee's share weight.
+        pub share: u128,
+        /// The payee's pending claim.
+        pub pending_claim: Balance,
+    }
+
+    /// The full accounting state of the contract, returned by `export_state`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct StateSnapshot {
+        /// The admin, authorized to configure the contract and trigger payouts.
+        pub designated_payee: AccountId,
+        /// The operator, authorized to trigger payouts only.
+        pub operator: Option<AccountId>,
+        /// The total number of registered payees.
+        pub payee_count: u32,
+        /// The requested page of payees.
+        pub payees: Vec<PayeeSnapshot>,
+        /// The contract's total native balance.
+        pub balance: Balance,
+        /// The part of the balance the next `trigger_payout` would distribute.
+        pub distributable: Balance,
+        /// Sum of all pending claims.
+        pub total_pending: Balance,
+        /// Sum of the balances of all additional pools.
+        pub pooled_balance: Balance,
+        /// How the balance is divided among the payees.
+        pub split_mode: SplitMode,
+        /// The account that receives rounding remainders, if any.
+        pub remainder_recipient: Option<AccountId>,
+        /// The number of completed payout rounds.
+        pub payout_nonce: u64,
+        /// The payout round currently open.
+        pub round_id: u64,
+        /// Whether deposits and payouts are suspended.
+        pub paused: bool,
+        /// The part of the balance kept in the contract by every payout.
+        pub reserve: Balance,
+        /// Everything ever received through deposits.
+        pub total_received: Balance,
+        /// Everything ever transferred out to payees.
+        pub total_distributed: Balance,
+    }
+
+    /// A configuration setting reported by the `ConfigChanged` event.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum ConfigField {
+        /// The deposit deadline (`Option<BlockNumber>`).
+        DepositDeadline,
+        /// The remainder recipient (`Option<AccountId>`).
+        RemainderRecipient,
+        /// The remainder policy (`RemainderPolicy`).
+        RemainderPolicy,
+        /// The maximum deposit (`Option<Balance>`).
+        MaxDeposit,
+        /// The minimum deposit (`Balance`).
+        MinDeposit,
+        /// The payout interval, `poke` minimum balance and tip (`(BlockNumber, Balance, Balance)`).
+        PayoutSchedule,
+        /// The payout signer (`Option<AccountId>`).
+        PayoutSigner,
+        /// The parent splitter (`Option<AccountId>`).
+        ParentSplitter,
+        /// The approval committee and threshold (`(Vec<AccountId>, u32)`).
+        Approvers,
+        /// The balance kept out of payouts (`Balance`).
+        Reserve,
+        /// A payee's tier (`PayeeTier`).
+        Tier(AccountId),
+        /// The amount a payee is owed (`Balance`).
+        Owed(AccountId),
+        /// A payee's claim unlock block (`Option<BlockNumber>`).
+        ClaimLock(AccountId),
+        /// A payee's vesting schedule (`Option<VestingSchedule>`).
+        VestingSchedule(AccountId),
+        /// Whether a payee is a child splitter (`bool`).
+        ChildSplitter(AccountId),
+        /// Whether a payee is paid through its payment hook (`bool`).
+        PaymentHook(AccountId),
+        /// Whether a token is registered (`bool`).
+        Token(AccountId),
+        /// The gas stipend of a payment hook call (`u64`).
+        PaymentHookGasLimit,
+        /// The number of blocks a designated payee proposal stays open (`BlockNumber`).
+        RecoveryPeriod,
+        /// The number of blocks between scheduling and executing an emergency withdrawal
+        /// (`BlockNumber`).
+        EmergencyDelay,
+        /// The maximum number of transfers a single call may make (`u32`).
+        MaxTransfersPerCall,
+        /// The smallest share a payout may pay a payee (`Balance`).
+        MinPayoutPerPayee,
+        /// The minimum number of blocks between two payouts (`BlockNumber`).
+        PayoutCooldown,
+        /// The length of a payout period (`BlockNumber`).
+        PayoutPeriod,
+        /// The number of blocks after which anyone may trigger a payout (`BlockNumber`).
+        TriggerDeadline,
+        /// The cut of a permissionless payout paid to its caller, in basis points (`u32`).
+        TriggerBountyBps,
+    }
+
+    /// Optional settings accepted by the `new_with_config` constructor.
+    #[derive(Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct SplitterConfig {
+        /// Minimum number of blocks between two payouts (0 disables the cooldown).
+        pub payout_cooldown: BlockNumber,
+        /// Length in blocks of the periods in which at most one payout may run (0 disables
+        /// payout periods).
+        pub payout_period: BlockNumber,
+        /// How the contract balance is divided among the payees.
+        pub split_mode: SplitMode,
+        /// An account allowed to trigger payouts without being able to change the configuration.
+        pub operator: Option<AccountId>,
+        /// The last block in which deposits are accepted, if any.
+        pub deposit_deadline: Option<BlockNumber>,
+        /// The ECDSA account whose signatures authorize `trigger_payout_signed`.
+        pub payout_signer: Option<AccountId>,
+        /// Merge repeated accounts in `payees` into a single entry instead of giving them
+        /// one share per occurrence.
+        pub merge_duplicate_payees: bool,
+        /// Split every deposit among the payees as soon as it arrives instead of waiting for
+        /// a payout.
+        pub auto_split: bool,
+        /// Only split value received through `deposit` and its variants, leaving the
+        /// pre-existing balance and direct transfers in the contract.
+        pub split_tracked_only: bool,
+        /// Let every payee withdraw their share at any time with `release`, without waiting for
+        /// a payout.
+        pub pull_payments: bool,
+        /// The account that receives rounding remainders instead of the first payee.
+        pub remainder_recipient: Option<AccountId>,
+        /// The largest value a single deposit may carry, if any.
+        pub max_deposit: Option<Balance>,
+        /// The smallest value a non-empty deposit must carry; values below 1 are raised to 1.
+        pub min_deposit: Balance,
+        /// Minimum number of blocks between committing and executing a payout plan.
+        pub dispute_window: BlockNumber,
+        /// Number of blocks after the last payout from which anyone may `poke` the next one
+        /// (0 disables scheduled payouts).
+        pub payout_interval: BlockNumber,
+        /// The distributable balance must exceed this for `poke` to run a payout.
+        pub poke_min_balance: Balance,
+        /// Paid to the caller of a successful `poke` before the balance is split.
+        pub poke_tip: Balance,
+        /// Number of blocks a proposal to replace the designated payee stays open (0 keeps
+        /// proposals open until they pass).
+        pub recovery_period: BlockNumber,
+    }
+
     /// Defines the storage for the PaymentSplitter contract.
     #[ink(storage)]
     pub struct PaymentSplitter {
+        /// The `VERSION` whose layout the storage was last written with.
+        version: Lazy<u32, ManualKey<STORAGE_VERSION_KEY>>,
         /// A list of `AccountId`s representing the payees who will receive funds.
         payees: Vec<AccountId>,
-        /// The `AccountId` that is authorized to trigger the payout process.
+        /// The `AccountId` that administers the contract: it changes the configuration, rotates
+        /// roles and is authorized to trigger the payout process.
         designated_payee: AccountId,
+        /// An `AccountId` that is only authorized to trigger the payout process.
+        operator: Option<AccountId>,
+        /// Roles granted to accounts other than the designated payee.
+        roles: Mapping<(Role, AccountId), ()>,
+        /// The account proposed by `propose_designated_payee`, waiting to accept the role.
+        pending_designated_payee: Option<AccountId>,
+        /// Whether the admin role was renounced: anyone may trigger payouts and nobody may
+        /// change the configuration.
+        is_permissionless: bool,
+        /// Whether deposits and payouts are suspended by `pause`.
+        paused: bool,
         /// Reentrancy guard.
         locked: bool,
+        /// Number of completed payout rounds, used to coordinate and de-duplicate payouts.
+        payout_nonce: u64,
+        /// Id of the payout round currently open for deposits, starting at 1.
+        round_id: u64,
+        /// Minimum number of blocks between two payouts.
+        payout_cooldown: BlockNumber,
+        /// The block of the last successful payout, if any.
+        last_payout_block: Option<BlockNumber>,
+        /// Length in blocks of the periods in which at most one payout may run (0 disables
+        /// payout periods).
+        payout_period: BlockNumber,
+        /// The block the contract was instantiated in.
+        instantiated_at: BlockNumber,
+        /// Number of blocks without a payout after which anyone may trigger one (0 disables
+        /// the deadline).
+        trigger_deadline: BlockNumber,
+        /// Cut of a permissionless payout paid to its caller, in basis points.
+        trigger_bounty_bps: u32,
+        /// PSP22 token contracts whose balances are split alongside the native balance.
+        registered_tokens: Vec<AccountId>,
+        /// Fast membership lookup for `registered_tokens`.
+        is_registered_token: Mapping<AccountId, ()>,
+        /// Total amount of each PSP22 token deposited through `deposit_token`.
+        token_received: Mapping<AccountId, Balance>,
+        /// Amount of each PSP22 token deposited by each account, keyed by `(token, depositor)`.
+        token_deposited: Mapping<(AccountId, AccountId), Balance>,
+        /// Total amount of each PSP22 token distributed to the payees.
+        token_distributed: Mapping<AccountId, Balance>,
+        /// Tokens withdrawn by each payee through `release_all` since the token's last payout,
+        /// keyed by `(token, payee)`.
+        token_released_early: Mapping<(AccountId, AccountId), Balance>,
+        /// Sum of the `token_released_early` amounts of each token.
+        token_total_released_early: Mapping<AccountId, Balance>,
+        /// Token shares a token payout could not transfer, keyed by `(token, payee)`.
+        token_pending_claims: Mapping<(AccountId, AccountId), Balance>,
+        /// Sum of the `token_pending_claims` amounts of each token.
+        token_total_pending: Mapping<AccountId, Balance>,
+        /// The block of each token's last payout, for the payout cooldown.
+        token_last_payout: Mapping<AccountId, BlockNumber>,
+        /// Payees that are themselves splitters and get told to distribute after being paid.
+        child_splitters: Mapping<AccountId, ()>,
+        /// Payee contracts paid by calling their `on_payment_received` message.
+        payment_hooks: Mapping<AccountId, ()>,
+        /// Reference-time limit of a single `on_payment_received` hook call.
+        payment_hook_gas_limit: u64,
+        /// The parent splitter allowed to call `notify_and_distribute` on this contract.
+        parent_splitter: Option<AccountId>,
+        /// Fixed amounts of the tier-1 payees; payees without an entry are tier 2.
+        fixed_amounts: Mapping<AccountId, Balance>,
+        /// Weights of the tier-2 payees in the proportional split; payees without an entry
+        /// weigh 1.
+        shares: Mapping<AccountId, u128>,
+        /// Sum of the weights of all payees.
+        total_shares: u128,
+        /// How the contract balance is divided among the payees.
+        split_mode: SplitMode,
+        /// In owed mode, the amount each payee is still owed.
+        owed: Mapping<AccountId, Balance>,
+        /// In contributions mode, what each depositor deposited since the last payout.
+        contributions: Mapping<AccountId, Balance>,
+        /// Sum of all `contributions`.
+        total_contributions: Balance,
+        /// Payees added automatically by their deposit in contributions mode, removed again when
+        /// the round is paid out.
+        contributors: Mapping<AccountId, ()>,
+        /// Amounts paid early through `release_to` in the current round, deducted from the
+        /// payee's share in the next payout.
+        released_early: Mapping<AccountId, Balance>,
+        /// Sum of all `released_early` amounts.
+        total_released_early: Balance,
+        /// Additional payout pools; pool 0 is the contract's own payee list and is not stored here.
+        pools: Mapping<u32, Pool>,
+        /// The id the next created pool will get.
+        next_pool_id: u32,
+        /// Sum of the balances of all additional pools, excluded from pool 0's payouts.
+        pooled_balance: Balance,
+        /// Amounts whose transfer failed during a payout, claimable by the payee via `claim`.
+        pending_claims: Mapping<AccountId, Balance>,
+        /// Sum of all pending claims, excluded from future payouts.
+        total_pending: Balance,
+        /// Accounts with a pending claim, in the order their first transfer failed.
+        retry_queue: Vec<AccountId>,
+        /// Payees that may not receive funds before the given block.
+        claim_unlock_block: Mapping<AccountId, BlockNumber>,
+        /// Payees whose shares accrue instead of being transferred.
+        frozen: Mapping<AccountId, ()>,
+        /// The shares accrued by frozen payees.
+        accrued: Mapping<AccountId, Balance>,
+        /// Sum of all `accrued` amounts, excluded from future payouts.
+        total_accrued: Balance,
+        /// Payees whose shares vest instead of being transferred.
+        vesting_schedules: Mapping<AccountId, VestingSchedule>,
+        /// Everything credited to each payee's vesting schedule.
+        vesting_credited: Mapping<AccountId, Balance>,
+        /// What each payee has withdrawn from their vesting schedule.
+        vesting_released: Mapping<AccountId, Balance>,
+        /// Sum of the credited amounts not withdrawn yet, excluded from future payouts.
+        total_vesting: Balance,
+        /// The current or last payout stream, if any.
+        stream: Option<Stream>,
+        /// What each account has claimed from the current stream.
+        stream_claimed: Mapping<AccountId, Balance>,
+        /// Sum of the stream allocations not claimed yet, excluded from future payouts.
+        total_streaming: Balance,
+        /// The last block in which deposits are accepted, if any.
+        deposit_deadline: Option<BlockNumber>,
+        /// The committee whose approvals are required before a payout.
+        approvers: Vec<AccountId>,
+        /// The number of approvals required per payout round (0 disables the requirement).
+        approval_threshold: u32,
+        /// Approvals given, keyed by payout nonce and approver.
+        approvals: Mapping<(u64, AccountId), ()>,
+        /// Number of approvals given per payout nonce.
+        approval_count: Mapping<u64, u32>,
+        /// Open proposals to replace the designated payee.
+        designated_payee_proposals: Mapping<u64, DesignatedPayeeProposal>,
+        /// Votes given, keyed by proposal id and payee.
+        designated_payee_votes: Mapping<(u64, AccountId), ()>,
+        /// The proposals each payee voted for, so the votes can be dropped with the payee.
+        voted_proposals: Mapping<AccountId, Vec<u64>>,
+        /// The id the next designated payee proposal will get.
+        next_proposal_id: u64,
+        /// Number of blocks a designated payee proposal stays open (0 disables expiry).
+        recovery_period: BlockNumber,
+        /// Number of blocks between scheduling and executing an emergency withdrawal.
+        emergency_delay: BlockNumber,
+        /// A shorter emergency delay and the block it takes effect at, once the current delay
+        /// has passed.
+        pending_emergency_delay: Option<(BlockNumber, BlockNumber)>,
+        /// The scheduled emergency withdrawal, if any.
+        emergency_withdrawal: Option<EmergencyWithdrawal>,
+        /// The ECDSA account (BLAKE2 hash of the compressed public key) whose signatures
+        /// authorize `trigger_payout_signed`.
+        payout_signer: Option<AccountId>,
+        /// Whether `deposit` immediately splits the deposited value among the payees.
+        auto_split: bool,
+        /// Whether payouts are limited to `accrued_since_last_payout`.
+        split_tracked_only: bool,
+        /// Whether payees may withdraw their share at any time with `release`.
+        pull_payments: bool,
+        /// Value received through deposits that was not split yet.
+        accrued_since_last_payout: Balance,
+        /// Where rounding remainders go.
+        remainder_policy: RemainderPolicy,
+        /// Remainder left in the contract by the last payout round under
+        /// `RemainderPolicy::CarryOver`, split with the next payout.
+        carried_dust: Balance,
+        /// Everything ever received through `deposit` and `deposit_to_pool`.
+        total_received: Balance,
+        /// Everything deposited per account the deposits are attributed to.
+        deposited: Mapping<AccountId, Balance>,
+        /// Every deposit per account it is attributed to, keyed by account and `deposit_id`.
+        receipts: Mapping<(AccountId, u32), Receipt>,
+        /// Number of receipts stored per account.
+        receipt_count: Mapping<AccountId, u32>,
+        /// The largest value a single deposit may carry; `None` means unlimited.
+        max_deposit: Option<Balance>,
+        /// The smallest value a deposit must carry, at least 1.
+        min_deposit: Balance,
+        /// Committed payout plans waiting for execution.
+        payout_plans: Mapping<u64, PayoutPlan>,
+        /// The id the next committed payout plan will get.
+        next_plan_id: u64,
+        /// Minimum number of blocks between committing and executing a payout plan.
+        dispute_window: BlockNumber,
+        /// Number of blocks after the last payout from which anyone may `poke` the next one.
+        payout_interval: BlockNumber,
+        /// The distributable balance must exceed this for `poke` to run a payout.
+        poke_min_balance: Balance,
+        /// Paid to the caller of a successful `poke`.
+        poke_tip: Balance,
+        /// Balance kept in the contract and excluded from payouts, initially the chain's
+        /// minimum balance.
+        reserve: Balance,
+        /// Sum of the totals of all committed payout plans, excluded from other payouts.
+        committed_total: Balance,
+        /// The batched payout round in progress, if any.
+        payout_batch: Option<PayoutBatch>,
+        /// Maximum number of transfers a single payout call may make (0 means unlimited).
+        max_transfers_per_call: u32,
+        /// Smallest share a payout may pay a payee (0 means no minimum).
+        min_payout_per_payee: Balance,
+        /// Everything ever transferred to each account by payouts, releases and claims.
+        released: Mapping<AccountId, Balance>,
+        /// Everything ever transferred out, including released claims and `poke` tips.
+        total_distributed: Balance,
     }
 
     /// An event emitted when funds are deposited into the contract.
     #[ink::event]
     pub struct Deposit {
-        /// The `AccountId` that deposited the funds.
+        /// The `AccountId` the deposit is attributed to.
         #[ink(topic)]
         pub from: AccountId,
+        /// The `AccountId` that actually sent the funds.
+        pub caller: AccountId,
         /// The amount of funds deposited.
         pub value: Balance,
+        /// The payout round the deposit will be paid out in.
+        #[ink(topic)]
+        pub round_id: u64,
     }
 
-    impl PaymentSplitter {
-        /// Constructor to initialize the PaymentSplitter contract.
-        ///
-        /// This constructor sets up the contract with a list of payees and an authorized payee.
-        ///
-        /// # Arguments
-        ///
-        /// * `payees`: A vector of `AccountId`s representing the payees who will receive payments.
-        /// * `designated_payee`: The `AccountId` that is authorized to trigger the payout.
-        ///
-        #[ink(constructor)]
-        pub fn new(payees: Vec<AccountId>, designated_payee: AccountId) -> Self {
-            Self {
-                payees,
-                designated_payee,
-                locked: false,
-            }
-        }
+    /// An event emitted when a payee is added after deployment.
+    #[ink::event]
+    pub struct PayeeAdded {
+        /// The new payee.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The weight of the new payee.
+        pub share: u128,
+    }
 
-        /// Allows anyone to deposit funds into the contract.
-        ///
-        /// The deposited amount is added to the contract's balance.
-        /// Emits a `Deposit` event when funds are received, recording the depositor and the amount.
-        ///
-        /// # Errors
-        ///
-        /// * `ZeroShare`: If the transferred value is zero.
-        ///
-        #[ink(message, payable)]
-        pub fn deposit(&self) -> Result<(), Error> {
-            let transferred_value = self.env().transferred_value();
-            if transferred_value == 0 {
-                return Err(Error::ZeroShare);
-            }
-            self.env().emit_event(Deposit {
-                from: self.env().caller(),
-                value: transferred_value,
-            });
-            Ok(())
-        }
+    /// An event emitted when a payee's address is replaced by another one.
+    #[ink::event]
+    pub struct PayeeReplaced {
+        /// The previous address.
+        #[ink(topic)]
+        pub old: AccountId,
+        /// The new address.
+        #[ink(topic)]
+        pub new: AccountId,
+    }
 
-        /// Calculates the payout distribution among the registered payees.
-        ///
-        /// This function determines how much each payee should receive based on the contract's balance.
-        /// The remainder after division is added to the first payee's share.
-        ///
-        /// # Errors
-        ///
-        /// * `Unauthorized`: If the caller is not the `designated_payee`.
-        /// * `NoPayees`: If there are no registered payees.
-        /// * `ZeroShare`: If the total balance is zero or if a calculation error (division by zero) occurs.
-        ///
-        pub fn calculate_payout(&mut self) -> Result<Vec<PayoutInfo>, Error> {
-            self.ensure_caller_is_designated_payee()?;
-            let total_balance = self.env().balance();
-            let num_payees = self.payees.len();
+    /// An event emitted when a payee is frozen.
+    #[ink::event]
+    pub struct PayeeFrozen {
+        /// The frozen payee.
+        #[ink(topic)]
+        pub payee: AccountId,
+    }
 
-            if num_payees == 0 {
-                return Err(Error::NoPayees);
-            }
+    /// An event emitted when a payee is unfrozen.
+    #[ink::event]
+    pub struct PayeeUnfrozen {
+        /// The unfrozen payee.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The accrued amount released to the payee, or parked as a pending claim if the
+        /// transfer failed.
+        pub released: Balance,
+    }
 
-            if total_balance == 0 {
-                return Err(Error::ZeroShare);
-            }
+    /// An event emitted when the whole payee list is replaced by `set_payees`.
+    #[ink::event]
+    pub struct PayeesReplaced {
+        /// The number of payees before the replacement.
+        pub old_count: u32,
+        /// The number of payees after the replacement.
+        pub new_count: u32,
+    }
 
-            // Calculate the share each payee should receive.
-            let share = total_balance.checked_div(num_payees as u128).ok_or(Error::ZeroShare)?;
+    /// An event emitted when the weight of a payee changes.
+    #[ink::event]
+    pub struct SharesUpdated {
+        /// The payee whose weight changed.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The previous weight.
+        pub old: u128,
+        /// The new weight.
+        pub new: u128,
+    }
 
-            if share == 0 {
-                return Err(Error::ZeroShare);
-            }
+    /// An event emitted when a payee is removed.
+    #[ink::event]
+    pub struct PayeeRemoved {
+        /// The removed payee.
+        #[ink(topic)]
+        pub payee: AccountId,
+    }
 
-            // Calculate the remainder after division.
-            let mut remainder = total_balance.saturating_sub(
-                share.checked_mul(num_payees as u128).ok_or(Error::ZeroShare)?
-            );
+    /// An event emitted when the admin role (the designated payee) is handed to another account.
+    #[ink::event]
+    pub struct DesignatedPayeeChanged {
+        /// The previous admin.
+        #[ink(topic)]
+        pub old: AccountId,
+        /// The new admin.
+        #[ink(topic)]
+        pub new: AccountId,
+    }
 
-            let mut payout_info = Vec::new();
-            for (i, payee) in self.payees.iter().enumerate() {
-                // Add the remainder to the first payee's share.
-                let to_transfer = if i == 0 {
-                    share.checked_add(remainder).ok_or(Error::TransferFailed)?
-                } else {
-                    share
-                };
+    /// An event emitted when a payee proposes a new designated payee.
+    #[ink::event]
+    pub struct DesignatedPayeeProposed {
+        /// The id of the proposal.
+        #[ink(topic)]
+        pub proposal_id: u64,
+        /// The payee that opened the proposal.
+        #[ink(topic)]
+        pub proposer: AccountId,
+        /// The proposed designated payee.
+        pub candidate: AccountId,
+    }
 
-                payout_info.push(PayoutInfo {
-                    payee: *payee,
-                    amount: to_transfer,
-                });
+    /// An event emitted when a payee votes for a designated payee proposal.
+    #[ink::event]
+    pub struct DesignatedPayeeVoted {
+        /// The id of the proposal.
+        #[ink(topic)]
+        pub proposal_id: u64,
+        /// The voting payee.
+        #[ink(topic)]
+        pub voter: AccountId,
+        /// The combined shares of the current payees that voted for the proposal so far.
+        pub votes: u128,
+    }
 
-                // Only add remainder to first payee.
-                remainder = 0;
-            }
-            Ok(payout_info)
-        }
+    /// An event emitted when a role is granted to an account.
+    #[ink::event]
+    pub struct RoleGranted {
+        /// The granted role.
+        pub role: Role,
+        /// The account that received the role.
+        #[ink(topic)]
+        pub account: AccountId,
+        /// The account that granted it.
+        pub sender: AccountId,
+    }
 
-        /// Triggers the actual payout process based on the payout distribution calculated by `calculate_payout`.
-        ///
-        /// Only the `designated_payee` is authorized to call this function.
-        /// Transfers the funds to each payee based on the `PayoutInfo` provided.
-        ///
-        /// # Errors
-        ///
-        /// * `Unauthorized`: If the caller is not the `designated_payee`.
-        /// * `TransferFailed`: If the transfer of funds to a payee fails.
-        ///
-        #[ink(message)]
-        pub fn trigger_payout(&mut self) -> Result<(), Error> {
-            self.ensure_caller_is_designated_payee()?;
-            self.ensure_reentrancy_guard_not_locked()?;
+    /// An event emitted when a role is revoked from an account.
+    #[ink::event]
+    pub struct RoleRevoked {
+        /// The revoked role.
+        pub role: Role,
+        /// The account that lost the role.
+        #[ink(topic)]
+        pub account: AccountId,
+        /// The account that revoked it.
+        pub sender: AccountId,
+    }
 
-            self.locked = true;
-            let payout_info = self.calculate_payout()?;
+    /// An event emitted when deposits and payouts are suspended.
+    #[ink::event]
+    pub struct Paused {
+        /// The account that paused the contract.
+        #[ink(topic)]
+        pub account: AccountId,
+    }
 
-            for info in payout_info {
-                self
-                    .env()
-                    .transfer(info.payee, info.amount)
-                    .map_err(|_| Error::TransferFailed)?;
-            }
-            self.locked = false;
-            Ok(())
-        }
+    /// An event emitted when deposits and payouts are resumed.
+    #[ink::event]
+    pub struct Unpaused {
+        /// The account that unpaused the contract.
+        #[ink(topic)]
+        pub account: AccountId,
+    }
 
-        /// Helper function to check if the caller is the designated payee.
-        fn ensure_caller_is_designated_payee(&self) -> Result<(), Error> {
-            if self.env().caller() != self.designated_payee {
-                return Err(Error::Unauthorized);
-            }
-            Ok(())
-        }
+    /// An event emitted when the admin schedules an emergency withdrawal, so payees can object
+    /// before it becomes executable.
+    #[ink::event]
+    pub struct EmergencyWithdrawalScheduled {
+        /// The account that will receive the balance.
+        #[ink(topic)]
+        pub to: AccountId,
+        /// The first block in which the withdrawal can be executed.
+        pub executable_at: BlockNumber,
+    }
 
-        /// Helper function to check the reentrancy guard.
-        fn ensure_reentrancy_guard_not_locked(&self) -> Result<(), Error> {
-            if self.locked {
-                return Err(Error::ReentrancyGuardLocked);
-            }
-            Ok(())
-        }
+    /// An event emitted when a scheduled emergency withdrawal is cancelled.
+    #[ink::event]
+    pub struct EmergencyWithdrawalCancelled {
+        /// The account that would have received the balance.
+        #[ink(topic)]
+        pub to: AccountId,
     }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use ink::env::{
-            test::{
-                default_accounts,
-                set_caller,
-                set_value_transferred,
-                get_account_balance,
-                set_account_balance,
-            },
-            DefaultEnvironment,
-        };
-        use ink::codegen::Env;
+    /// An event emitted when an emergency withdrawal is executed.
+    #[ink::event]
+    pub struct EmergencyWithdrawn {
+        /// The account that received the balance.
+        #[ink(topic)]
+        pub to: AccountId,
+        /// The amount transferred.
+        pub amount: Balance,
+    }
 
-        // Helper function to get the current balance of an account.
+    /// An event emitted when the contract code is replaced by `upgrade`.
+    #[ink::event]
+    pub struct CodeUpgraded {
+        /// The hash of the new code.
+        #[ink(topic)]
+        pub code_hash: Hash,
+        /// The account that performed the upgrade.
+        pub sender: AccountId,
+    }
+
+    /// An event emitted right before the contract is removed by `terminate`.
+    #[ink::event]
+    pub struct Terminated {
+        /// The account that receives the residual balance.
+        #[ink(topic)]
+        pub beneficiary: AccountId,
+        /// The amount transferred to the payees by the final payout.
+        pub final_payout: Balance,
+    }
+
+    /// An event emitted when `migrate` brings the storage to the current layout.
+    #[ink::event]
+    pub struct Migrated {
+        /// The storage version before the migration.
+        pub from: u32,
+        /// The storage version after the migration.
+        pub to: u32,
+    }
+
+    /// An event emitted when the designated payee proposes a successor.
+    #[ink::event]
+    pub struct DesignationProposed {
+        /// The current designated payee.
+        #[ink(topic)]
+        pub current: AccountId,
+        /// The account that has to accept the role.
+        #[ink(topic)]
+        pub proposed: AccountId,
+    }
+
+    /// An event emitted when a pending designated payee proposal is withdrawn.
+    #[ink::event]
+    pub struct DesignationProposalCancelled {
+        /// The account that was proposed.
+        #[ink(topic)]
+        pub proposed: AccountId,
+    }
+
+    /// An event emitted when the designated payee renounces the admin role for good.
+    #[ink::event]
+    pub struct DesignationRenounced {
+        /// The last admin.
+        #[ink(topic)]
+        pub designated_payee: AccountId,
+    }
+
+    /// An event emitted when the operator role changes.
+    #[ink::event]
+    pub struct OperatorChanged {
+        /// The previous operator, if any.
+        #[ink(topic)]
+        pub old: Option<AccountId>,
+        /// The new operator, if any.
+        #[ink(topic)]
+        pub new: Option<AccountId>,
+    }
+
+    /// An event emitted when a configuration setting changes.
+    #[ink::event]
+    pub struct ConfigChanged {
+        /// The setting, including the account it belongs to for per-account settings.
+        #[ink(topic)]
+        pub field: ConfigField,
+        /// The SCALE-encoded previous value.
+        pub old: Vec<u8>,
+        /// The SCALE-encoded new value.
+        pub new: Vec<u8>,
+    }
+
+    /// An event emitted when funds are deposited into an additional payout pool.
+    #[ink::event]
+    pub struct PoolDeposit {
+        /// The pool the funds were deposited into.
+        #[ink(topic)]
+        pub pool_id: u32,
+        /// The `AccountId` that deposited the funds.
+        #[ink(topic)]
+        pub from: AccountId,
+        /// The amount of funds deposited.
+        pub value: Balance,
+    }
+
+    /// An event emitted when an additional payout pool has been paid out.
+    #[ink::event]
+    pub struct PoolPayout {
+        /// The pool that was paid out.
+        #[ink(topic)]
+        pub pool_id: u32,
+        /// The total amount distributed.
+        pub total: Balance,
+        /// The number of payees that took part in the payout.
+        pub num_payees: u32,
+    }
+
+    /// An event emitted when a payout plan has been committed.
+    #[ink::event]
+    pub struct PayoutCommitted {
+        /// The id of the plan.
+        #[ink(topic)]
+        pub plan_id: u64,
+        /// The total amount the plan distributes.
+        pub total: Balance,
+    }
+
+    /// An event emitted when a committed payout plan has been cancelled.
+    #[ink::event]
+    pub struct PayoutCancelled {
+        /// The id of the cancelled plan.
+        #[ink(topic)]
+        pub plan_id: u64,
+    }
+
+    /// An event emitted for every successful transfer to a payee.
+    #[ink::event]
+    pub struct PaymentReleased {
+        /// The payee that received the funds.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The amount transferred.
+        pub amount: Balance,
+        /// The payout round, or for a claim withdrawal the round open at the time.
+        #[ink(topic)]
+        pub round_id: u64,
+    }
+
+    /// An event emitted when a transfer to a payee failed and the amount was parked as a
+    /// pending claim instead.
+    #[ink::event]
+    pub struct PaymentParked {
+        /// The payee the amount is owed to.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The amount credited to the payee's pending claim.
+        pub amount: Balance,
+        /// The payout round in which the transfer failed.
+        #[ink(topic)]
+        pub round_id: u64,
+    }
+
+    /// An event emitted when an approver approves the upcoming payout round.
+    #[ink::event]
+    pub struct PayoutApproved {
+        /// The approver.
+        #[ink(topic)]
+        pub approver: AccountId,
+        /// The payout nonce of the approved round.
+        pub nonce: u64,
+        /// The number of approvals for the round so far.
+        pub approvals: u32,
+    }
+
+    /// An event emitted when a payout round has been completed.
+    #[ink::event]
+    pub struct PayoutTriggered {
+        /// The id of the completed round.
+        #[ink(topic)]
+        pub round_id: u64,
+        /// The payout nonce the round was executed under.
+        pub nonce: u64,
+        /// The total amount distributed in the round.
+        pub total: Balance,
+        /// The number of payees that took part in the round.
+        pub num_payees: u32,
+    }
+
+    /// An event emitted when the caller of a permissionless payout has been paid the trigger
+    /// bounty.
+    #[ink::event]
+    pub struct TriggerBountyPaid {
+        /// The id of the round the bounty was paid from.
+        #[ink(topic)]
+        pub round_id: u64,
+        /// The caller that triggered the payout.
+        #[ink(topic)]
+        pub caller: AccountId,
+        /// The bounty transferred to the caller.
+        pub amount: Balance,
+    }
+
+    /// An event emitted when a payout round or a pool payout starts under
+    /// `RemainderPolicy::RandomPayee`, naming the payee drawn to receive the rounding remainder.
+    #[ink::event]
+    pub struct RemainderRecipientDrawn {
+        /// The id of the round.
+        #[ink(topic)]
+        pub round_id: u64,
+        /// The pool paid out, 0 for the contract's own payee list.
+        #[ink(topic)]
+        pub pool_id: u32,
+        /// The payee that receives the remainder.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// Whether `payee` was drawn at random; `false` if the chain's randomness source
+        /// reported an error and the first payee was taken instead.
+        pub random: bool,
+    }
+
+    /// An event emitted when an amount has been split among a subset of the payees.
+    #[ink::event]
+    pub struct SubsetPayoutTriggered {
+        /// The total amount transferred.
+        pub total: Balance,
+        /// The number of payees the amount was split among.
+        pub num_payees: u32,
+    }
+
+    /// An event emitted when a payout stream has been started.
+    #[ink::event]
+    pub struct StreamStarted {
+        /// The total amount allocated to the stream.
+        pub total: Balance,
+        /// The block the stream started in.
+        pub start: BlockNumber,
+        /// Number of blocks over which the allocations accrue.
+        pub duration: BlockNumber,
+    }
+
+    /// An event emitted when the last batch of a batched payout round has been processed.
+    #[ink::event]
+    pub struct PayoutCompleted {
+        /// The id of the completed round.
+        #[ink(topic)]
+        pub round_id: u64,
+        /// The total amount distributed over all batches of the round.
+        pub total: Balance,
+        /// The number of payees that took part in the round.
+        pub num_payees: u32,
+    }
+
+    /// An event emitted after a child splitter has been asked to distribute its share.
+    #[ink::event]
+    pub struct ChildSplitterNotified {
+        /// The child splitter contract.
+        #[ink(topic)]
+        pub child: AccountId,
+        /// Whether the nested distribution succeeded.
+        pub success: bool,
+    }
+
+    /// An event emitted when a payee's `on_payment_received` hook failed and the share was
+    /// transferred without it.
+    #[ink::event]
+    pub struct PaymentHookFailed {
+        /// The payee whose hook failed.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The amount that was to be delivered through the hook.
+        pub amount: Balance,
+    }
+
+    /// An event emitted when the balance of a PSP22 token has been split among the payees.
+    #[ink::event]
+    pub struct TokenPayout {
+        /// The PSP22 token contract that was paid out.
+        #[ink(topic)]
+        pub token: AccountId,
+        /// The total amount of tokens distributed.
+        pub total: Balance,
+        /// The number of payees that took part in the payout.
+        pub num_payees: u32,
+    }
+
+    /// An event emitted when a token payout could not transfer a payee's share and parked it
+    /// as a token claim instead.
+    #[ink::event]
+    pub struct TokenPaymentParked {
+        /// The PSP22 token contract.
+        #[ink(topic)]
+        pub token: AccountId,
+        /// The payee the tokens are owed to.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The amount of tokens credited to the payee's token claim.
+        pub amount: Balance,
+    }
+
+    /// An event emitted when PSP22 tokens are deposited through `deposit_token`.
+    #[ink::event]
+    pub struct TokenDeposit {
+        /// The PSP22 token contract.
+        #[ink(topic)]
+        pub token: AccountId,
+        /// The account the tokens were pulled from.
+        #[ink(topic)]
+        pub from: AccountId,
+        /// The amount of tokens deposited.
+        pub amount: Balance,
+    }
+
+    /// An event emitted when tokens the splitter does not manage are swept out by the admin.
+    #[ink::event]
+    pub struct TokenRescued {
+        /// The PSP22 token contract.
+        #[ink(topic)]
+        pub token: AccountId,
+        /// The account that received the tokens.
+        #[ink(topic)]
+        pub to: AccountId,
+        /// The amount of tokens transferred.
+        pub amount: Balance,
+    }
+
+    /// An event emitted when a payee withdraws their share of a PSP22 token ahead of its payout.
+    #[ink::event]
+    pub struct TokenReleased {
+        /// The PSP22 token contract.
+        #[ink(topic)]
+        pub token: AccountId,
+        /// The payee that received the tokens.
+        #[ink(topic)]
+        pub payee: AccountId,
+        /// The amount of tokens transferred.
+        pub amount: Balance,
+    }
+
+    impl PaymentSplitter {
+        /// Constructor to initialize the PaymentSplitter contract.
+        ///
+        /// This constructor sets up the contract with a list of payees and an authorized payee.
+        ///
+        /// # Arguments
+        ///
+        /// * `payees`: A vector of `AccountId`s representing the payees who will receive payments.
+        /// * `designated_payee`: The `AccountId` that is authorized to trigger the payout.
+        ///
+        #[ink(constructor)]
+        pub fn new(payees: Vec<AccountId>, designated_payee: AccountId) -> Self {
+            Self::new_with_config(payees, designated_payee, SplitterConfig::default())
+        }
+
+        /// Constructor to initialize the PaymentSplitter contract and fund it in the same call.
+        ///
+        /// Behaves like `new`; the value transferred with the instantiation is recorded as a
+        /// deposit of the instantiator, as if they called `deposit` right after. Instantiating
+        /// without value is allowed and records nothing.
+        ///
+        /// # Arguments
+        ///
+        /// * `payees`: A vector of `AccountId`s representing the payees who will receive payments.
+        /// * `designated_payee`: The `AccountId` that is authorized to trigger the payout.
+        ///
+        #[ink(constructor, payable)]
+        pub fn new_funded(payees: Vec<AccountId>, designated_payee: AccountId) -> Self {
+            let mut contract = Self::new(payees, designated_payee);
+            let transferred_value = Self::env().transferred_value();
+            if transferred_value > 0 {
+                let caller = Self::env().caller();
+                contract.record_deposit(caller, transferred_value);
+                contract.accrued_since_last_payout = transferred_value;
+                Self::env().emit_event(Deposit {
+                    from: caller,
+                    caller,
+                    value: transferred_value,
+                    round_id: contract.round_id,
+                });
+            }
+            contract
+        }
+
+        /// Constructor to initialize the PaymentSplitter contract with weighted payees.
+        ///
+        /// Behaves like `new`, but the balance is split among the payees in proportion to their
+        /// weights instead of equally. An account listed more than once gets a single entry with
+        /// the sum of its weights, and a payee with weight 0 receives nothing.
+        ///
+        /// # Arguments
+        ///
+        /// * `shares`: Pairs of a payee and its weight.
+        /// * `designated_payee`: The `AccountId` that is authorized to trigger the payout.
+        ///
+        #[ink(constructor)]
+        pub fn new_weighted(shares: Vec<(AccountId, u128)>, designated_payee: AccountId) -> Self {
+            let mut merged: Vec<(AccountId, u128)> = Vec::new();
+            for (payee, weight) in shares {
+                match merged.iter_mut().find(|(merged_payee, _)| *merged_payee == payee) {
+                    Some((_, merged_weight)) => *merged_weight = merged_weight.saturating_add(weight),
+                    None => merged.push((payee, weight)),
+                }
+            }
+            let payees = merged.iter().map(|(payee, _)| *payee).collect();
+            let mut contract = Self::new(payees, designated_payee);
+            contract.total_shares = 0;
+            for (payee, weight) in merged {
+                contract.shares.insert(payee, &weight);
+                contract.total_shares = contract.total_shares.saturating_add(weight);
+            }
+            contract
+        }
+
+        /// Constructor to initialize the PaymentSplitter contract with shares in basis points.
+        ///
+        /// Behaves like `new_weighted` with the basis points as weights (2500 is 25%), but the
+        /// instantiation fails unless they add up to exactly `TOTAL_BASIS_POINTS`.
+        ///
+        /// # Arguments
+        ///
+        /// * `shares`: Pairs of a payee and its basis points.
+        /// * `designated_payee`: The `AccountId` that is authorized to trigger the payout.
+        ///
+        /// # Errors
+        ///
+        /// * `SharesMismatch`: If the basis points do not add up to `TOTAL_BASIS_POINTS`.
+        ///
+        #[ink(constructor)]
+        pub fn new_basis_points(shares: Vec<(AccountId, u32)>, designated_payee: AccountId) -> Result<Self, Error> {
+            let sum = shares
+                .iter()
+                .fold(0u128, |sum, (_, basis_points)| sum.saturating_add(*basis_points as u128));
+            if sum != TOTAL_BASIS_POINTS as u128 {
+                return Err(Error::SharesMismatch);
+            }
+            let weights = shares
+                .into_iter()
+                .map(|(payee, basis_points)| (payee, basis_points as u128))
+                .collect();
+            Ok(Self::new_weighted(weights, designated_payee))
+        }
+
+        /// Constructor to initialize the PaymentSplitter contract with additional settings.
+        ///
+        /// # Arguments
+        ///
+        /// * `payees`: A vector of `AccountId`s representing the payees who will receive payments.
+        /// * `designated_payee`: The `AccountId` that is authorized to trigger the payout.
+        /// * `config`: Optional settings, see `SplitterConfig`.
+        ///
+        #[ink(constructor)]
+        pub fn new_with_config(
+            payees: Vec<AccountId>,
+            designated_payee: AccountId,
+            config: SplitterConfig
+        ) -> Self {
+            let payees = if config.merge_duplicate_payees {
+                let mut merged: Vec<AccountId> = Vec::new();
+                for payee in payees {
+                    if !merged.contains(&payee) {
+                        merged.push(payee);
+                    }
+                }
+                merged
+            } else {
+                payees
+            };
+            let total_shares = payees.len() as u128;
+            let mut contract = Self {
+                version: Lazy::new(),
+                payees,
+                designated_payee,
+                operator: config.operator,
+                roles: Mapping::default(),
+                pending_designated_payee: None,
+                is_permissionless: false,
+                paused: false,
+                locked: false,
+                payout_nonce: 0,
+                round_id: 1,
+                payout_cooldown: config.payout_cooldown,
+                last_payout_block: None,
+                payout_period: config.payout_period,
+                instantiated_at: Self::env().block_number(),
+                trigger_deadline: 0,
+                trigger_bounty_bps: 0,
+                registered_tokens: Vec::new(),
+                is_registered_token: Mapping::default(),
+                token_received: Mapping::default(),
+                token_deposited: Mapping::default(),
+                token_distributed: Mapping::default(),
+                token_released_early: Mapping::default(),
+                token_total_released_early: Mapping::default(),
+                token_pending_claims: Mapping::default(),
+                token_total_pending: Mapping::default(),
+                token_last_payout: Mapping::default(),
+                child_splitters: Mapping::default(),
+                payment_hooks: Mapping::default(),
+                payment_hook_gas_limit: PAYMENT_HOOK_GAS_LIMIT,
+                parent_splitter: None,
+                fixed_amounts: Mapping::default(),
+                shares: Mapping::default(),
+                total_shares,
+                split_mode: config.split_mode,
+                owed: Mapping::default(),
+                contributions: Mapping::default(),
+                total_contributions: 0,
+                contributors: Mapping::default(),
+                released_early: Mapping::default(),
+                total_released_early: 0,
+                pools: Mapping::default(),
+                next_pool_id: 1,
+                pooled_balance: 0,
+                pending_claims: Mapping::default(),
+                retry_queue: Vec::new(),
+                total_pending: 0,
+                claim_unlock_block: Mapping::default(),
+                frozen: Mapping::default(),
+                accrued: Mapping::default(),
+                total_accrued: 0,
+                vesting_schedules: Mapping::default(),
+                vesting_credited: Mapping::default(),
+                vesting_released: Mapping::default(),
+                total_vesting: 0,
+                stream: None,
+                stream_claimed: Mapping::default(),
+                total_streaming: 0,
+                deposit_deadline: config.deposit_deadline,
+                approvers: Vec::new(),
+                approval_threshold: 0,
+                approvals: Mapping::default(),
+                approval_count: Mapping::default(),
+                designated_payee_proposals: Mapping::default(),
+                designated_payee_votes: Mapping::default(),
+                voted_proposals: Mapping::default(),
+                next_proposal_id: 1,
+                recovery_period: config.recovery_period,
+                emergency_delay: DEFAULT_EMERGENCY_DELAY,
+                pending_emergency_delay: None,
+                emergency_withdrawal: None,
+                payout_signer: config.payout_signer,
+                auto_split: config.auto_split,
+                split_tracked_only: config.split_tracked_only,
+                pull_payments: config.pull_payments,
+                accrued_since_last_payout: 0,
+                remainder_policy: config.remainder_recipient
+                    .map_or(RemainderPolicy::FirstPayee, RemainderPolicy::Treasury),
+                carried_dust: 0,
+                total_received: 0,
+                deposited: Mapping::default(),
+                receipts: Mapping::default(),
+                receipt_count: Mapping::default(),
+                max_deposit: config.max_deposit,
+                min_deposit: config.min_deposit.max(1),
+                payout_plans: Mapping::default(),
+                next_plan_id: 1,
+                dispute_window: config.dispute_window,
+                payout_interval: config.payout_interval,
+                poke_min_balance: config.poke_min_balance,
+                poke_tip: config.poke_tip,
+                reserve: Self::env().minimum_balance(),
+                committed_total: 0,
+                payout_batch: None,
+                max_transfers_per_call: 0,
+                min_payout_per_payee: 0,
+                released: Mapping::default(),
+                total_distributed: 0,
+            };
+            contract.version.set(&VERSION);
+            contract
+        }
+
+        /// Deposits funds on behalf of `beneficiary`.
+        ///
+        /// Behaves like `deposit`, but the deposit is attributed to `beneficiary`: it is counted
+        /// in `deposited_by(beneficiary)`, credited as their contribution, and the `Deposit`
+        /// event names them as `from` with the caller in the `caller` field.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidAccount`: If `beneficiary` is the all-zero account.
+        /// * Any error of `deposit`.
+        ///
+        #[ink(message, payable)]
+        pub fn deposit_for(&mut self, beneficiary: AccountId) -> Result<(), Error> {
+            if beneficiary == AccountId::from([0; 32]) {
+                return Err(Error::InvalidAccount);
+            }
+            self.receive_deposit(beneficiary, self.env().transferred_value())
+        }
+
+        /// Deposits exactly `expected` and refunds any surplus to the caller.
+        ///
+        /// Behaves like `deposit` for `expected`: only that amount is recorded and reported in
+        /// the `Deposit` event, while `transferred_value - expected` is transferred back to the
+        /// caller in the same call.
+        ///
+        /// # Errors
+        ///
+        /// * `InsufficientDeposit`: If less than `expected` was transferred. This reverts the
+        ///   call on-chain, so the transferred value goes back to the sender.
+        /// * `ReentrancyGuardLocked`: If called while a payout or refund is in progress.
+        /// * `TransferFailed`: If the refund cannot be transferred.
+        /// * Any error of `deposit`.
+        ///
+        #[ink(message, payable)]
+        pub fn deposit_exact(&mut self, expected: Balance) -> Result<(), Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            let transferred_value = self.env().transferred_value();
+            if transferred_value < expected {
+                return Err(Error::InsufficientDeposit);
+            }
+            let caller = self.env().caller();
+            self.receive_deposit(caller, expected)?;
+
+            let surplus = transferred_value.saturating_sub(expected);
+            if surplus > 0 {
+                self.with_reentrancy_guard(|contract| contract.transfer_to(caller, surplus))?;
+            }
+            Ok(())
+        }
+
+        /// Returns everything deposited by or attributed to `account`.
+        #[ink(message)]
+        pub fn deposited_by(&self, account: AccountId) -> Balance {
+            self.deposited.get(account).unwrap_or(0)
+        }
+
+        /// Returns the receipts of the deposits attributed to `account`, oldest first.
+        ///
+        /// At most `MAX_RECEIPTS_PER_PAGE` receipts from `offset` are returned, so the history
+        /// is paged with `offset` and `limit`; `deposit_count` tells how many there are.
+        ///
+        /// Every deposit permanently stores one 24-byte receipt (plus its storage item
+        /// overhead), paid for by the depositor through the chain's storage deposit.
+        #[ink(message)]
+        pub fn deposits_of(&self, account: AccountId, offset: u32, limit: u32) -> Vec<Receipt> {
+            let end = offset
+                .saturating_add(limit.min(MAX_RECEIPTS_PER_PAGE))
+                .min(self.deposit_count(account));
+            (offset..end)
+                .filter_map(|deposit_id| self.receipts.get((account, deposit_id)))
+                .collect()
+        }
+
+        /// Returns the number of deposits attributed to `account`.
+        #[ink(message)]
+        pub fn deposit_count(&self, account: AccountId) -> u32 {
+            self.receipt_count.get(account).unwrap_or(0)
+        }
+
+        /// Accepts the transferred value as a deposit attributed to `beneficiary`.
+        fn receive_deposit(&mut self, beneficiary: AccountId, transferred_value: Balance) -> Result<(), Error> {
+            self.ensure_deposits_open()?;
+            if transferred_value == 0 {
+                return Err(Error::ZeroShare);
+            }
+            self.ensure_deposit_within_limits(transferred_value)?;
+            if self.split_mode == SplitMode::Contributions {
+                self.record_contribution(beneficiary, transferred_value)?;
+            }
+            self.record_deposit(beneficiary, transferred_value);
+            self.accrued_since_last_payout = self.accrued_since_last_payout.saturating_add(transferred_value);
+            self.env().emit_event(Deposit {
+                from: beneficiary,
+                caller: self.env().caller(),
+                value: transferred_value,
+                round_id: self.round_id,
+            });
+            if self.auto_split {
+                self.auto_split_deposit(transferred_value)?;
+            }
+            Ok(())
+        }
+
+        /// Adds `amount` to the lifetime totals and the receipts, attributed to `account`.
+        fn record_deposit(&mut self, account: AccountId, amount: Balance) {
+            let deposited = self.deposited.get(account).unwrap_or(0);
+            self.deposited.insert(account, &deposited.saturating_add(amount));
+            self.total_received = self.total_received.saturating_add(amount);
+
+            let deposit_id = self.deposit_count(account);
+            self.receipts.insert((account, deposit_id), &Receipt {
+                deposit_id,
+                block: self.env().block_number(),
+                amount,
+            });
+            self.receipt_count.insert(account, &deposit_id.saturating_add(1));
+        }
+
+        /// Adds `amount` to the contribution of `depositor`, making them a payee for the current
+        /// round if needed.
+        fn record_contribution(&mut self, depositor: AccountId, amount: Balance) -> Result<(), Error> {
+            if !self.payees.contains(&depositor) {
+                if self.payees.len() >= MAX_PAYEES as usize {
+                    return Err(Error::TooManyPayees);
+                }
+                self.payees.push(depositor);
+                self.contributors.insert(depositor, &());
+                self.total_shares = self.total_shares.saturating_add(1);
+                self.env().emit_event(PayeeAdded { payee: depositor, share: 1 });
+            }
+            let contribution = self.contributions.get(depositor).unwrap_or(0);
+            self.contributions.insert(depositor, &contribution.saturating_add(amount));
+            self.total_contributions = self.total_contributions.saturating_add(amount);
+            Ok(())
+        }
+
+        /// Calculates the payout distribution among the registered payees.
+        ///
+        /// This function determines how much each payee should receive based on the contract's balance.
+        /// The remainder after division goes where the remainder policy says, by default to the
+        /// first payee. It is read-only and
+        /// anyone may call it to preview the next payout; the split itself is done by the pure
+        /// functions of the `distribution` module, shared with `trigger_payout`.
+        ///
+        /// # Errors
+        ///
+        /// * `NoPayees`: If there are no registered payees.
+        /// * `NothingDistributable`: If the whole balance is encumbered.
+        /// * `ZeroShare`: If a calculation error (division by zero) occurs.
+        ///
+        #[ink(message)]
+        pub fn calculate_payout(&self) -> Result<Vec<PayoutInfo>, Error> {
+            self.next_payouts()
+        }
+
+        /// Returns exactly the transfers the next `trigger_payout` would make, for payees and
+        /// frontends. Anyone may call it.
+        ///
+        /// # Errors
+        ///
+        /// * Any error of `calculate_payout`.
+        /// * `BelowThreshold`: If a payee's share is below `min_payout_per_payee`.
+        ///
+        #[ink(message)]
+        pub fn payout_preview(&self) -> Result<Vec<PayoutInfo>, Error> {
+            let payouts = self.next_payouts()?;
+            self.ensure_above_payout_threshold(&self.payees, &payouts)?;
+            Ok(payouts)
+        }
+
+        /// Calculates the payout distribution as if the contract's balance were `amount`.
+        ///
+        /// Uses the same calculation as the payout itself, so the encumbered funds (pending
+        /// claims, reserve, ...) are deducted from `amount` first, the tracked deposits limit
+        /// applies, and the split mode, tiers and remainder recipient apply as usual.
+        ///
+        /// # Errors
+        ///
+        /// * `NothingDistributable`: If `amount` does not exceed the encumbered funds.
+        /// * Any error `calculate_payout` would return for that balance.
+        ///
+        #[ink(message)]
+        pub fn preview_for_amount(&self, amount: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            match self.distributable_of(amount) {
+                0 => Err(Error::NothingDistributable),
+                distributable => self.payouts_from(distributable),
+            }
+        }
+
+        /// Calculates the next payout from the current `distributable()` balance.
+        fn next_payouts(&self) -> Result<Vec<PayoutInfo>, Error> {
+            self.payouts_from(self.nonzero_distributable()?)
+        }
+
+        /// Splits `distributable` among the payees as if the amounts paid early through
+        /// `release_to` in this round were still in the contract, then deducts those amounts
+        /// from the shares of the payees that received them.
+        fn payouts_from(&self, distributable: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            let total = distributable.saturating_add(self.total_released_early);
+            let payouts = self.split_amount(total)?;
+            if self.total_released_early == 0 {
+                return Ok(payouts);
+            }
+            Ok(Self::deduct_early_releases(payouts, |payee| self.released_early.get(payee).unwrap_or(0)))
+        }
+
+        /// Deducts from `payouts` the amount each payee already withdrew this round, dropping
+        /// the payees left with nothing.
+        fn deduct_early_releases(
+            payouts: Vec<PayoutInfo>,
+            released_early: impl Fn(AccountId) -> Balance
+        ) -> Vec<PayoutInfo> {
+            let mut deducted: Vec<(AccountId, Balance)> = Vec::new();
+            let mut adjusted = Vec::new();
+            for mut info in payouts {
+                let already_deducted = deducted
+                    .iter()
+                    .find(|(payee, _)| *payee == info.payee)
+                    .map_or(0, |(_, amount)| *amount);
+                let deduction = released_early(info.payee)
+                    .saturating_sub(already_deducted)
+                    .min(info.amount);
+                match deducted.iter_mut().find(|(payee, _)| *payee == info.payee) {
+                    Some((_, amount)) => *amount = amount.saturating_add(deduction),
+                    None => deducted.push((info.payee, deduction)),
+                }
+                info.amount = info.amount.saturating_sub(deduction);
+                if info.amount > 0 {
+                    adjusted.push(info);
+                }
+            }
+            adjusted
+        }
+
+        /// Returns the part of the contract balance that is set aside and must not be split:
+        /// pending claims, the shares accrued by frozen payees, the unwithdrawn vesting credits,
+        /// the unclaimed stream allocations, the balances of the additional pools, the reserve
+        /// and the totals of committed payout plans.
+        #[ink(message)]
+        pub fn encumbered(&self) -> Balance {
+            self.total_pending
+                .saturating_add(self.total_accrued)
+                .saturating_add(self.total_vesting)
+                .saturating_add(self.total_streaming)
+                .saturating_add(self.pooled_balance)
+                .saturating_add(self.reserve)
+                .saturating_add(self.committed_total)
+        }
+
+        /// Returns the part of the contract balance that the next payout may split, i.e. the
+        /// balance minus `encumbered()`, limited to `accrued_since_last_payout()` if only
+        /// tracked deposits are split.
+        #[ink(message)]
+        pub fn distributable(&self) -> Balance {
+            self.distributable_of(self.env().balance())
+        }
+
+        /// Returns what `distributable()` would be if the contract's balance were `balance`.
+        fn distributable_of(&self, balance: Balance) -> Balance {
+            let free = balance.saturating_sub(self.encumbered());
+            if self.split_tracked_only {
+                free.min(self.accrued_since_last_payout)
+            } else {
+                free
+            }
+        }
+
+        /// Returns the value received through deposits that was not split yet.
+        #[ink(message)]
+        pub fn accrued_since_last_payout(&self) -> Balance {
+            self.accrued_since_last_payout
+        }
+
+        /// Returns whether payouts only split value received through deposits.
+        #[ink(message)]
+        pub fn split_tracked_only(&self) -> bool {
+            self.split_tracked_only
+        }
+
+        /// Removes `amount` from the deposits waiting to be split.
+        fn consume_accrued_deposits(&mut self, amount: Balance) {
+            self.accrued_since_last_payout = self.accrued_since_last_payout.saturating_sub(amount);
+        }
+
+        /// Returns `distributable()`, or `NothingDistributable` if it is zero.
+        fn nonzero_distributable(&self) -> Result<Balance, Error> {
+            match self.distributable() {
+                0 => Err(Error::NothingDistributable),
+                amount => Ok(amount),
+            }
+        }
+
+        /// Splits `total_balance` among the registered payees according to the split mode.
+        fn split_amount(&self, total_balance: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            match self.split_mode {
+                SplitMode::Proportional => self.split_proportionally(total_balance),
+                SplitMode::Owed => self.split_owed(total_balance),
+                SplitMode::Contributions => self.split_by_contribution(total_balance),
+            }
+        }
+
+        /// Splits the contributed part of `total_balance` pro-rata to the recorded contributions.
+        ///
+        /// The rounding remainder, and any funds that arrived without a `deposit`, go to the
+        /// remainder recipient or else the first payee.
+        fn split_by_contribution(&self, total_balance: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            let contributions: Vec<(AccountId, Balance)> = self.payees
+                .iter()
+                .map(|payee| (*payee, self.contributions.get(payee).unwrap_or(0)))
+                .collect();
+            let payouts = compute_contribution_distribution(total_balance, &contributions, self.remainder_account())?;
+            Ok(self.without_carry_over(payouts))
+        }
+
+        /// Returns the account the distribution functions pay the remainder to under the
+        /// remainder policy; `None` adds it to the first payee. To carry the remainder over, it
+        /// is assigned to the contract itself and dropped by `without_carry_over`.
+        fn remainder_account(&self) -> Option<AccountId> {
+            self.remainder_account_among(&self.payees)
+        }
+
+        /// Returns the remainder recipient like `remainder_account`, drawing a random payee
+        /// among `payees`, e.g. the payees of a pool.
+        fn remainder_account_among(&self, payees: &[AccountId]) -> Option<AccountId> {
+            match self.remainder_policy {
+                RemainderPolicy::FirstPayee => None,
+                RemainderPolicy::DesignatedPayee => Some(self.designated_payee),
+                RemainderPolicy::Treasury(account) => Some(account),
+                RemainderPolicy::CarryOver => Some(self.env().account_id()),
+                RemainderPolicy::RandomPayee => self.random_payee_among(payees),
+            }
+        }
+
+        /// Draws one of `payees`, see `random_draw_among`.
+        fn random_payee_among(&self, payees: &[AccountId]) -> Option<AccountId> {
+            self.random_draw_among(payees).map(|(payee, _)| payee)
+        }
+
+        /// Draws one of `payees` with the chain's randomness source, seeded with the current
+        /// round so that every draw within a round and block agrees, and returns it with
+        /// whether it was drawn at random. Falls back to the first payee if the randomness
+        /// source reports an error.
+        fn random_draw_among(&self, payees: &[AccountId]) -> Option<(AccountId, bool)> {
+            let mut subject = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(self.env().account_id(), self.round_id), &mut subject);
+            let seed = ink::env::chain_extension::ChainExtensionMethod::build(FETCH_RANDOM)
+                .input::<[u8; 32]>()
+                .output::<[u8; 32], false>()
+                .handle_error_code::<RandomUnavailable>()
+                .call(&subject);
+            match seed {
+                Ok(seed) => {
+                    let draw = u32::from_le_bytes([seed[0], seed[1], seed[2], seed[3]]);
+                    let payee = payees.get((draw as usize).checked_rem(payees.len())?)?;
+                    Some((*payee, true))
+                }
+                Err(RandomUnavailable) => payees.first().map(|payee| (*payee, false)),
+            }
+        }
+
+        /// Announces the payee drawn among `payees` for the remainder of the current round, or
+        /// of a payout of pool `pool_id`, under `RemainderPolicy::RandomPayee`.
+        fn emit_remainder_draw(&self, pool_id: u32, payees: &[AccountId]) {
+            if self.remainder_policy != RemainderPolicy::RandomPayee {
+                return;
+            }
+            if let Some((payee, random)) = self.random_draw_among(payees) {
+                self.env().emit_event(RemainderRecipientDrawn {
+                    round_id: self.round_id,
+                    pool_id,
+                    payee,
+                    random,
+                });
+            }
+        }
+
+        /// Removes the remainder assigned to the contract itself, so it stays in the balance.
+        fn without_carry_over(&self, payouts: Vec<PayoutInfo>) -> Vec<PayoutInfo> {
+            if self.remainder_policy != RemainderPolicy::CarryOver {
+                return payouts;
+            }
+            let contract = self.env().account_id();
+            payouts
+                .into_iter()
+                .filter(|info| info.payee != contract)
+                .collect()
+        }
+
+        /// Clears the recorded contributions so the next payout round starts from zero, and
+        /// removes the payees that were only added by their contribution.
+        fn reset_contributions(&mut self) {
+            for payee in self.payees.clone() {
+                self.contributions.remove(payee);
+                if self.contributors.contains(payee) {
+                    self.drop_payee(payee);
+                }
+            }
+            self.total_contributions = 0;
+        }
+
+        /// Forgets the early releases of the round that just ended.
+        fn reset_early_releases(&mut self) {
+            if self.total_released_early == 0 {
+                return;
+            }
+            for payee in self.payees.iter() {
+                self.released_early.remove(payee);
+            }
+            self.total_released_early = 0;
+        }
+
+        /// Pays each payee what they are owed, in list order, until `total_balance` runs out.
+        fn split_owed(&self, total_balance: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            let owed: Vec<(AccountId, Balance)> = self.payees
+                .iter()
+                .map(|payee| (*payee, self.owed.get(payee).unwrap_or(0)))
+                .collect();
+            compute_owed_distribution(total_balance, &owed)
+        }
+
+        /// Splits `total_balance` among the registered payees by tier.
+        ///
+        /// Tier-1 payees are paid their fixed amounts first, pro-rated down if the balance
+        /// cannot cover them all. Whatever is left is split equally among the tier-2 payees.
+        /// Without tier-1 payees this is a plain equal split.
+        fn split_proportionally(&self, total_balance: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            let tiers: Vec<(AccountId, Option<Balance>, u128)> = self.payees
+                .iter()
+                .map(|payee| (*payee, self.fixed_amounts.get(payee), self.shares_of(*payee)))
+                .collect();
+            let payouts = compute_tiered_distribution(total_balance, &tiers, self.remainder_account())?;
+            Ok(self.without_carry_over(payouts))
+        }
+
+        /// Triggers the payout and returns the outcome of every transfer.
+        ///
+        /// Behaves like `trigger_payout`, which parks failed transfers as pending claims, but
+        /// also reports for each payee whether the transfer went through.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `CooldownActive`: If the payout cooldown since the last payout has not elapsed, or a
+        ///   payout already ran in the current payout period.
+        /// * `NothingDistributable` / `NoPayees` / `ZeroShare`: If there is nothing to distribute.
+        ///
+        #[ink(message)]
+        pub fn trigger_payout_lossy(&mut self) -> Result<PayoutReport, Error> {
+            self.ensure_caller_can_trigger_full_payout()?;
+            self.execute_native_payout()
+        }
+
+        /// Distributes only `amount` of the distributable balance, e.g. one month's revenue, and
+        /// leaves the rest in the contract for later payouts.
+        ///
+        /// `amount` is split exactly like a full balance in `trigger_payout`, and failed
+        /// transfers are parked as pending claims. Returns the transfers that went through.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `ZeroShare`: If `amount` is zero.
+        /// * `InsufficientBalance`: If `amount` exceeds `distributable()`, i.e. the balance minus
+        ///   the reserve and the other encumbered funds.
+        /// * Any other error of `trigger_payout`.
+        ///
+        #[ink(message)]
+        pub fn trigger_payout_amount(&mut self, amount: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            self.ensure_caller_can_trigger_payout()?;
+            self.ensure_native_payout_allowed()?;
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+            if amount > self.distributable() {
+                return Err(Error::InsufficientBalance);
+            }
+            let report = self.pay_out_snapshot(amount)?;
+            Ok(report
+                .into_iter()
+                .filter_map(|(payee, result)| result.ok().map(|amount| PayoutInfo { payee, amount }))
+                .collect())
+        }
+
+        /// Splits `amount` of the distributable balance among `payees` only, e.g. the part of the
+        /// team that worked on a revenue source, pro-rata to their shares.
+        ///
+        /// This is not a payout round: the round, the cooldown and payout approvals are not
+        /// affected, and the rest of the balance stays for the next payout to all payees. Failed
+        /// transfers are parked as pending claims. Returns the transfers that went through.
+        ///
+        /// Choosing the subset favours some payees over others, so this stays restricted to the
+        /// operator and the `PayoutTrigger` role even when payouts are permissionless.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `ContractPaused` / `PayoutInProgress` / `ReentrancyGuardLocked`: As for `trigger_payout`.
+        /// * `NoPayees`: If `payees` is empty.
+        /// * `PayeeNotFound`: If an account in `payees` is not a registered payee.
+        /// * `DuplicatePayee`: If `payees` lists an account twice.
+        /// * `ZeroShare`: If `amount` is zero or too small to give every listed payee something.
+        /// * `InsufficientBalance`: If `amount` exceeds `distributable()`.
+        /// * `BelowThreshold` / `TooManyTransfers`: As for `trigger_payout`.
+        ///
+        #[ink(message)]
+        pub fn trigger_payout_for(&mut self, payees: Vec<AccountId>, amount: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            self.ensure_caller_can_trigger_payout()?;
+            self.ensure_not_paused()?;
+            self.ensure_no_payout_batch()?;
+            self.ensure_reentrancy_guard_not_locked()?;
+            if payees.is_empty() {
+                return Err(Error::NoPayees);
+            }
+            for (index, payee) in payees.iter().enumerate() {
+                if !self.payees.contains(payee) {
+                    return Err(Error::PayeeNotFound);
+                }
+                if payees[..index].contains(payee) {
+                    return Err(Error::DuplicatePayee);
+                }
+            }
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+            if amount > self.distributable() {
+                return Err(Error::InsufficientBalance);
+            }
+
+            let weights: Vec<(AccountId, u128)> = payees
+                .iter()
+                .map(|payee| (*payee, self.shares_of(*payee)))
+                .collect();
+            let payout_info = self.without_carry_over(
+                compute_weighted_distribution(amount, &weights, self.remainder_account())?
+            );
+            self.ensure_above_payout_threshold(&self.payees, &payout_info)?;
+            self.ensure_within_transfer_limit(payout_info.len())?;
+            let report = self.with_reentrancy_guard(|contract| {
+                let (total, report) = contract.release_payouts(&payout_info);
+                contract.env().emit_event(SubsetPayoutTriggered {
+                    total,
+                    num_payees: payees.len() as u32,
+                });
+                Ok(report)
+            })?;
+            Ok(report
+                .into_iter()
+                .filter_map(|(payee, result)| result.ok().map(|amount| PayoutInfo { payee, amount }))
+                .collect())
+        }
+
+        /// Streams `amount` of the distributable balance to the payees over `duration` blocks.
+        ///
+        /// The amount is split among the payees as by `trigger_payout_for` with every payee, and
+        /// each allocation accrues linearly from this block on, so a payee can `claim_stream`
+        /// whatever has accrued at any time instead of waiting for a payout. Like
+        /// `trigger_payout_for`, this is not a payout round. Whatever the accounts did not claim
+        /// from the previous stream is parked as pending claims.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `ContractPaused` / `PayoutInProgress` / `ReentrancyGuardLocked`: As for `trigger_payout`.
+        /// * `StreamActive`: If the previous stream has not ended yet.
+        /// * `ZeroShare`: If `amount` or `duration` is zero.
+        /// * `InsufficientBalance`: If `amount` exceeds `distributable()`.
+        ///
+        #[ink(message)]
+        pub fn start_stream(&mut self, amount: Balance, duration: BlockNumber) -> Result<(), Error> {
+            self.ensure_caller_can_trigger_payout()?;
+            self.ensure_not_paused()?;
+            self.ensure_no_payout_batch()?;
+            self.ensure_reentrancy_guard_not_locked()?;
+            let now = self.env().block_number();
+            if let Some(stream) = &self.stream {
+                if now < stream.start.saturating_add(stream.duration) {
+                    return Err(Error::StreamActive);
+                }
+            }
+            if amount == 0 || duration == 0 {
+                return Err(Error::ZeroShare);
+            }
+            if amount > self.distributable() {
+                return Err(Error::InsufficientBalance);
+            }
+
+            let weights = self.get_payees_with_shares();
+            let allocations = self.without_carry_over(
+                compute_weighted_distribution(amount, &weights, self.remainder_account())?
+            );
+            self.close_stream();
+            let total = allocations
+                .iter()
+                .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+            self.total_streaming = total;
+            self.consume_accrued_deposits(total);
+            self.stream = Some(Stream { allocations, start: now, duration });
+            self.env().emit_event(StreamStarted { total, start: now, duration });
+            Ok(())
+        }
+
+        /// Withdraws everything that has accrued to the caller from the current stream and was
+        /// not claimed yet. Returns the amount paid.
+        ///
+        /// # Errors
+        ///
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `PayeeFrozen`: If the caller is frozen.
+        /// * `ClaimLocked`: If the caller's lock-up period has not ended yet.
+        /// * `ZeroShare`: If nothing has accrued to the caller since their last claim.
+        /// * `TransferFailed`: If the transfer fails.
+        ///
+        #[ink(message)]
+        pub fn claim_stream(&mut self) -> Result<Balance, Error> {
+            let caller = self.env().caller();
+            self.ensure_reentrancy_guard_not_locked()?;
+            if self.frozen.contains(caller) {
+                return Err(Error::PayeeFrozen);
+            }
+            if self.is_claim_locked(caller) {
+                return Err(Error::ClaimLocked);
+            }
+            let amount = self.claimable_stream(caller);
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+
+            self.with_reentrancy_guard(|contract| contract.transfer_to(caller, amount))?;
+
+            let claimed = self.stream_claimed.get(caller).unwrap_or(0);
+            self.stream_claimed.insert(caller, &claimed.saturating_add(amount));
+            self.total_streaming = self.total_streaming.saturating_sub(amount);
+            self.record_release(caller, amount);
+            Ok(amount)
+        }
+
+        /// Returns what has accrued to `account` from the current stream so far, including what
+        /// it already claimed.
+        #[ink(message)]
+        pub fn streamed_amount(&self, account: AccountId) -> Balance {
+            let Some(stream) = &self.stream else {
+                return 0;
+            };
+            let allocation = stream.allocations
+                .iter()
+                .filter(|info| info.payee == account)
+                .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+            let elapsed = self.env().block_number().saturating_sub(stream.start);
+            if elapsed >= stream.duration {
+                return allocation;
+            }
+            allocation.saturating_mul(elapsed as Balance) / stream.duration as Balance
+        }
+
+        /// Returns what `claim_stream` would pay `account` right now.
+        #[ink(message)]
+        pub fn claimable_stream(&self, account: AccountId) -> Balance {
+            self.streamed_amount(account)
+                .saturating_sub(self.stream_claimed.get(account).unwrap_or(0))
+        }
+
+        /// Returns the current or last payout stream, if any.
+        #[ink(message)]
+        pub fn stream(&self) -> Option<Stream> {
+            self.stream.clone()
+        }
+
+        /// Parks what the accounts did not claim from the ended stream as pending claims and
+        /// clears its claim records.
+        fn close_stream(&mut self) {
+            let Some(stream) = self.stream.take() else {
+                return;
+            };
+            for info in stream.allocations.iter() {
+                let claimed = self.stream_claimed.take(info.payee).unwrap_or(0);
+                let unclaimed = info.amount.saturating_sub(claimed);
+                if unclaimed > 0 {
+                    self.park_payment(info.payee, unclaimed);
+                }
+            }
+            self.total_streaming = 0;
+        }
+
+        /// Checks, without moving funds, whether each transfer of the next payout would succeed.
+        ///
+        /// Returns the amount per payee, or the reason its transfer is expected to fail:
+        /// `PayeeFrozen`, `PayeeVesting`, `ClaimLocked`, `BelowMinimumBalance` for amounts under the chain's
+        /// minimum balance, or `InsufficientBalance` once the contract's transferable balance is used up. If the
+        /// payout cannot be calculated at all, every payee gets that error.
+        #[ink(message)]
+        pub fn validate_payout(&self) -> PayoutReport {
+            let payout_info = match self.next_payouts() {
+                Ok(payout_info) => payout_info,
+                Err(error) => {
+                    return self.payees
+                        .iter()
+                        .map(|payee| (*payee, Err(error)))
+                        .collect();
+                }
+            };
+
+            let minimum_balance = self.env().minimum_balance();
+            let mut transferable = self.env().balance();
+            let mut report = Vec::new();
+            for info in payout_info.iter() {
+                let diagnosis = if self.frozen.contains(info.payee) {
+                    Err(Error::PayeeFrozen)
+                } else if self.vesting_schedules.contains(info.payee) {
+                    Err(Error::PayeeVesting)
+                } else if self.is_claim_locked(info.payee) {
+                    Err(Error::ClaimLocked)
+                } else if info.amount < minimum_balance {
+                    Err(Error::BelowMinimumBalance)
+                } else if info.amount > transferable {
+                    Err(Error::InsufficientBalance)
+                } else {
+                    transferable = transferable.saturating_sub(info.amount);
+                    Ok(info.amount)
+                };
+                report.push((info.payee, diagnosis));
+            }
+            report
+        }
+
+        /// Distributes the native balance and returns the total amount transferred.
+        fn payout_native(&mut self) -> Result<Balance, Error> {
+            let report = self.execute_native_payout()?;
+            Ok(Self::transferred_total(&report))
+        }
+
+        /// Returns the total of the transfers that went through in `report`.
+        fn transferred_total(report: &PayoutReport) -> Balance {
+            report
+                .iter()
+                .filter_map(|(_, result)| result.as_ref().ok())
+                .fold(0, |total, amount| total.saturating_add(*amount))
+        }
+
+        /// Distributes the native balance and reports the outcome of each transfer.
+        ///
+        /// A failed transfer does not stop the payout: the amount is credited to the payee's
+        /// pending claim and the remaining payees are still paid.
+        fn execute_native_payout(&mut self) -> Result<PayoutReport, Error> {
+            self.ensure_native_payout_allowed()?;
+            let snapshot = self.nonzero_distributable()?;
+            self.pay_out_snapshot(snapshot)
+        }
+
+        /// Checks the conditions every native payout round has to meet before it is calculated.
+        fn ensure_native_payout_allowed(&self) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            self.ensure_no_payout_batch()?;
+            self.ensure_reentrancy_guard_not_locked()?;
+            self.ensure_cooldown_elapsed()?;
+            self.ensure_payout_approved()
+        }
+
+        /// Distributes the native balance like `execute_native_payout`, but while triggering is
+        /// permissionless sets the trigger bounty aside and transfers it to the caller once the
+        /// payout went through.
+        ///
+        /// The payout is calculated and validated before anything is paid, so a payout that
+        /// fails pays no bounty. A bounty whose transfer fails is parked as the caller's
+        /// pending claim.
+        fn execute_native_payout_with_bounty(&mut self) -> Result<PayoutReport, Error> {
+            if self.trigger_bounty_bps == 0 || !(self.is_permissionless || self.trigger_deadline_passed()) {
+                return self.execute_native_payout();
+            }
+            self.ensure_native_payout_allowed()?;
+            let snapshot = self.nonzero_distributable()?;
+            let bounty = snapshot.saturating_mul(self.trigger_bounty_bps as Balance) / TOTAL_BASIS_POINTS as Balance;
+            let payout_info = self.prepare_payout(snapshot.saturating_sub(bounty))?;
+            let round_id = self.round_id;
+            let report = self.run_payout(&payout_info)?;
+            if bounty > 0 {
+                let caller = self.env().caller();
+                self.consume_accrued_deposits(bounty);
+                if self.transfer_to(caller, bounty).is_ok() {
+                    self.total_distributed = self.total_distributed.saturating_add(bounty);
+                    self.env().emit_event(TriggerBountyPaid { round_id, caller, amount: bounty });
+                } else {
+                    self.park_payment(caller, bounty);
+                }
+            }
+            Ok(report)
+        }
+
+        /// Splits `snapshot` of the distributable balance among the payees as a payout round.
+        fn pay_out_snapshot(&mut self, snapshot: Balance) -> Result<PayoutReport, Error> {
+            let payout_info = self.prepare_payout(snapshot)?;
+            self.run_payout(&payout_info)
+        }
+
+        /// Splits `snapshot` of the distributable balance among the payees and checks that the
+        /// split may be paid out, before any transfer is made.
+        fn prepare_payout(&mut self, snapshot: Balance) -> Result<Vec<PayoutInfo>, Error> {
+            let payout_info = self.payouts_from(snapshot)?;
+            self.ensure_above_payout_threshold(&self.payees, &payout_info)?;
+            self.ensure_within_transfer_limit(payout_info.len())?;
+            self.ensure_snapshot_covered(snapshot, &payout_info)?;
+            self.emit_remainder_draw(0, &self.payees);
+            self.record_carried_dust(snapshot, &payout_info);
+            Ok(payout_info)
+        }
+
+        /// Records what splitting `snapshot` into `payouts` leaves in the contract under
+        /// `RemainderPolicy::CarryOver`, or zero under any other policy.
+        fn record_carried_dust(&mut self, snapshot: Balance, payouts: &[PayoutInfo]) {
+            self.carried_dust = if self.remainder_policy == RemainderPolicy::CarryOver {
+                let paid = payouts
+                    .iter()
+                    .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+                snapshot.saturating_sub(paid)
+            } else {
+                0
+            };
+        }
+
+        /// Checks that `payout_info` does not exceed the `snapshot` it was calculated from and
+        /// that the distributable balance still covers the snapshot.
+        fn ensure_snapshot_covered(&self, snapshot: Balance, payout_info: &[PayoutInfo]) -> Result<(), Error> {
+            let total = payout_info
+                .iter()
+                .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+            if total > snapshot || self.distributable() < snapshot {
+                return Err(Error::BalanceChanged);
+            }
+            Ok(())
+        }
+
+        /// Transfers `payout_info` as a payout round and advances the round bookkeeping.
+        fn run_payout(&mut self, payout_info: &[PayoutInfo]) -> Result<PayoutReport, Error> {
+            self.with_reentrancy_guard(|contract| {
+                let (total, report) = contract.release_payouts(payout_info);
+
+                contract.env().emit_event(PayoutTriggered {
+                    round_id: contract.round_id,
+                    nonce: contract.payout_nonce,
+                    total,
+                    num_payees: payout_info.len() as u32,
+                });
+                contract.finish_round();
+                Ok(report)
+            })
+        }
+
+        /// Closes the current payout round and opens the next one.
+        fn finish_round(&mut self) {
+            self.consume_approvals();
+            if self.split_mode == SplitMode::Contributions {
+                self.reset_contributions();
+            }
+            self.reset_early_releases();
+            self.payout_nonce = self.payout_nonce.saturating_add(1);
+            self.round_id = self.round_id.saturating_add(1);
+            self.last_payout_block = Some(self.env().block_number());
+        }
+
+        /// Pays the next `max_payees` payees of the current payout round, at most
+        /// `max_transfers_per_call` of them.
+        ///
+        /// For payee sets too large for a single `trigger_payout`. The first batch of a round
+        /// calculates the payout from the distributable balance at that time and sets the
+        /// amounts aside; later batches continue from a stored cursor. Once the cursor reaches
+        /// the end, the round is completed and a `PayoutCompleted` event is emitted. Failed
+        /// transfers are parked as pending claims like in `trigger_payout`. Returns the
+        /// outcome of each transfer of this batch.
+        ///
+        /// While a batched round is in progress, the other payout messages and pull-payment
+        /// releases return `PayoutInProgress`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `InvalidBatchSize`: If `max_payees` is zero.
+        /// * `ContractPaused`: If the contract is paused.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `CooldownActive` / `InsufficientApprovals`: When starting a round, as for `trigger_payout`.
+        /// * `NothingDistributable` / `NoPayees` / `ZeroShare`: When starting a round, if there
+        ///   is nothing to distribute.
+        ///
+        #[ink(message)]
+        pub fn trigger_payout_batch(&mut self, max_payees: u32) -> Result<PayoutReport, Error> {
+            self.ensure_caller_can_trigger_full_payout()?;
+            if max_payees == 0 {
+                return Err(Error::InvalidBatchSize);
+            }
+            self.ensure_not_paused()?;
+            self.ensure_reentrancy_guard_not_locked()?;
+
+            let mut batch = match self.payout_batch.clone() {
+                Some(batch) => batch,
+                None => {
+                    self.ensure_cooldown_elapsed()?;
+                    self.ensure_payout_approved()?;
+                    let payouts = self.payouts_from(self.nonzero_distributable()?)?;
+                    self.ensure_above_payout_threshold(&self.payees, &payouts)?;
+                    let total = payouts
+                        .iter()
+                        .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+                    self.record_carried_dust(self.distributable(), &payouts);
+                    self.committed_total = self.committed_total.saturating_add(total);
+                    self.emit_remainder_draw(0, &self.payees);
+                    PayoutBatch { payouts, cursor: 0, total: 0 }
+                }
+            };
+
+            let batch_size = if self.max_transfers_per_call > 0 {
+                max_payees.min(self.max_transfers_per_call)
+            } else {
+                max_payees
+            };
+            let start = batch.cursor as usize;
+            let end = start.saturating_add(batch_size as usize).min(batch.payouts.len());
+            let (paid, report) = self.with_reentrancy_guard(|contract| {
+                Ok(contract.release_payouts(&batch.payouts[start..end]))
+            })?;
+            let processed = batch.payouts[start..end]
+                .iter()
+                .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+            self.committed_total = self.committed_total.saturating_sub(processed);
+            batch.cursor = end as u32;
+            batch.total = batch.total.saturating_add(paid);
+
+            if end == batch.payouts.len() {
+                self.env().emit_event(PayoutCompleted {
+                    round_id: self.round_id,
+                    total: batch.total,
+                    num_payees: batch.payouts.len() as u32,
+                });
+                self.payout_batch = None;
+                self.finish_round();
+            } else {
+                self.payout_batch = Some(batch);
+            }
+            Ok(report)
+        }
+
+        /// Returns the batched payout round in progress, if any.
+        #[ink(message)]
+        pub fn payout_batch(&self) -> Option<PayoutBatch> {
+            self.payout_batch.clone()
+        }
+
+        /// Sets the maximum number of transfers a single payout call may make, to keep calls
+        /// within the chain's block weight limit (0 means unlimited).
+        ///
+        /// `trigger_payout_batch` processes at most this many payees per call, while payouts
+        /// that run in a single call fail with `TooManyTransfers` if they would need more.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_max_transfers_per_call(&mut self, max_transfers: u32) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(
+                ConfigField::MaxTransfersPerCall,
+                &self.max_transfers_per_call,
+                &max_transfers
+            );
+            self.max_transfers_per_call = max_transfers;
+            Ok(())
+        }
+
+        /// Returns the maximum number of transfers a single payout call may make (0 means
+        /// unlimited).
+        #[ink(message)]
+        pub fn max_transfers_per_call(&self) -> u32 {
+            self.max_transfers_per_call
+        }
+
+        /// Sets the smallest share a payout may pay a payee (0 means no minimum).
+        ///
+        /// Paying out tiny shares wastes transaction fees, so until the balance is large enough
+        /// for every payee with a share to receive at least this much, payouts fail with
+        /// `BelowThreshold`. Remainders paid to accounts that are not payees are not checked.
+        /// Pool and token payouts are held back the same way, a token share being compared in
+        /// the token's own units.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_min_payout_per_payee(&mut self, min_payout: Balance) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::MinPayoutPerPayee, &self.min_payout_per_payee, &min_payout);
+            self.min_payout_per_payee = min_payout;
+            Ok(())
+        }
+
+        /// Returns the smallest share a payout may pay a payee (0 means no minimum).
+        #[ink(message)]
+        pub fn min_payout_per_payee(&self) -> Balance {
+            self.min_payout_per_payee
+        }
+
+        /// Calculates the next payout and stores it as a plan for `execute_payout`.
+        ///
+        /// Returns the id of the plan. The amounts are fixed at commit time, so payees can
+        /// review them and the admin can `cancel_payout_plan` before execution. The carried
+        /// dust is only recorded when the plan is executed.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `ContractPaused`: If the contract is paused.
+        /// * `CooldownActive`: If the payout cooldown has not elapsed yet.
+        /// * `NothingDistributable` / `NoPayees` / `ZeroShare`: If there is nothing to distribute.
+        ///
+        #[ink(message)]
+        pub fn commit_payout(&mut self) -> Result<u64, Error> {
+            self.ensure_caller_can_trigger_payout()?;
+            self.ensure_not_paused()?;
+            self.ensure_cooldown_elapsed()?;
+            let payouts = self.next_payouts()?;
+            self.ensure_above_payout_threshold(&self.payees, &payouts)?;
+            let total = payouts
+                .iter()
+                .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+            let carried_dust = if self.remainder_policy == RemainderPolicy::CarryOver {
+                self.distributable().saturating_sub(total)
+            } else {
+                0
+            };
+
+            let plan_id = self.next_plan_id;
+            self.next_plan_id = plan_id.saturating_add(1);
+            self.committed_total = self.committed_total.saturating_add(total);
+            self.payout_plans.insert(plan_id, &PayoutPlan {
+                payouts,
+                committed_at: self.env().block_number(),
+                carried_dust,
+            });
+            self.env().emit_event(PayoutCommitted { plan_id, total });
+            Ok(plan_id)
+        }
+
+        /// Executes a committed payout plan and returns the outcome of every transfer.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `PlanNotFound`: If no plan exists with the given id, e.g. because it was cancelled
+        ///   or already executed.
+        /// * `DisputeWindowActive`: If fewer than `dispute_window` blocks passed since the commit.
+        /// * `InsufficientBalance`: If the distributable balance no longer covers the plan.
+        /// * `ReentrancyGuardLocked` / `CooldownActive` / `InsufficientApprovals`: As for
+        ///   `trigger_payout`.
+        ///
+        #[ink(message)]
+        pub fn execute_payout(&mut self, plan_id: u64) -> Result<PayoutReport, Error> {
+            self.ensure_caller_can_trigger_payout()?;
+            let plan = self.payout_plans.get(plan_id).ok_or(Error::PlanNotFound)?;
+            if self.env().block_number() < plan.committed_at.saturating_add(self.dispute_window) {
+                return Err(Error::DisputeWindowActive);
+            }
+            self.ensure_within_transfer_limit(plan.payouts.len())?;
+            self.ensure_not_paused()?;
+            self.ensure_no_payout_batch()?;
+            self.ensure_reentrancy_guard_not_locked()?;
+            self.ensure_cooldown_elapsed()?;
+            self.ensure_payout_approved()?;
+            let total = plan.payouts
+                .iter()
+                .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+            let available = self.env()
+                .balance()
+                .saturating_sub(self.encumbered().saturating_sub(total));
+            if total > available {
+                return Err(Error::InsufficientBalance);
+            }
+
+            self.payout_plans.remove(plan_id);
+            self.committed_total = self.committed_total.saturating_sub(total);
+            self.carried_dust = plan.carried_dust;
+            self.run_payout(&plan.payouts)
+        }
+
+        /// Cancels a committed payout plan, e.g. because a payee disputes the amounts.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PlanNotFound`: If no plan exists with the given id.
+        ///
+        #[ink(message)]
+        pub fn cancel_payout_plan(&mut self, plan_id: u64) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            let plan = self.payout_plans.take(plan_id).ok_or(Error::PlanNotFound)?;
+            let total = plan.payouts
+                .iter()
+                .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+            self.committed_total = self.committed_total.saturating_sub(total);
+            self.env().emit_event(PayoutCancelled { plan_id });
+            Ok(())
+        }
+
+        /// Returns a committed payout plan.
+        #[ink(message)]
+        pub fn payout_plan(&self, plan_id: u64) -> Option<PayoutPlan> {
+            self.payout_plans.get(plan_id)
+        }
+
+        /// Splits a freshly deposited `amount` among the payees and transfers the shares
+        /// right away.
+        ///
+        /// An amount too small to split stays in the contract for the next payout.
+        fn auto_split_deposit(&mut self, amount: Balance) -> Result<(), Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            if let Ok(payout_info) = self.split_amount(amount) {
+                self.with_reentrancy_guard(|contract| {
+                    contract.release_payouts(&payout_info);
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        }
+
+        /// Transfers each entry of `payout_info`, parking the ones that cannot be paid now.
+        ///
+        /// Returns the total transferred and the outcome per payee. The caller holds the
+        /// reentrancy guard.
+        fn release_payouts(&mut self, payout_info: &[PayoutInfo]) -> (Balance, PayoutReport) {
+            for info in payout_info.iter() {
+                self.consume_accrued_deposits(info.amount);
+            }
+            let (total, report) = self.release_shares(payout_info);
+            if self.split_mode == SplitMode::Owed {
+                // A parked share is still owed; a frozen or vesting payee's share is not.
+                for (info, (_, result)) in payout_info.iter().zip(report.iter()) {
+                    if matches!(result, Ok(_) | Err(Error::PayeeFrozen | Error::PayeeVesting)) {
+                        let owed = self.owed.get(info.payee).unwrap_or(0);
+                        self.owed.insert(info.payee, &owed.saturating_sub(info.amount));
+                    }
+                }
+            }
+            (total, report)
+        }
+
+        /// Pays out `payout_info`: a frozen payee's share accrues, a vesting payee's share is
+        /// credited to the schedule, and the share of a claim-locked payee or a failed transfer
+        /// is parked as a pending claim, so one payee never blocks the others.
+        ///
+        /// Returns the total transferred and the outcome per payee. The caller holds the
+        /// reentrancy guard.
+        fn release_shares(&mut self, payout_info: &[PayoutInfo]) -> (Balance, PayoutReport) {
+            let mut total: Balance = 0;
+            let mut report = Vec::new();
+            for info in payout_info.iter() {
+                if self.frozen.contains(info.payee) {
+                    let accrued = self.accrued.get(info.payee).unwrap_or(0);
+                    self.accrued.insert(info.payee, &accrued.saturating_add(info.amount));
+                    self.total_accrued = self.total_accrued.saturating_add(info.amount);
+                    report.push((info.payee, Err(Error::PayeeFrozen)));
+                    continue;
+                }
+                if self.vesting_schedules.contains(info.payee) {
+                    self.credit_vesting(info.payee, info.amount);
+                    report.push((info.payee, Err(Error::PayeeVesting)));
+                    continue;
+                }
+                if self.is_claim_locked(info.payee) {
+                    self.park_payment(info.payee, info.amount);
+                    report.push((info.payee, Err(Error::ClaimLocked)));
+                    continue;
+                }
+                if let Err(error) = self.deliver_payment(info.payee, info.amount) {
+                    self.park_payment(info.payee, info.amount);
+                    report.push((info.payee, Err(error)));
+                    continue;
+                }
+                total = total.saturating_add(info.amount);
+                report.push((info.payee, Ok(info.amount)));
+                self.record_release(info.payee, info.amount);
+
+                if self.child_splitters.contains(info.payee) {
+                    self.notify_child_splitter(info.payee);
+                }
+            }
+            (total, report)
+        }
+
+        /// Pays `payee` their current share of the next payout ahead of the round.
+        ///
+        /// The amount is what `payee_share` reports. It is deducted from the payee's share in the
+        /// next payout (or, in `SplitMode::Owed`, from what they are owed), so the payee is not
+        /// paid twice for the same funds. The share of a payee with a vesting schedule is credited
+        /// to the schedule instead of being transferred. Returns the amount paid or credited.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `PayeeFrozen`: If the payee is frozen.
+        /// * `ClaimLocked`: If the payee's lock-up period has not ended yet.
+        /// * `ZeroShare`: If the payee's current share is zero.
+        /// * `TransferFailed`: If the transfer fails.
+        ///
+        #[ink(message)]
+        pub fn release_to(&mut self, payee: AccountId) -> Result<Balance, Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            self.release_share(payee)
+        }
+
+        /// Withdraws everything `payee` is entitled to right now: their pending claim, their
+        /// share of the current distributable balance and the vested part of their vesting
+        /// credits.
+        ///
+        /// Anyone may call this in pull-payment mode, or for a payee with a vesting schedule; the
+        /// funds always go to `payee`. The share is accounted like in `release_to`, so a bad payee
+        /// cannot hold up the others, who withdraw independently. Returns the amount paid.
+        ///
+        /// # Errors
+        ///
+        /// * `PullPaymentsDisabled`: If the contract is not in pull-payment mode and `payee` has
+        ///   no vesting schedule.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee and has no pending claim.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `PayeeFrozen`: If the payee is frozen.
+        /// * `ClaimLocked`: If the payee's lock-up period has not ended yet.
+        /// * `ZeroShare`: If nothing is releasable for `payee`.
+        /// * `TransferFailed`: If a transfer fails.
+        ///
+        #[ink(message)]
+        pub fn release(&mut self, payee: AccountId) -> Result<Balance, Error> {
+            if !self.pull_payments && !self.vesting_schedules.contains(payee) {
+                return Err(Error::PullPaymentsDisabled);
+            }
+            self.release_entitled(payee, self.pull_payments)
+        }
+
+        /// Transfers the pending claim and the vested credits of `payee`, and with
+        /// `include_share` their share of the current distributable balance, like `release`.
+        fn release_entitled(&mut self, payee: AccountId, include_share: bool) -> Result<Balance, Error> {
+            let is_vesting = self.vesting_schedules.contains(payee);
+            let is_payee = self.payees.contains(&payee);
+            let pending = self.pending_claims.get(payee).unwrap_or(0);
+            if !is_payee && pending == 0 && !is_vesting {
+                return Err(Error::PayeeNotFound);
+            }
+            let mut released = 0;
+            if pending > 0 {
+                released = self.release_pending(payee)?;
+            }
+            if is_payee && include_share {
+                match self.release_share(payee) {
+                    Ok(amount) if !is_vesting => released = released.saturating_add(amount),
+                    Ok(_) => {}
+                    Err(Error::ZeroShare) if released > 0 || is_vesting => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            if is_vesting {
+                match self.release_vested(payee) {
+                    Ok(amount) => released = released.saturating_add(amount),
+                    Err(Error::ZeroShare) if released > 0 => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            Ok(released)
+        }
+
+        /// Withdraws everything the caller is entitled to right now, see `release`.
+        ///
+        /// # Errors
+        ///
+        /// * Any error of `release`.
+        ///
+        #[ink(message)]
+        pub fn release_self(&mut self) -> Result<Balance, Error> {
+            self.release(self.env().caller())
+        }
+
+        /// Withdraws everything `payee` is entitled to across all assets in one call: the native
+        /// amount of `release`, then their share of every registered token in registration order.
+        ///
+        /// Anyone may call this in pull-payment mode; the funds always go to `payee`. A failure
+        /// on one asset does not abort the others: the outcome of each asset is reported in the
+        /// returned vector, native balance first. Token withdrawals are deducted from the payee's
+        /// share at the token's next payout.
+        ///
+        /// # Errors
+        ///
+        /// * `PullPaymentsDisabled`: If the contract is not in pull-payment mode.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee and has no pending claim.
+        ///
+        #[ink(message)]
+        pub fn release_all(&mut self, payee: AccountId) -> Result<Vec<AssetPayoutResult>, Error> {
+            if !self.pull_payments {
+                return Err(Error::PullPaymentsDisabled);
+            }
+            let is_payee = self.payees.contains(&payee);
+            if !is_payee && !self.pending_claims.contains(payee) {
+                return Err(Error::PayeeNotFound);
+            }
+
+            let mut results = Vec::with_capacity(self.registered_tokens.len() + 1);
+            results.push(AssetPayoutResult { token: None, result: self.release(payee) });
+            for token in self.registered_tokens.clone() {
+                let result = if is_payee {
+                    self.release_token_share(token, payee)
+                } else {
+                    Err(Error::PayeeNotFound)
+                };
+                results.push(AssetPayoutResult { token: Some(token), result });
+            }
+            Ok(results)
+        }
+
+        /// Returns whether payees may withdraw their share at any time with `release`.
+        #[ink(message)]
+        pub fn pull_payments(&self) -> bool {
+            self.pull_payments
+        }
+
+        /// Transfers the current share of `payee` ahead of the next payout and records it so the
+        /// payout deducts it.
+        fn release_share(&mut self, payee: AccountId) -> Result<Balance, Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            self.ensure_no_payout_batch()?;
+            if self.frozen.contains(payee) {
+                return Err(Error::PayeeFrozen);
+            }
+            if self.is_claim_locked(payee) {
+                return Err(Error::ClaimLocked);
+            }
+            let amount = self.current_share(payee);
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+
+            let is_vesting = self.vesting_schedules.contains(payee);
+            if is_vesting {
+                self.credit_vesting(payee, amount);
+            } else {
+                self.with_reentrancy_guard(|contract| contract.deliver_payment(payee, amount))?;
+            }
+            self.consume_accrued_deposits(amount);
+
+            if self.split_mode == SplitMode::Owed {
+                let owed = self.owed.get(payee).unwrap_or(0);
+                self.owed.insert(payee, &owed.saturating_sub(amount));
+            } else {
+                let released = self.released_early.get(payee).unwrap_or(0);
+                self.released_early.insert(payee, &released.saturating_add(amount));
+                self.total_released_early = self.total_released_early.saturating_add(amount);
+            }
+            if !is_vesting {
+                self.record_release(payee, amount);
+            }
+            Ok(amount)
+        }
+
+        /// Returns what `payee` would be paid if the payout ran now, early releases deducted.
+        fn current_share(&self, payee: AccountId) -> Balance {
+            self.next_payouts()
+                .unwrap_or_default()
+                .iter()
+                .filter(|info| info.payee == payee)
+                .fold(0, |share: Balance, info| share.saturating_add(info.amount))
+        }
+
+        /// Records that `amount` was transferred to `payee` and emits `PaymentReleased`.
+        fn record_release(&mut self, payee: AccountId, amount: Balance) {
+            self.total_distributed = self.total_distributed.saturating_add(amount);
+            let released = self.released.get(payee).unwrap_or(0);
+            self.released.insert(payee, &released.saturating_add(amount));
+            self.env().emit_event(PaymentReleased {
+                payee,
+                amount,
+                round_id: self.round_id,
+            });
+        }
+
+        /// Returns what `release` would pay `account` right now: its pending claim plus, for a
+        /// payee that is neither frozen nor locked, its share of the current distributable
+        /// balance or, with a vesting schedule, the vested part of its vesting credits.
+        #[ink(message)]
+        pub fn releasable(&self, account: AccountId) -> Balance {
+            let pending = self.pending_claims.get(account).unwrap_or(0);
+            if self.frozen.contains(account) || self.is_claim_locked(account) {
+                return pending;
+            }
+            if self.vesting_schedules.contains(account) {
+                return pending.saturating_add(self.unreleased_vested(account));
+            }
+            if !self.payees.contains(&account) {
+                return pending;
+            }
+            pending.saturating_add(self.current_share(account))
+        }
+
+        /// Returns everything ever transferred to `account` by payouts, releases and claims.
+        #[ink(message)]
+        pub fn released(&self, account: AccountId) -> Balance {
+            self.released.get(account).unwrap_or(0)
+        }
+
+        /// Credits `amount` to the pending claim of `payee`.
+        fn park_payment(&mut self, payee: AccountId, amount: Balance) {
+            let pending = self.pending_claims.get(payee).unwrap_or(0);
+            if pending == 0 {
+                self.retry_queue.push(payee);
+            }
+            self.pending_claims.insert(payee, &pending.saturating_add(amount));
+            self.total_pending = self.total_pending.saturating_add(amount);
+            self.env().emit_event(PaymentParked {
+                payee,
+                amount,
+                round_id: self.round_id,
+            });
+        }
+
+        /// Retries the transfer of every pending claim, oldest failure first, and reports the
+        /// outcome per account.
+        ///
+        /// Anyone may call this; each claim always goes to its own account. A claim whose
+        /// transfer goes through leaves the queue, a claim that fails again stays in it.
+        ///
+        /// # Errors
+        ///
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        ///
+        #[ink(message)]
+        pub fn retry_failed(&mut self) -> Result<PayoutReport, Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            let mut report = Vec::with_capacity(self.retry_queue.len());
+            for account in self.retry_queue.clone() {
+                report.push((account, self.release_pending(account)));
+            }
+            Ok(report)
+        }
+
+        /// Returns the accounts with a pending claim, in the order their first transfer failed.
+        #[ink(message)]
+        pub fn retry_queue(&self) -> Vec<AccountId> {
+            self.retry_queue.clone()
+        }
+
+        /// Withdraws the caller's pending claim.
+        ///
+        /// # Errors
+        ///
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `PayeeFrozen`: If the payee is frozen.
+        /// * `ClaimLocked`: If the payee's lock-up period has not ended yet.
+        /// * `ZeroShare`: If the caller has no pending claim.
+        /// * `TransferFailed`: If the transfer fails; the claim is kept.
+        ///
+        #[ink(message)]
+        pub fn claim(&mut self) -> Result<Balance, Error> {
+            self.release_pending(self.env().caller())
+        }
+
+        /// Pushes what `releasable` reports for `payee` to them: their pending claim, their share
+        /// of the current distributable balance and the vested part of their vesting credits.
+        ///
+        /// Anyone may call this, e.g. on behalf of a cold wallet that never submits transactions,
+        /// also outside pull-payment mode. The funds always go to `payee`, never to the caller,
+        /// and the share is accounted like in `release`.
+        ///
+        /// # Errors
+        ///
+        /// * `PayeeNotFound`: If `payee` is not a registered payee and has no pending claim.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `PayeeFrozen`: If the payee is frozen.
+        /// * `ClaimLocked`: If the payee's lock-up period has not ended yet.
+        /// * `ZeroShare`: If nothing is owed to `payee`.
+        /// * `TransferFailed`: If a transfer fails; the claim is kept.
+        ///
+        #[ink(message)]
+        pub fn release_for(&mut self, payee: AccountId) -> Result<Balance, Error> {
+            if !self.payees.contains(&payee) && !self.pending_claims.contains(payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            self.release_entitled(payee, true)
+        }
+
+        /// Transfers the pending claim of `payee` to them.
+        fn release_pending(&mut self, payee: AccountId) -> Result<Balance, Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            if self.frozen.contains(payee) {
+                return Err(Error::PayeeFrozen);
+            }
+            if self.is_claim_locked(payee) {
+                return Err(Error::ClaimLocked);
+            }
+            let amount = self.pending_claims.get(payee).unwrap_or(0);
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+
+            self.with_reentrancy_guard(|contract| contract.transfer_to(payee, amount))?;
+
+            self.pending_claims.remove(payee);
+            self.retry_queue.retain(|account| *account != payee);
+            self.total_pending = self.total_pending.saturating_sub(amount);
+            self.record_release(payee, amount);
+            Ok(amount)
+        }
+
+        /// Locks the claims of `payee` until `block`.
+        ///
+        /// Before the unlock block, `claim` and `release_for` fail for the payee and payouts
+        /// park the payee's share as a pending claim instead of transferring it.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee.
+        ///
+        #[ink(message)]
+        pub fn set_claim_lock(&mut self, payee: AccountId, block: BlockNumber) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            let old = self.claim_unlock_block.get(payee);
+            self.claim_unlock_block.insert(payee, &block);
+            self.emit_config_changed(ConfigField::ClaimLock(payee), &old, &Some(block));
+            Ok(())
+        }
+
+        /// Freezes `payee`, e.g. under a legal hold.
+        ///
+        /// While frozen, payouts accrue the payee's share instead of transferring it, and
+        /// `claim`, `release_for` and `release_to` fail for the payee.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee.
+        ///
+        #[ink(message)]
+        pub fn freeze_payee(&mut self, payee: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            if self.frozen.insert(payee, &()).is_none() {
+                self.env().emit_event(PayeeFrozen { payee });
+            }
+            Ok(())
+        }
+
+        /// Unfreezes `payee` and releases the shares accrued while frozen.
+        ///
+        /// If the accrued amount cannot be transferred, it is parked as a pending claim.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PayeeNotFound`: If `payee` is not frozen.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        ///
+        #[ink(message)]
+        pub fn unfreeze_payee(&mut self, payee: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.frozen.contains(payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            self.ensure_reentrancy_guard_not_locked()?;
+            self.frozen.remove(payee);
+
+            let released = self.accrued.take(payee).unwrap_or(0);
+            self.total_accrued = self.total_accrued.saturating_sub(released);
+            if released > 0 {
+                let delivered = self.with_reentrancy_guard(|contract| contract.deliver_payment(payee, released));
+                if delivered.is_ok() {
+                    self.record_release(payee, released);
+                } else {
+                    self.park_payment(payee, released);
+                }
+            }
+            self.env().emit_event(PayeeUnfrozen { payee, released });
+            Ok(())
+        }
+
+        /// Returns whether `payee` is frozen.
+        #[ink(message)]
+        pub fn frozen(&self, payee: AccountId) -> bool {
+            self.frozen.contains(payee)
+        }
+
+        /// Returns the shares accrued by `payee` while frozen.
+        #[ink(message)]
+        pub fn accrued(&self, payee: AccountId) -> Balance {
+            self.accrued.get(payee).unwrap_or(0)
+        }
+
+        /// Returns the block from which `payee` can receive funds, if a lock-up was set.
+        #[ink(message)]
+        pub fn claim_unlock_block(&self, payee: AccountId) -> Option<BlockNumber> {
+            self.claim_unlock_block.get(payee)
+        }
+
+        /// Sets the vesting schedule of `payee`, replacing any previous one.
+        ///
+        /// From then on the payee's shares are credited to the schedule instead of being
+        /// transferred, and `release` lets the payee withdraw only the vested part of
+        /// everything credited so far. A schedule can only be replaced once the payee has
+        /// withdrawn everything credited to the previous one, so funds that vested cannot be
+        /// locked again; the new schedule starts from no credits.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee.
+        /// * `VestingUnreleased`: If credits of the current schedule are not withdrawn yet.
+        ///
+        #[ink(message)]
+        pub fn set_vesting_schedule(&mut self, payee: AccountId, schedule: VestingSchedule) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            let credited = self.vesting_credited.get(payee).unwrap_or(0);
+            if credited > self.vesting_released.get(payee).unwrap_or(0) {
+                return Err(Error::VestingUnreleased);
+            }
+            self.vesting_credited.remove(payee);
+            self.vesting_released.remove(payee);
+            let old = self.vesting_schedules.get(payee);
+            self.emit_config_changed(ConfigField::VestingSchedule(payee), &old, &Some(schedule.clone()));
+            self.vesting_schedules.insert(payee, &schedule);
+            Ok(())
+        }
+
+        /// Returns the vesting schedule of `payee`, if any.
+        #[ink(message)]
+        pub fn vesting_schedule(&self, payee: AccountId) -> Option<VestingSchedule> {
+            self.vesting_schedules.get(payee)
+        }
+
+        /// Returns the part of everything credited to `payee`'s vesting schedule that has
+        /// vested so far, including what the payee already withdrew.
+        #[ink(message)]
+        pub fn vested_amount(&self, payee: AccountId) -> Balance {
+            let credited = self.vesting_credited.get(payee).unwrap_or(0);
+            let Some(schedule) = self.vesting_schedules.get(payee) else {
+                return credited;
+            };
+            let now = self.env().block_number();
+            if now < schedule.start.saturating_add(schedule.cliff) {
+                return 0;
+            }
+            let elapsed = now.saturating_sub(schedule.start);
+            if elapsed >= schedule.duration {
+                return credited;
+            }
+            credited.saturating_mul(elapsed as Balance) / schedule.duration as Balance
+        }
+
+        /// Returns the vested credits of `payee` it has not withdrawn yet.
+        fn unreleased_vested(&self, payee: AccountId) -> Balance {
+            self.vested_amount(payee)
+                .saturating_sub(self.vesting_released.get(payee).unwrap_or(0))
+        }
+
+        /// Credits `amount` to the vesting schedule of `payee`.
+        fn credit_vesting(&mut self, payee: AccountId, amount: Balance) {
+            let credited = self.vesting_credited.get(payee).unwrap_or(0);
+            self.vesting_credited.insert(payee, &credited.saturating_add(amount));
+            self.total_vesting = self.total_vesting.saturating_add(amount);
+        }
+
+        /// Transfers the vested credits of `payee` it has not withdrawn yet.
+        fn release_vested(&mut self, payee: AccountId) -> Result<Balance, Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            if self.frozen.contains(payee) {
+                return Err(Error::PayeeFrozen);
+            }
+            if self.is_claim_locked(payee) {
+                return Err(Error::ClaimLocked);
+            }
+            let amount = self.unreleased_vested(payee);
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+
+            self.with_reentrancy_guard(|contract| contract.transfer_to(payee, amount))?;
+
+            let released = self.vesting_released.get(payee).unwrap_or(0);
+            self.vesting_released.insert(payee, &released.saturating_add(amount));
+            self.total_vesting = self.total_vesting.saturating_sub(amount);
+            self.record_release(payee, amount);
+            Ok(amount)
+        }
+
+        /// Helper function to check whether `payee` is still in its lock-up period.
+        fn is_claim_locked(&self, payee: AccountId) -> bool {
+            self.claim_unlock_block
+                .get(payee)
+                .is_some_and(|unlock_block| self.env().block_number() < unlock_block)
+        }
+
+        /// Returns the pending claim of `payee`.
+        #[ink(message)]
+        pub fn pending_claim_of(&self, payee: AccountId) -> Balance {
+            self.pending_claims.get(payee).unwrap_or(0)
+        }
+
+        /// Transfers `amount` of the native currency from the contract to `to`.
+        ///
+        /// The unit tests replace it with a version that can fail, see `tests`.
+        #[cfg(not(test))]
+        fn transfer_to(&self, to: AccountId, amount: Balance) -> Result<(), Error> {
+            self.env().transfer(to, amount).map_err(|_| Error::TransferFailed)
+        }
+
+        /// Triggers the payout only if `expected_nonce` matches the current payout nonce.
+        ///
+        /// Lets off-chain tooling build payout transactions ahead of time without risking a
+        /// double payout: once a round succeeds the nonce is incremented and any resubmission
+        /// of the same transaction is rejected.
+        ///
+        /// # Errors
+        ///
+        /// * `NonceMismatch`: If `expected_nonce` differs from the current payout nonce.
+        /// * Any error returned by `trigger_payout`.
+        ///
+        #[ink(message)]
+        pub fn trigger_payout_with_nonce(&mut self, expected_nonce: u64) -> Result<(), Error> {
+            if expected_nonce != self.payout_nonce {
+                return Err(Error::NonceMismatch);
+            }
+            self.trigger_payout().map(|_| ())
+        }
+
+        /// Triggers the payout on behalf of the payout signer, authorized by an ECDSA signature.
+        ///
+        /// Lets a relayer submit the transaction while the authorization comes from an offline
+        /// key. The signed message is `signed_payout_hash(nonce)`, i.e. the BLAKE2-256 hash of
+        /// the SCALE-encoded `(contract address, nonce)`. Since the nonce is incremented by the
+        /// payout, a signature cannot be replayed.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If no payout signer is configured.
+        /// * `NonceMismatch`: If `nonce` differs from the current payout nonce.
+        /// * `InvalidSignature`: If the signature was not produced by the payout signer.
+        /// * Any error returned by `trigger_payout`.
+        ///
+        #[ink(message)]
+        pub fn trigger_payout_signed(&mut self, nonce: u64, signature: [u8; 65]) -> Result<(), Error> {
+            let payout_signer = self.payout_signer.ok_or(Error::Unauthorized)?;
+            if nonce != self.payout_nonce {
+                return Err(Error::NonceMismatch);
+            }
+
+            let message_hash = self.signed_payout_hash(nonce);
+            let public_key = self
+                .env()
+                .ecdsa_recover(&signature, &message_hash)
+                .map_err(|_| Error::InvalidSignature)?;
+            let mut signer = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut signer);
+            if AccountId::from(signer) != payout_signer {
+                return Err(Error::InvalidSignature);
+            }
+
+            self.execute_native_payout().map(|_| ())
+        }
+
+        /// Returns the message hash the payout signer signs to authorize the round `nonce`.
+        #[ink(message)]
+        pub fn signed_payout_hash(&self, nonce: u64) -> [u8; 32] {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(self.env().account_id(), nonce), &mut output);
+            output
+        }
+
+        /// Sets the ECDSA account whose signatures authorize `trigger_payout_signed`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_payout_signer(&mut self, payout_signer: Option<AccountId>) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::PayoutSigner, &self.payout_signer, &payout_signer);
+            self.payout_signer = payout_signer;
+            Ok(())
+        }
+
+        /// Adds a single payee with weight `share`, taking effect from the next payout.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `PayeeManager` role.
+        /// * `DuplicatePayee`: If `account` is already a payee.
+        /// * `ZeroShare`: If `share` is 0.
+        /// * `TooManyPayees`: If the payee count is already `MAX_PAYEES`.
+        ///
+        #[ink(message)]
+        pub fn add_payee(&mut self, account: AccountId, share: u128) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::PayeeManager)?;
+            if self.payees.contains(&account) {
+                return Err(Error::DuplicatePayee);
+            }
+            if share == 0 {
+                return Err(Error::ZeroShare);
+            }
+            if self.payees.len() >= MAX_PAYEES as usize {
+                return Err(Error::TooManyPayees);
+            }
+
+            self.payees.push(account);
+            self.shares.insert(account, &share);
+            self.total_shares = self.total_shares.saturating_add(share);
+            self.env().emit_event(PayeeAdded { payee: account, share });
+            Ok(())
+        }
+
+        /// Removes `account` from the payees, taking effect from the next payout.
+        ///
+        /// Funds already allocated to `account` stay theirs: its pending claim is kept and the
+        /// shares it accrued while frozen become a pending claim (still blocked until it is
+        /// unfrozen), both claimable through `claim` or `release_for`. Its weight, tier, owed
+        /// amount, contribution and early release of the current round are dropped. Emits
+        /// `PayeeRemoved`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `PayeeManager` role.
+        /// * `PayeeNotFound`: If `account` is not a payee.
+        /// * `NoPayees`: If `account` is the last payee.
+        ///
+        #[ink(message)]
+        pub fn remove_payee(&mut self, account: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::PayeeManager)?;
+            if !self.payees.contains(&account) {
+                return Err(Error::PayeeNotFound);
+            }
+            if self.payees.len() == 1 {
+                return Err(Error::NoPayees);
+            }
+            self.drop_payee(account);
+            Ok(())
+        }
+
+        /// Removes `account` from the payees, turning what it accrued into a pending claim.
+        fn drop_payee(&mut self, account: AccountId) {
+            self.payees.retain(|payee| *payee != account);
+            self.contributors.remove(account);
+            self.forget_votes(account);
+            let weight = self.shares.take(account).unwrap_or(1);
+            self.total_shares = self.total_shares.saturating_sub(weight);
+            self.fixed_amounts.remove(account);
+            self.owed.remove(account);
+            if let Some(contribution) = self.contributions.take(account) {
+                self.total_contributions = self.total_contributions.saturating_sub(contribution);
+            }
+            if let Some(released) = self.released_early.take(account) {
+                self.total_released_early = self.total_released_early.saturating_sub(released);
+            }
+            self.forget_token_releases(account);
+            if let Some(accrued) = self.accrued.take(account) {
+                self.total_accrued = self.total_accrued.saturating_sub(accrued);
+                self.park_payment(account, accrued);
+            }
+            self.env().emit_event(PayeeRemoved { payee: account });
+        }
+
+        /// Adds a batch of payees, pairs of an account and its weight.
+        ///
+        /// The whole batch is validated before storage is touched, so either every account is
+        /// added or none is. Returns the number of payees added.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `PayeeManager` role.
+        /// * `DuplicatePayee`: If an account is already a payee or appears twice in the batch.
+        /// * `ZeroShare`: If a weight is 0.
+        /// * `TooManyPayees`: If the batch would take the payee count above `MAX_PAYEES`.
+        ///
+        #[ink(message)]
+        pub fn add_payees(&mut self, new_payees: Vec<(AccountId, u128)>) -> Result<u32, Error> {
+            self.ensure_caller_has_role(Role::PayeeManager)?;
+            for (i, (payee, share)) in new_payees.iter().enumerate() {
+                if self.payees.contains(payee) || new_payees[..i].iter().any(|(other, _)| other == payee) {
+                    return Err(Error::DuplicatePayee);
+                }
+                if *share == 0 {
+                    return Err(Error::ZeroShare);
+                }
+            }
+            if self.payees.len().saturating_add(new_payees.len()) > MAX_PAYEES as usize {
+                return Err(Error::TooManyPayees);
+            }
+
+            for (payee, share) in new_payees.iter() {
+                self.payees.push(*payee);
+                self.shares.insert(*payee, share);
+                self.total_shares = self.total_shares.saturating_add(*share);
+                self.env().emit_event(PayeeAdded { payee: *payee, share: *share });
+            }
+            Ok(new_payees.len() as u32)
+        }
+
+        /// Removes a batch of payees.
+        ///
+        /// The whole batch is validated before storage is touched, so either every account is
+        /// removed or none is. Each account is removed as by `remove_payee`. Returns the number
+        /// of payees removed.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `PayeeManager` role.
+        /// * `PayeeNotFound`: If an account is not a payee.
+        /// * `DuplicatePayee`: If an account appears twice in the batch.
+        /// * `NoPayees`: If the batch would remove every payee.
+        ///
+        #[ink(message)]
+        pub fn remove_payees(&mut self, accounts: Vec<AccountId>) -> Result<u32, Error> {
+            self.ensure_caller_has_role(Role::PayeeManager)?;
+            for (i, account) in accounts.iter().enumerate() {
+                if !self.payees.contains(account) {
+                    return Err(Error::PayeeNotFound);
+                }
+                if accounts[..i].contains(account) {
+                    return Err(Error::DuplicatePayee);
+                }
+            }
+            if accounts.len() >= self.payees.len() {
+                return Err(Error::NoPayees);
+            }
+
+            for account in accounts.iter() {
+                self.drop_payee(*account);
+            }
+            Ok(accounts.len() as u32)
+        }
+
+        /// Replaces the whole payee list with `new_payees`, pairs of an account and its weight,
+        /// in one step.
+        ///
+        /// The new list is validated before storage is touched. Accounts that are no longer
+        /// payees are removed as by `remove_payee`. Accounts that stay take the weight given in
+        /// `new_payees`. Emits `PayeeRemoved` and `PayeeAdded` for the accounts that left or
+        /// joined and `SharesUpdated` for a changed weight, followed by `PayeesReplaced`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `PayeeManager` role.
+        /// * `NoPayees`: If `new_payees` is empty.
+        /// * `InvalidAccount`: If an account is the all-zero account.
+        /// * `DuplicatePayee`: If an account appears twice in `new_payees`.
+        /// * `ZeroShare`: If a weight is 0.
+        /// * `TooManyPayees`: If `new_payees` has more than `MAX_PAYEES` entries.
+        ///
+        #[ink(message)]
+        pub fn set_payees(&mut self, new_payees: Vec<(AccountId, u128)>) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::PayeeManager)?;
+            if new_payees.is_empty() {
+                return Err(Error::NoPayees);
+            }
+            for (i, (payee, share)) in new_payees.iter().enumerate() {
+                if *payee == AccountId::from([0; 32]) {
+                    return Err(Error::InvalidAccount);
+                }
+                if new_payees[..i].iter().any(|(other, _)| other == payee) {
+                    return Err(Error::DuplicatePayee);
+                }
+                if *share == 0 {
+                    return Err(Error::ZeroShare);
+                }
+            }
+            if new_payees.len() > MAX_PAYEES as usize {
+                return Err(Error::TooManyPayees);
+            }
+
+            let old_count = self.payees.len() as u32;
+            let leaving: Vec<AccountId> = self.payees
+                .iter()
+                .filter(|payee| !new_payees.iter().any(|(account, _)| account == *payee))
+                .copied()
+                .collect();
+            for payee in leaving {
+                self.drop_payee(payee);
+            }
+            let staying = core::mem::take(&mut self.payees);
+            for (payee, share) in new_payees.iter() {
+                self.payees.push(*payee);
+                if staying.contains(payee) {
+                    let old = self.shares.get(payee).unwrap_or(1);
+                    if old != *share {
+                        self.shares.insert(*payee, share);
+                        self.total_shares = self.total_shares.saturating_sub(old).saturating_add(*share);
+                        self.env().emit_event(SharesUpdated { payee: *payee, old, new: *share });
+                    }
+                } else {
+                    self.shares.insert(*payee, share);
+                    self.total_shares = self.total_shares.saturating_add(*share);
+                    self.env().emit_event(PayeeAdded { payee: *payee, share: *share });
+                }
+            }
+            self.env().emit_event(PayeesReplaced {
+                old_count,
+                new_count: self.payees.len() as u32,
+            });
+            Ok(())
+        }
+
+        /// Replaces the address of payee `old` with `new`, keeping its position in the payee
+        /// list.
+        ///
+        /// Everything recorded for `old` moves to `new`: tier, weight, owed amount, contribution,
+        /// pending claim, claim lock, child-splitter flag and payment hook.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `PayeeManager` role.
+        /// * `PayeeNotFound`: If `old` is not a payee.
+        /// * `DuplicatePayee`: If `new` is already a payee.
+        ///
+        #[ink(message)]
+        pub fn replace_payee(&mut self, old: AccountId, new: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::PayeeManager)?;
+            let position = self.payees
+                .iter()
+                .position(|payee| *payee == old)
+                .ok_or(Error::PayeeNotFound)?;
+            if self.payees.contains(&new) {
+                return Err(Error::DuplicatePayee);
+            }
+
+            self.payees[position] = new;
+            self.forget_votes(old);
+            Self::move_entry(&mut self.fixed_amounts, old, new);
+            Self::move_entry(&mut self.shares, old, new);
+            Self::move_entry(&mut self.owed, old, new);
+            Self::move_entry(&mut self.contributions, old, new);
+            Self::move_entry(&mut self.contributors, old, new);
+            Self::move_entry(&mut self.released_early, old, new);
+            Self::move_entry(&mut self.pending_claims, old, new);
+            if let Some(position) = self.retry_queue.iter().position(|account| *account == old) {
+                self.retry_queue[position] = new;
+            }
+            Self::move_entry(&mut self.claim_unlock_block, old, new);
+            Self::move_entry(&mut self.frozen, old, new);
+            Self::move_entry(&mut self.accrued, old, new);
+            Self::move_entry(&mut self.vesting_schedules, old, new);
+            Self::move_entry(&mut self.vesting_credited, old, new);
+            Self::move_entry(&mut self.vesting_released, old, new);
+            Self::move_entry(&mut self.child_splitters, old, new);
+            Self::move_entry(&mut self.payment_hooks, old, new);
+            for token in self.registered_tokens.iter() {
+                if let Some(released) = self.token_released_early.take((*token, old)) {
+                    self.token_released_early.insert((*token, new), &released);
+                }
+                if let Some(pending) = self.token_pending_claims.take((*token, old)) {
+                    self.token_pending_claims.insert((*token, new), &pending);
+                }
+            }
+            self.env().emit_event(PayeeReplaced { old, new });
+            Ok(())
+        }
+
+        /// Moves the entry of `old` in `mapping`, if any, to `new`.
+        fn move_entry<V: Packed + scale::EncodeLike, K: StorageKey>(
+            mapping: &mut Mapping<AccountId, V, K>,
+            old: AccountId,
+            new: AccountId
+        ) {
+            if let Some(value) = mapping.take(old) {
+                mapping.insert(new, &value);
+            }
+        }
+
+        /// Returns everything the contract has ever received through `deposit` and
+        /// `deposit_to_pool`.
+        #[ink(message)]
+        pub fn total_received(&self) -> Balance {
+            self.total_received
+        }
+
+        /// Returns everything the contract has ever transferred out, including `poke` tips.
+        #[ink(message)]
+        pub fn total_distributed(&self) -> Balance {
+            self.total_distributed
+        }
+
+        /// Returns the funds that arrived without a deposit, i.e. the current balance plus
+        /// `total_distributed` minus `total_received`.
+        #[ink(message)]
+        pub fn untracked_balance(&self) -> Balance {
+            self.env()
+                .balance()
+                .saturating_add(self.total_distributed)
+                .saturating_sub(self.total_received)
+        }
+
+        /// Returns the contract version and a summary of its configuration in a single call.
+        #[ink(message)]
+        pub fn contract_info(&self) -> ContractInfo {
+            ContractInfo {
+                version: VERSION,
+                designated_payee: self.designated_payee,
+                operator: self.operator,
+                payee_count: self.payees.len() as u32,
+                balance: self.env().balance(),
+                distributable: self.distributable(),
+                split_mode: self.split_mode,
+                payout_nonce: self.payout_nonce,
+                payout_cooldown: self.payout_cooldown,
+                deposit_deadline: self.deposit_deadline,
+                approval_threshold: self.approval_threshold,
+                paused: self.paused,
+                reserve: self.reserve,
+            }
+        }
+
+        /// Returns the accounting state of the contract in a single call.
+        ///
+        /// Only the payees from `offset` up to `limit` entries are included, so large payee
+        /// lists can be paged; `payee_count` tells how many there are in total.
+        #[ink(message)]
+        pub fn export_state(&self, offset: u32, limit: u32) -> StateSnapshot {
+            let payees = self.payees
+                .iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .map(|payee| PayeeSnapshot {
+                    account: *payee,
+                    tier: self.payee_tier(*payee),
+                    share: self.shares_of(*payee),
+                    pending_claim: self.pending_claim_of(*payee),
+                })
+                .collect();
+            StateSnapshot {
+                designated_payee: self.designated_payee,
+                operator: self.operator,
+                payee_count: self.payees.len() as u32,
+                payees,
+                balance: self.env().balance(),
+                distributable: self.distributable(),
+                total_pending: self.total_pending,
+                pooled_balance: self.pooled_balance,
+                split_mode: self.split_mode,
+                remainder_recipient: self.remainder_recipient(),
+                payout_nonce: self.payout_nonce,
+                round_id: self.round_id,
+                paused: self.paused,
+                reserve: self.reserve,
+                total_received: self.total_received,
+                total_distributed: self.total_distributed,
+            }
+        }
+
+        /// Returns the current payout nonce, i.e. the number of completed payout rounds.
+        #[ink(message)]
+        pub fn payout_nonce(&self) -> u64 {
+            self.payout_nonce
+        }
+
+        /// Returns the id of the payout round currently open; the next payout completes it.
+        #[ink(message)]
+        pub fn current_round(&self) -> u64 {
+            self.round_id
+        }
+
+        /// Sets the minimum number of blocks between two payouts; 0 disables the cooldown.
+        ///
+        /// Rate-limits the payout messages, so a compromised or buggy trigger key cannot spam
+        /// dust payouts; an early payout fails with `CooldownActive`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_payout_cooldown(&mut self, blocks: BlockNumber) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::PayoutCooldown, &self.payout_cooldown, &blocks);
+            self.payout_cooldown = blocks;
+            Ok(())
+        }
+
+        /// Returns the minimum number of blocks between two payouts (0 means disabled).
+        #[ink(message)]
+        pub fn payout_cooldown(&self) -> BlockNumber {
+            self.payout_cooldown
+        }
+
+        /// Returns the first block at which the payout cooldown allows the next payout; see
+        /// `next_payout_at` for the payout period.
+        #[ink(message)]
+        pub fn next_payout_block(&self) -> BlockNumber {
+            match self.last_payout_block {
+                Some(block) => block.saturating_add(self.payout_cooldown),
+                None => 0,
+            }
+        }
+
+        /// Returns the first block at which the next payout is allowed, taking both the
+        /// cooldown and the payout period into account.
+        ///
+        /// With a payout period of `p` blocks, block `n` falls in period `n / p`, and a payout
+        /// may only run in a later period than the last one.
+        #[ink(message)]
+        pub fn next_payout_at(&self) -> BlockNumber {
+            let next_period = match self.last_payout_block {
+                Some(block) if self.payout_period > 0 => {
+                    (block / self.payout_period).saturating_add(1).saturating_mul(self.payout_period)
+                }
+                _ => 0,
+            };
+            self.next_payout_block().max(next_period)
+        }
+
+        /// Sets the length of the payout periods in blocks; 0 disables them.
+        ///
+        /// At most one payout (manual or scheduled) runs per period, e.g. one per month with
+        /// the number of blocks in a month, so the payees can rely on a recurring payout.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_payout_period(&mut self, period: BlockNumber) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::PayoutPeriod, &self.payout_period, &period);
+            self.payout_period = period;
+            Ok(())
+        }
+
+        /// Returns the length of the payout periods in blocks (0 means disabled).
+        #[ink(message)]
+        pub fn payout_period(&self) -> BlockNumber {
+            self.payout_period
+        }
+
+        /// Sets the number of blocks without a payout after which anyone may trigger one; 0
+        /// disables the deadline.
+        ///
+        /// If the designated payee and the operator disappear, the balance would be stranded.
+        /// Once `blocks` blocks passed since the last payout (or the instantiation, if there
+        /// was none), every account may call `trigger_payout` and its lossy and batched variants,
+        /// until the next payout restarts the deadline. Partial, pool, token and committed
+        /// payouts stay with the authorized accounts.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_trigger_deadline(&mut self, blocks: BlockNumber) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::TriggerDeadline, &self.trigger_deadline, &blocks);
+            self.trigger_deadline = blocks;
+            Ok(())
+        }
+
+        /// Returns the number of blocks without a payout after which anyone may trigger one
+        /// (0 means disabled).
+        #[ink(message)]
+        pub fn trigger_deadline(&self) -> BlockNumber {
+            self.trigger_deadline
+        }
+
+        /// Sets the cut of a permissionless payout paid to whoever triggers it, in basis points
+        /// (100 is 1%); 0 disables the bounty.
+        ///
+        /// While anyone may trigger payouts (after `renounce_designation` or once the trigger
+        /// deadline has passed), `trigger_payout` first transfers this cut of the distributable
+        /// balance to its caller and splits the rest, so keeper bots are paid for running the
+        /// payout. The bounty is announced in a `TriggerBountyPaid` event.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `InvalidBasisPoints`: If `bps` exceeds `TOTAL_BASIS_POINTS`.
+        ///
+        #[ink(message)]
+        pub fn set_trigger_bounty_bps(&mut self, bps: u32) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if bps > TOTAL_BASIS_POINTS {
+                return Err(Error::InvalidBasisPoints);
+            }
+            self.emit_config_changed(ConfigField::TriggerBountyBps, &self.trigger_bounty_bps, &bps);
+            self.trigger_bounty_bps = bps;
+            Ok(())
+        }
+
+        /// Returns the cut of a permissionless payout paid to its caller, in basis points.
+        #[ink(message)]
+        pub fn trigger_bounty_bps(&self) -> u32 {
+            self.trigger_bounty_bps
+        }
+
+        /// Returns the first block from which anyone may trigger a payout, or `None` if the
+        /// trigger deadline is disabled.
+        #[ink(message)]
+        pub fn permissionless_trigger_at(&self) -> Option<BlockNumber> {
+            if self.trigger_deadline == 0 {
+                return None;
+            }
+            let since = self.last_payout_block.unwrap_or(self.instantiated_at);
+            Some(since.saturating_add(self.trigger_deadline))
+        }
+
+        /// Runs the scheduled payout if it is due; anyone may call this.
+        ///
+        /// A payout is due once `payout_interval` blocks have passed since the last payout
+        /// (manual or scheduled) and the distributable balance exceeds the minimum balance and
+        /// the tip. The caller receives the tip before the rest is split. Returns whether a
+        /// payout happened.
+        ///
+        /// # Errors
+        ///
+        /// * `ReentrancyGuardLocked` / `CooldownActive` / `InsufficientApprovals`: As for
+        ///   `trigger_payout`.
+        /// * `TransferFailed`: If the tip cannot be transferred.
+        ///
+        #[ink(message)]
+        pub fn poke(&mut self) -> Result<bool, Error> {
+            if self.payout_interval == 0 {
+                return Ok(false);
+            }
+            if let Some(last) = self.last_payout_block {
+                if self.env().block_number() < last.saturating_add(self.payout_interval) {
+                    return Ok(false);
+                }
+            }
+            let distributable = self.distributable();
+            if distributable <= self.poke_min_balance || distributable <= self.poke_tip {
+                return Ok(false);
+            }
+            self.ensure_not_paused()?;
+            self.ensure_no_payout_batch()?;
+            self.ensure_reentrancy_guard_not_locked()?;
+            self.ensure_cooldown_elapsed()?;
+            self.ensure_payout_approved()?;
+
+            if self.poke_tip > 0 {
+                self.transfer_to(self.env().caller(), self.poke_tip)?;
+                self.total_distributed = self.total_distributed.saturating_add(self.poke_tip);
+                self.consume_accrued_deposits(self.poke_tip);
+            }
+            self.payout_native()?;
+            Ok(true)
+        }
+
+        /// Configures scheduled payouts; an `interval` of 0 disables them.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_payout_schedule(
+            &mut self,
+            interval: BlockNumber,
+            min_balance: Balance,
+            tip: Balance
+        ) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(
+                ConfigField::PayoutSchedule,
+                &self.payout_schedule(),
+                &(interval, min_balance, tip)
+            );
+            self.payout_interval = interval;
+            self.poke_min_balance = min_balance;
+            self.poke_tip = tip;
+            Ok(())
+        }
+
+        /// Returns the payout interval, the `poke` minimum balance and the `poke` tip.
+        #[ink(message)]
+        pub fn payout_schedule(&self) -> (BlockNumber, Balance, Balance) {
+            (self.payout_interval, self.poke_min_balance, self.poke_tip)
+        }
+
+        /// Creates an additional payout pool with its own payees and returns its id.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `NoPayees`: If `payees` is empty.
+        /// * `TooManyPayees`: If `payees` has more than `MAX_PAYEES` entries.
+        /// * `DuplicatePayee`: If an account appears twice in `payees`.
+        ///
+        #[ink(message)]
+        pub fn create_pool(&mut self, payees: Vec<AccountId>) -> Result<u32, Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if payees.is_empty() {
+                return Err(Error::NoPayees);
+            }
+            if payees.len() > MAX_PAYEES as usize {
+                return Err(Error::TooManyPayees);
+            }
+            if payees.iter().enumerate().any(|(i, payee)| payees[..i].contains(payee)) {
+                return Err(Error::DuplicatePayee);
+            }
+            let pool_id = self.next_pool_id;
+            self.pools.insert(pool_id, &Pool { payees, balance: 0, last_payout_block: None });
+            self.next_pool_id = self.next_pool_id.saturating_add(1);
+            Ok(pool_id)
+        }
+
+        /// Deposits funds into a payout pool.
+        ///
+        /// Pool 0 is the contract's own payee list, so depositing into it is the same as `deposit`.
+        ///
+        /// # Errors
+        ///
+        /// * `DepositsClosed`: If the deposit deadline has passed.
+        /// * `ZeroShare`: If the transferred value is zero.
+        /// * `BelowMinimumDeposit` / `DepositTooLarge`: If the transferred value is outside the
+        ///   deposit limits.
+        /// * `PoolNotFound`: If no pool exists with the given id.
+        ///
+        #[ink(message, payable)]
+        pub fn deposit_to_pool(&mut self, pool_id: u32) -> Result<(), Error> {
+            if pool_id == 0 {
+                return self.deposit();
+            }
+            self.ensure_deposits_open()?;
+            let transferred_value = self.env().transferred_value();
+            if transferred_value == 0 {
+                return Err(Error::ZeroShare);
+            }
+            self.ensure_deposit_within_limits(transferred_value)?;
+            let mut pool = self.pools.get(pool_id).ok_or(Error::PoolNotFound)?;
+            pool.balance = pool.balance.saturating_add(transferred_value);
+            self.pools.insert(pool_id, &pool);
+            self.pooled_balance = self.pooled_balance.saturating_add(transferred_value);
+            self.record_deposit(self.env().caller(), transferred_value);
+            self.env().emit_event(PoolDeposit {
+                pool_id,
+                from: self.env().caller(),
+                value: transferred_value,
+            });
+            Ok(())
+        }
+
+        /// Splits a pool's accounted balance equally among the pool's payees.
+        ///
+        /// Pool 0 is the contract's own payee list, so paying it out is the same as `trigger_payout`.
+        /// Other pools pass the same checks as a payout round: the pause, the payout approvals
+        /// and the payout cooldown, which also applies between two payouts of the same pool. A
+        /// pool payout consumes the approvals and advances the payout nonce like a round. Shares of frozen, vesting and claim-locked payees, and
+        /// failed transfers, are handled like in `trigger_payout_lossy`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `ContractPaused`: If the contract is paused.
+        /// * `PayoutInProgress`: If a batched payout round is in progress.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `CooldownActive`: If the payout cooldown has not elapsed yet.
+        /// * `InsufficientApprovals`: If the payout has not been approved by enough approvers.
+        /// * `PoolNotFound`: If no pool exists with the given id.
+        /// * `ZeroShare`: If the pool's balance is too small to give every payee something.
+        /// * `BelowThreshold`: If a pool payee's share is below `min_payout_per_payee`.
+        /// * `TooManyTransfers`: If the pool payout needs more transfers than `max_transfers_per_call`.
+        ///
+        #[ink(message)]
+        pub fn trigger_pool_payout(&mut self, pool_id: u32) -> Result<(), Error> {
+            if pool_id == 0 {
+                return self.trigger_payout().map(|_| ());
+            }
+            self.ensure_caller_can_trigger_payout()?;
+            self.ensure_native_payout_allowed()?;
+
+            let mut pool = self.pools.get(pool_id).ok_or(Error::PoolNotFound)?;
+            if let Some(block) = pool.last_payout_block {
+                if self.env().block_number() < block.saturating_add(self.payout_cooldown) {
+                    return Err(Error::CooldownActive);
+                }
+            }
+            let remainder = self.remainder_account_among(&pool.payees);
+            let payout_info = compute_distribution(pool.balance, &pool.payees, remainder)?;
+            let payout_info = self.without_carry_over(payout_info);
+            self.ensure_above_payout_threshold(&pool.payees, &payout_info)?;
+            self.ensure_within_transfer_limit(payout_info.len())?;
+            self.emit_remainder_draw(pool_id, &pool.payees);
+
+            self.with_reentrancy_guard(|contract| {
+                let (total, _) = contract.release_shares(&payout_info);
+                let settled = payout_info
+                    .iter()
+                    .fold(0, |settled: Balance, info| settled.saturating_add(info.amount));
+
+                pool.balance = pool.balance.saturating_sub(settled);
+                pool.last_payout_block = Some(contract.env().block_number());
+                contract.pools.insert(pool_id, &pool);
+                contract.pooled_balance = contract.pooled_balance.saturating_sub(settled);
+                contract.advance_payout_nonce();
+                contract.env().emit_event(PoolPayout {
+                    pool_id,
+                    total,
+                    num_payees: payout_info.len() as u32,
+                });
+                Ok(())
+            })
+        }
+
+        /// Returns the accounted balance of a payout pool.
+        ///
+        /// For pool 0 this is the contract balance not claimed by any additional pool.
+        #[ink(message)]
+        pub fn pool_balance(&self, pool_id: u32) -> Result<Balance, Error> {
+            if pool_id == 0 {
+                return Ok(self.distributable());
+            }
+            self.pools
+                .get(pool_id)
+                .map(|pool| pool.balance)
+                .ok_or(Error::PoolNotFound)
+        }
+
+        /// Returns the payees of a payout pool.
+        #[ink(message)]
+        pub fn pool_payees(&self, pool_id: u32) -> Result<Vec<AccountId>, Error> {
+            if pool_id == 0 {
+                return Ok(self.payees.clone());
+            }
+            self.pools
+                .get(pool_id)
+                .map(|pool| pool.payees)
+                .ok_or(Error::PoolNotFound)
+        }
+
+        /// Sets the priority tier of a payee.
+        ///
+        /// Tier-1 payees receive their fixed amount before the remaining balance is split
+        /// equally among the tier-2 payees.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee.
+        /// * `ZeroShare`: If a tier-1 payee is given a fixed amount of zero.
+        ///
+        #[ink(message)]
+        pub fn set_payee_tier(&mut self, payee: AccountId, tier: PayeeTier) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            let old = self.payee_tier(payee);
+            match tier {
+                PayeeTier::Fixed(0) => return Err(Error::ZeroShare),
+                PayeeTier::Fixed(amount) => {
+                    self.fixed_amounts.insert(payee, &amount);
+                }
+                PayeeTier::Residual => self.fixed_amounts.remove(payee),
+            }
+            self.emit_config_changed(ConfigField::Tier(payee), &old, &tier);
+            Ok(())
+        }
+
+        /// Returns the priority tier of a payee.
+        #[ink(message)]
+        pub fn payee_tier(&self, payee: AccountId) -> PayeeTier {
+            match self.fixed_amounts.get(payee) {
+                Some(amount) => PayeeTier::Fixed(amount),
+                None => PayeeTier::Residual,
+            }
+        }
+
+        /// Returns the weight of `payee` in the proportional split, or 0 if it is not a payee.
+        #[ink(message)]
+        pub fn shares_of(&self, payee: AccountId) -> u128 {
+            if !self.payees.contains(&payee) {
+                return 0;
+            }
+            self.shares.get(payee).unwrap_or(1)
+        }
+
+        /// Returns the registered payees with their weights, in payout order.
+        #[ink(message)]
+        pub fn get_payees_with_shares(&self) -> Vec<(AccountId, u128)> {
+            self.payees
+                .iter()
+                .map(|payee| (*payee, self.shares.get(payee).unwrap_or(1)))
+                .collect()
+        }
+
+        /// Returns the sum of the weights of all payees.
+        #[ink(message)]
+        pub fn total_shares(&self) -> u128 {
+            self.total_shares
+        }
+
+        /// Sets the weight of `payee` in the proportional split to `new_share`.
+        ///
+        /// Unlike removing and re-adding the payee, this keeps everything else recorded for it.
+        /// Only future payout calculations are affected; committed payout plans keep their
+        /// amounts.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `PayeeManager` role.
+        /// * `PayeeNotFound`: If `payee` is not a payee.
+        /// * `ZeroShare`: If `new_share` is 0.
+        ///
+        #[ink(message)]
+        pub fn update_share(&mut self, payee: AccountId, new_share: u128) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::PayeeManager)?;
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            if new_share == 0 {
+                return Err(Error::ZeroShare);
+            }
+            let old = self.shares_of(payee);
+            self.shares.insert(payee, &new_share);
+            self.total_shares = self.total_shares.saturating_sub(old).saturating_add(new_share);
+            self.env().emit_event(SharesUpdated { payee, old, new: new_share });
+            Ok(())
+        }
+
+        /// Returns the share of `payee` in the proportional split in basis points, rounded down.
+        ///
+        /// This is the payee's weight relative to `total_shares`, so it stays meaningful after
+        /// payees are added or replaced; 0 if `payee` is not a payee.
+        #[ink(message)]
+        pub fn basis_points_of(&self, payee: AccountId) -> u32 {
+            if self.total_shares == 0 {
+                return 0;
+            }
+            let scaled = self.shares_of(payee).saturating_mul(TOTAL_BASIS_POINTS as u128);
+            (scaled / self.total_shares) as u32
+        }
+
+        /// Sets the amount a payee is owed in owed mode.
+        ///
+        /// The amount replaces any previous target; it decreases as payouts are made.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `PayeeNotFound`: If `payee` is not a registered payee.
+        ///
+        #[ink(message)]
+        pub fn set_owed(&mut self, payee: AccountId, amount: Balance) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            self.emit_config_changed(ConfigField::Owed(payee), &self.owed(payee), &amount);
+            self.owed.insert(payee, &amount);
+            Ok(())
+        }
+
+        /// Returns the amount a payee is still owed in owed mode.
+        #[ink(message)]
+        pub fn owed(&self, payee: AccountId) -> Balance {
+            self.owed.get(payee).unwrap_or(0)
+        }
+
+        /// Returns what `depositor` contributed since the last payout in contributions mode.
+        #[ink(message)]
+        pub fn contribution_of(&self, depositor: AccountId) -> Balance {
+            self.contributions.get(depositor).unwrap_or(0)
+        }
+
+        /// Returns how the contract balance is divided among the payees.
+        #[ink(message)]
+        pub fn split_mode(&self) -> SplitMode {
+            self.split_mode
+        }
+
+        /// Marks or unmarks a payee as a child splitter.
+        ///
+        /// After a child splitter receives its share, `trigger_payout` calls its
+        /// `notify_and_distribute` message so the funds flow on to the child's own payees.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_child_splitter(&mut self, payee: AccountId, is_child: bool) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::ChildSplitter(payee), &self.is_child_splitter(payee), &is_child);
+            if is_child {
+                self.child_splitters.insert(payee, &());
+            } else {
+                self.child_splitters.remove(payee);
+            }
+            Ok(())
+        }
+
+        /// Returns whether `payee` is marked as a child splitter.
+        #[ink(message)]
+        pub fn is_child_splitter(&self, payee: AccountId) -> bool {
+            self.child_splitters.contains(payee)
+        }
+
+        /// Enables or disables the payment hook of a payee contract.
+        ///
+        /// Payees with the hook enabled are paid by calling their payable
+        /// `on_payment_received(amount: Balance)` message with the amount attached, limited to
+        /// the `payment_hook_gas_limit` stipend. Payees without the hook receive plain transfers,
+        /// which never execute payee code.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_payment_hook(&mut self, payee: AccountId, enabled: bool) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::PaymentHook(payee), &self.has_payment_hook(payee), &enabled);
+            if enabled {
+                self.payment_hooks.insert(payee, &());
+            } else {
+                self.payment_hooks.remove(payee);
+            }
+            Ok(())
+        }
+
+        /// Returns whether `payee` is paid through its `on_payment_received` hook.
+        #[ink(message)]
+        pub fn has_payment_hook(&self, payee: AccountId) -> bool {
+            self.payment_hooks.contains(payee)
+        }
+
+        /// Sets the reference-time stipend of each `on_payment_received` hook call.
+        ///
+        /// A hook that runs out of gas or reverts emits `PaymentHookFailed` and the amount is
+        /// transferred without the hook, so the rest of the round still completes.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `ZeroShare`: If `gas_limit` is zero, which the chain would treat as unlimited.
+        ///
+        #[ink(message)]
+        pub fn set_payment_hook_gas_limit(&mut self, gas_limit: u64) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if gas_limit == 0 {
+                return Err(Error::ZeroShare);
+            }
+            self.emit_config_changed(ConfigField::PaymentHookGasLimit, &self.payment_hook_gas_limit, &gas_limit);
+            self.payment_hook_gas_limit = gas_limit;
+            Ok(())
+        }
+
+        /// Returns the reference-time stipend of each `on_payment_received` hook call.
+        #[ink(message)]
+        pub fn payment_hook_gas_limit(&self) -> u64 {
+            self.payment_hook_gas_limit
+        }
+
+        /// Sets the parent splitter allowed to call `notify_and_distribute`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_parent_splitter(&mut self, parent: Option<AccountId>) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::ParentSplitter, &self.parent_splitter, &parent);
+            self.parent_splitter = parent;
+            Ok(())
+        }
+
+        /// Distributes the contract balance on behalf of a parent splitter.
+        ///
+        /// Called by the parent splitter after it has paid this contract its share.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the `designated_payee`, the operator or the parent splitter.
+        /// * Any error returned by `trigger_payout`.
+        ///
+        #[ink(message)]
+        pub fn notify_and_distribute(&mut self) -> Result<(), Error> {
+            if Some(self.env().caller()) != self.parent_splitter {
+                self.ensure_caller_can_trigger_full_payout()?;
+            }
+            self.payout_native().map(|_| ())
+        }
+
+        /// Asks a child splitter to distribute the share it has just received.
+        ///
+        /// A failed nested call does not fail the parent payout; it is only reported through
+        /// the `ChildSplitterNotified` event. The reentrancy guard stays locked during the call,
+        /// so the child cannot call back into this contract.
+        fn notify_child_splitter(&self, child: AccountId) {
+            let result = build_call::<Environment>()
+                .call(child)
+                .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!("notify_and_distribute"))))
+                .returns::<Result<(), Error>>()
+                .try_invoke();
+            self.env().emit_event(ChildSplitterNotified {
+                child,
+                success: matches!(result, Ok(Ok(Ok(())))),
+            });
+        }
+
+        /// Pays `amount` to `payee`, through its `on_payment_received` hook if it has one.
+        ///
+        /// If the hook call fails, the failure is reported through `PaymentHookFailed` and the
+        /// amount is transferred without it.
+        fn deliver_payment(&self, payee: AccountId, amount: Balance) -> Result<(), Error> {
+            if self.payment_hooks.contains(payee) {
+                let result = build_call::<Environment>()
+                    .call(payee)
+                    .ref_time_limit(self.payment_hook_gas_limit)
+                    .transferred_value(amount)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ink::selector_bytes!("on_payment_received")))
+                            .push_arg(amount)
+                    )
+                    .returns::<()>()
+                    .try_invoke();
+                if matches!(result, Ok(Ok(()))) {
+                    return Ok(());
+                }
+                self.env().emit_event(PaymentHookFailed { payee, amount });
+            }
+            self.transfer_to(payee, amount)
+        }
+
+        /// Registers a PSP22 token whose balance will be split by `trigger_payout_all_assets`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `TokenAlreadyRegistered`: If the token is already registered.
+        ///
+        #[ink(message)]
+        pub fn register_token(&mut self, token: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if self.is_registered_token.contains(token) {
+                return Err(Error::TokenAlreadyRegistered);
+            }
+            self.is_registered_token.insert(token, &());
+            self.registered_tokens.push(token);
+            self.emit_config_changed(ConfigField::Token(token), &false, &true);
+            Ok(())
+        }
+
+        /// Removes a PSP22 token from the list of registered assets.
+        ///
+        /// The token must be paid out first: once unregistered, `rescue_token` could sweep it.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `TokenNotRegistered`: If the token is not registered.
+        /// * `TokenUndistributed`: If more of the token was deposited than distributed, or token
+        ///   claims are still pending.
+        ///
+        #[ink(message)]
+        pub fn unregister_token(&mut self, token: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.is_registered_token.contains(token) {
+                return Err(Error::TokenNotRegistered);
+            }
+            let received = self.token_received.get(token).unwrap_or(0);
+            let distributed = self.token_distributed.get(token).unwrap_or(0);
+            if received > distributed || self.token_total_pending.get(token).unwrap_or(0) > 0 {
+                return Err(Error::TokenUndistributed);
+            }
+            self.reset_token_early_releases(token);
+            self.is_registered_token.remove(token);
+            self.registered_tokens.retain(|registered| *registered != token);
+            self.emit_config_changed(ConfigField::Token(token), &true, &false);
+            Ok(())
+        }
+
+        /// Returns the registered PSP22 tokens.
+        #[ink(message)]
+        pub fn registered_tokens(&self) -> Vec<AccountId> {
+            self.registered_tokens.clone()
+        }
+
+        /// Adds a PSP22 token to the supported assets. Same as `register_token`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `TokenAlreadyRegistered`: If the token is already supported.
+        ///
+        #[ink(message)]
+        pub fn add_asset(&mut self, token: AccountId) -> Result<(), Error> {
+            self.register_token(token)
+        }
+
+        /// Removes a PSP22 token from the supported assets. Same as `unregister_token`.
+        ///
+        /// Amounts already distributed stay accounted for if the token is added again.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `TokenNotRegistered`: If the token is not supported.
+        /// * `TokenUndistributed`: If the token is not fully distributed yet.
+        ///
+        #[ink(message)]
+        pub fn remove_asset(&mut self, token: AccountId) -> Result<(), Error> {
+            self.unregister_token(token)
+        }
+
+        /// Returns the supported assets with the total distributed of each: the native balance
+        /// first, then the registered tokens in registration order.
+        #[ink(message)]
+        pub fn list_assets(&self) -> Vec<AssetInfo> {
+            let mut assets = Vec::with_capacity(self.registered_tokens.len() + 1);
+            assets.push(AssetInfo {
+                token: None,
+                total_received: self.total_received,
+                total_distributed: self.total_distributed,
+            });
+            for token in self.registered_tokens.iter() {
+                assets.push(AssetInfo {
+                    token: Some(*token),
+                    total_received: self.token_received.get(token).unwrap_or(0),
+                    total_distributed: self.token_distributed.get(token).unwrap_or(0),
+                });
+            }
+            assets
+        }
+
+        /// Deposits `amount` of a registered PSP22 `token` from the caller.
+        ///
+        /// The tokens are pulled with `PSP22::transfer_from`, so the caller must first approve
+        /// the contract for at least `amount`. The deposit is counted in
+        /// `token_deposited_by(token, caller)` and in the token's `total_received`.
+        ///
+        /// # Errors
+        ///
+        /// * `TokenNotRegistered`: If the token is not registered.
+        /// * `ContractPaused` / `DepositsClosed`: If the contract is paused or the deposit
+        ///   deadline has passed.
+        /// * `ZeroShare`: If `amount` is zero.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `TokenCallFailed`: If the call to the token contract fails.
+        /// * `TransferFailed`: If the token rejects the transfer, e.g. for a missing allowance.
+        ///
+        #[ink(message)]
+        pub fn deposit_token(&mut self, token: AccountId, amount: Balance) -> Result<(), Error> {
+            if !self.is_registered_token.contains(token) {
+                return Err(Error::TokenNotRegistered);
+            }
+            self.ensure_deposits_open()?;
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+            self.ensure_reentrancy_guard_not_locked()?;
+
+            let caller = self.env().caller();
+            self.with_reentrancy_guard(|contract| contract.token_transfer_from(token, caller, amount))?;
+
+            let deposited = self.token_deposited.get((token, caller)).unwrap_or(0);
+            self.token_deposited.insert((token, caller), &deposited.saturating_add(amount));
+            let received = self.token_received.get(token).unwrap_or(0);
+            self.token_received.insert(token, &received.saturating_add(amount));
+            self.env().emit_event(TokenDeposit { token, from: caller, amount });
+            Ok(())
+        }
+
+        /// Returns the amount of `token` deposited by `account` through `deposit_token`.
+        #[ink(message)]
+        pub fn token_deposited_by(&self, token: AccountId, account: AccountId) -> Balance {
+            self.token_deposited.get((token, account)).unwrap_or(0)
+        }
+
+        /// Sweeps `amount` of a PSP22 `token` that was sent to the contract by mistake to `to`.
+        ///
+        /// Only tokens the splitter does not manage can be rescued: registered tokens belong to
+        /// the payees and must be removed with `remove_asset` first.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `AssetManaged`: If the token is registered.
+        /// * `InvalidAccount`: If `to` is the all-zero account.
+        /// * `ZeroShare`: If `amount` is zero.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `TokenCallFailed`: If the call to the token contract fails.
+        /// * `TransferFailed`: If the token rejects the transfer.
+        ///
+        #[ink(message)]
+        pub fn rescue_token(&mut self, token: AccountId, to: AccountId, amount: Balance) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if self.is_registered_token.contains(token) {
+                return Err(Error::AssetManaged);
+            }
+            if to == AccountId::from([0; 32]) {
+                return Err(Error::InvalidAccount);
+            }
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+            self.ensure_reentrancy_guard_not_locked()?;
+
+            self.with_reentrancy_guard(|contract| contract.token_transfer(token, to, amount))?;
+            self.env().emit_event(TokenRescued { token, to, amount });
+            Ok(())
+        }
+
+        /// Splits the native balance and the balance of every registered token among the payees.
+        ///
+        /// The checks of a payout round run once for all assets. A failure on one asset does
+        /// not abort the others: the outcome of each asset is reported in the returned vector,
+        /// native balance first, then the tokens in registration order.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `ContractPaused`: If the contract is paused.
+        /// * `PayoutInProgress`: If a batched payout round is in progress.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `CooldownActive`: If the payout cooldown has not elapsed yet.
+        /// * `InsufficientApprovals`: If the payout has not been approved by enough approvers.
+        ///
+        #[ink(message)]
+        pub fn trigger_payout_all_assets(&mut self) -> Result<Vec<AssetPayoutResult>, Error> {
+            self.ensure_caller_can_trigger_payout()?;
+            self.ensure_native_payout_allowed()?;
+
+            let native = self
+                .nonzero_distributable()
+                .and_then(|snapshot| self.pay_out_snapshot(snapshot))
+                .map(|report| Self::transferred_total(&report));
+            let round_closed = native.is_ok();
+            let mut results = Vec::new();
+            results.push(AssetPayoutResult { token: None, result: native });
+            for token in self.registered_tokens.clone() {
+                results.push(AssetPayoutResult {
+                    token: Some(token),
+                    result: self.pay_out_token(token),
+                });
+            }
+            if !round_closed && results.iter().any(|asset| asset.result.is_ok()) {
+                self.advance_payout_nonce();
+            }
+            Ok(results)
+        }
+
+        /// Splits the contract's balance of a registered PSP22 `token` among the payees and
+        /// returns the total transferred.
+        ///
+        /// The balance is read from the token contract and split with the same weights, tiers
+        /// and remainder handling as the native balance.
+        ///
+        /// The token payout passes the same checks as a native payout round, consumes the
+        /// payout approvals and advances the payout nonce. The payout cooldown also applies
+        /// between two payouts of the same token. The share of a frozen, claim-locked or still
+        /// vesting payee, and a share whose transfer fails, is parked as a token claim that the
+        /// payee withdraws with `claim_token`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger` role.
+        /// * `TokenNotRegistered`: If the token is not registered.
+        /// * `ContractPaused`: If the contract is paused.
+        /// * `PayoutInProgress`: If a batched payout round is in progress.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `CooldownActive`: If the payout cooldown has not elapsed yet.
+        /// * `InsufficientApprovals`: If the payout has not been approved by enough approvers.
+        /// * `TokenCallFailed`: If the balance query to the token contract fails.
+        /// * `NoPayees` / `ZeroShare`: If the token balance cannot be split.
+        /// * `BelowThreshold`: If a payee's token share is below `min_payout_per_payee`, applied
+        ///   in the token's units.
+        /// * `TooManyTransfers`: If the token payout needs more transfers than `max_transfers_per_call`.
+        ///
+        #[ink(message)]
+        pub fn trigger_token_payout(&mut self, token: AccountId) -> Result<Balance, Error> {
+            self.ensure_caller_can_trigger_payout()?;
+            if !self.is_registered_token.contains(token) {
+                return Err(Error::TokenNotRegistered);
+            }
+            self.ensure_native_payout_allowed()?;
+            let total = self.pay_out_token(token)?;
+            self.advance_payout_nonce();
+            Ok(total)
+        }
+
+        /// Splits the contract's balance of `token` among the payees, once the checks of the
+        /// payout round passed.
+        fn pay_out_token(&mut self, token: AccountId) -> Result<Balance, Error> {
+            if let Some(block) = self.token_last_payout.get(token) {
+                if self.env().block_number() < block.saturating_add(self.payout_cooldown) {
+                    return Err(Error::CooldownActive);
+                }
+            }
+            let payout_info = self.token_payouts(token)?;
+            self.ensure_above_payout_threshold(&self.payees, &payout_info)?;
+            self.ensure_within_transfer_limit(payout_info.len())?;
+
+            let total = self.with_reentrancy_guard(|contract| {
+                let mut total: Balance = 0;
+                for info in payout_info.iter() {
+                    let delivered = contract
+                        .ensure_token_claimable(info.payee)
+                        .and_then(|()| contract.token_transfer(token, info.payee, info.amount));
+                    if delivered.is_err() {
+                        contract.park_token_payment(token, info.payee, info.amount);
+                        continue;
+                    }
+                    total = total.saturating_add(info.amount);
+                }
+                let distributed = contract.token_distributed.get(token).unwrap_or(0);
+                contract.token_distributed.insert(token, &distributed.saturating_add(total));
+                contract.token_last_payout.insert(token, &contract.env().block_number());
+
+                contract.env().emit_event(TokenPayout {
+                    token,
+                    total,
+                    num_payees: payout_info.len() as u32,
+                });
+                Ok(total)
+            })?;
+            self.reset_token_early_releases(token);
+            Ok(total)
+        }
+
+        /// Credits `amount` of `token` to the token claim of `payee`.
+        fn park_token_payment(&mut self, token: AccountId, payee: AccountId, amount: Balance) {
+            let pending = self.token_pending_claims.get((token, payee)).unwrap_or(0);
+            self.token_pending_claims.insert((token, payee), &pending.saturating_add(amount));
+            let total = self.token_total_pending.get(token).unwrap_or(0);
+            self.token_total_pending.insert(token, &total.saturating_add(amount));
+            self.env().emit_event(TokenPaymentParked { token, payee, amount });
+        }
+
+        /// Helper function to check that `payee` may receive tokens now: they are not frozen,
+        /// not claim-locked and their vesting schedule, if any, has ended.
+        fn ensure_token_claimable(&self, payee: AccountId) -> Result<(), Error> {
+            if self.frozen.contains(payee) {
+                return Err(Error::PayeeFrozen);
+            }
+            if self.is_claim_locked(payee) {
+                return Err(Error::ClaimLocked);
+            }
+            if let Some(schedule) = self.vesting_schedules.get(payee) {
+                if self.env().block_number() < schedule.start.saturating_add(schedule.duration) {
+                    return Err(Error::PayeeVesting);
+                }
+            }
+            Ok(())
+        }
+
+        /// Withdraws the caller's claim of a PSP22 `token`, parked by a token payout that could
+        /// not transfer their share.
+        ///
+        /// # Errors
+        ///
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `PayeeFrozen`: If the caller is frozen.
+        /// * `ClaimLocked`: If the caller's lock-up period has not ended yet.
+        /// * `PayeeVesting`: If the caller's vesting schedule has not ended yet.
+        /// * `ZeroShare`: If the caller has no claim of `token`.
+        /// * `TokenCallFailed` / `TransferFailed`: If the transfer fails; the claim is kept.
+        ///
+        #[ink(message)]
+        pub fn claim_token(&mut self, token: AccountId) -> Result<Balance, Error> {
+            let payee = self.env().caller();
+            self.ensure_reentrancy_guard_not_locked()?;
+            self.ensure_token_claimable(payee)?;
+            let amount = self.token_pending_claims.get((token, payee)).unwrap_or(0);
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+
+            self.with_reentrancy_guard(|contract| contract.token_transfer(token, payee, amount))?;
+
+            self.token_pending_claims.remove((token, payee));
+            let total = self.token_total_pending.get(token).unwrap_or(0);
+            self.token_total_pending.insert(token, &total.saturating_sub(amount));
+            let distributed = self.token_distributed.get(token).unwrap_or(0);
+            self.token_distributed.insert(token, &distributed.saturating_add(amount));
+            self.env().emit_event(TokenReleased { token, payee, amount });
+            Ok(amount)
+        }
+
+        /// Returns the claim of `token` parked for `payee`.
+        #[ink(message)]
+        pub fn token_pending_claim_of(&self, token: AccountId, payee: AccountId) -> Balance {
+            self.token_pending_claims.get((token, payee)).unwrap_or(0)
+        }
+
+        /// Splits the contract's balance of `token`, less the parked token claims, as if the
+        /// tokens withdrawn early through `release_all` were still there, then deducts those
+        /// withdrawals.
+        fn token_payouts(&self, token: AccountId) -> Result<Vec<PayoutInfo>, Error> {
+            let total_released_early = self.token_total_released_early.get(token).unwrap_or(0);
+            let balance = self
+                .token_balance(token)?
+                .saturating_sub(self.token_total_pending.get(token).unwrap_or(0))
+                .saturating_add(total_released_early);
+            // Owed amounts are denominated in the native currency, so tokens are always split
+            // proportionally.
+            let payouts = self.split_proportionally(balance)?;
+            if total_released_early == 0 {
+                return Ok(payouts);
+            }
+            Ok(Self::deduct_early_releases(payouts, |payee| {
+                self.token_released_early.get((token, payee)).unwrap_or(0)
+            }))
+        }
+
+        /// Transfers the current share of `token` of `payee` ahead of the token's next payout
+        /// and records it so the payout deducts it.
+        fn release_token_share(&mut self, token: AccountId, payee: AccountId) -> Result<Balance, Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            if self.frozen.contains(payee) {
+                return Err(Error::PayeeFrozen);
+            }
+            if self.is_claim_locked(payee) {
+                return Err(Error::ClaimLocked);
+            }
+            let amount = self.token_payouts(token)?
+                .iter()
+                .filter(|info| info.payee == payee)
+                .fold(0, |share: Balance, info| share.saturating_add(info.amount));
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+
+            self.with_reentrancy_guard(|contract| contract.token_transfer(token, payee, amount))?;
+
+            let released = self.token_released_early.get((token, payee)).unwrap_or(0);
+            self.token_released_early.insert((token, payee), &released.saturating_add(amount));
+            let total_released_early = self.token_total_released_early.get(token).unwrap_or(0);
+            self.token_total_released_early.insert(token, &total_released_early.saturating_add(amount));
+            let distributed = self.token_distributed.get(token).unwrap_or(0);
+            self.token_distributed.insert(token, &distributed.saturating_add(amount));
+            self.env().emit_event(TokenReleased { token, payee, amount });
+            Ok(amount)
+        }
+
+        /// Forgets the early withdrawals of `token` since its last payout.
+        fn reset_token_early_releases(&mut self, token: AccountId) {
+            if self.token_total_released_early.take(token).is_none() {
+                return;
+            }
+            for payee in self.payees.iter() {
+                self.token_released_early.remove((token, *payee));
+            }
+        }
+
+        /// Forgets the early token withdrawals of `payee`, who is leaving the payee list.
+        fn forget_token_releases(&mut self, payee: AccountId) {
+            for token in self.registered_tokens.iter() {
+                if let Some(released) = self.token_released_early.take((*token, payee)) {
+                    let total = self.token_total_released_early.get(token).unwrap_or(0);
+                    self.token_total_released_early.insert(token, &total.saturating_sub(released));
+                }
+            }
+        }
+
+        /// Queries the contract's own balance of a PSP22 `token`.
+        fn token_balance(&self, token: AccountId) -> Result<Balance, Error> {
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::balance_of")))
+                        .push_arg(self.env().account_id())
+                )
+                .returns::<Balance>()
+                .try_invoke()
+                .map_err(|_| Error::TokenCallFailed)?
+                .map_err(|_| Error::TokenCallFailed)
+        }
+
+        /// Transfers `amount` of a PSP22 `token` from the contract to `to`.
+        fn token_transfer(&self, token: AccountId, to: AccountId, amount: Balance) -> Result<(), Error> {
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                        .push_arg(to)
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new())
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+                .map_err(|_| Error::TokenCallFailed)?
+                .map_err(|_| Error::TokenCallFailed)?
+                .map_err(|_| Error::TransferFailed)
+        }
+
+        /// Transfers `amount` of a PSP22 `token` from `from` to the contract, using the allowance
+        /// `from` gave the contract.
+        fn token_transfer_from(&self, token: AccountId, from: AccountId, amount: Balance) -> Result<(), Error> {
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                        .push_arg(from)
+                        .push_arg(self.env().account_id())
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new())
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+                .map_err(|_| Error::TokenCallFailed)?
+                .map_err(|_| Error::TokenCallFailed)?
+                .map_err(|_| Error::TransferFailed)
+        }
+
+        /// Hands the admin role to another account.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the `designated_payee`.
+        ///
+        #[ink(message)]
+        pub fn set_admin(&mut self, new_admin: AccountId) -> Result<(), Error> {
+            self.ensure_caller_is_designated_payee()?;
+            self.replace_designated_payee(new_admin);
+            Ok(())
+        }
+
+        /// Proposes `new` as the designated payee; the role only moves once `new` calls
+        /// `accept_designation`.
+        ///
+        /// Unlike `set_admin`, a mistyped address cannot take over the contract, and the
+        /// proposal can be withdrawn with `cancel_proposal`. A new proposal replaces the previous
+        /// one.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the `designated_payee`.
+        ///
+        #[ink(message)]
+        pub fn propose_designated_payee(&mut self, new: AccountId) -> Result<(), Error> {
+            self.ensure_caller_is_designated_payee()?;
+            self.pending_designated_payee = Some(new);
+            self.env().emit_event(DesignationProposed { current: self.designated_payee, proposed: new });
+            Ok(())
+        }
+
+        /// Accepts the designated payee role proposed to the caller.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the proposed account.
+        ///
+        #[ink(message)]
+        pub fn accept_designation(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if self.pending_designated_payee != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+            self.replace_designated_payee(caller);
+            Ok(())
+        }
+
+        /// Withdraws the pending designated payee proposal.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the `designated_payee`.
+        /// * `ProposalNotFound`: If no proposal is pending.
+        ///
+        #[ink(message)]
+        pub fn cancel_proposal(&mut self) -> Result<(), Error> {
+            self.ensure_caller_is_designated_payee()?;
+            let proposed = self.pending_designated_payee.take().ok_or(Error::ProposalNotFound)?;
+            self.env().emit_event(DesignationProposalCancelled { proposed });
+            Ok(())
+        }
+
+        /// Returns the account proposed as designated payee, if any.
+        #[ink(message)]
+        pub fn pending_designated_payee(&self) -> Option<AccountId> {
+            self.pending_designated_payee
+        }
+
+        /// Makes `new` the designated payee and drops any pending proposal.
+        fn replace_designated_payee(&mut self, new: AccountId) {
+            let old = self.designated_payee;
+            self.designated_payee = new;
+            self.pending_designated_payee = None;
+            self.env().emit_event(DesignatedPayeeChanged { old, new });
+        }
+
+        /// Hands the designated payee role to `new`, e.g. when the operations wallet is rotated.
+        /// Same as `set_admin`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the `designated_payee`.
+        ///
+        #[ink(message)]
+        pub fn change_designated_payee(&mut self, new: AccountId) -> Result<(), Error> {
+            self.set_admin(new)
+        }
+
+        /// Sets the operator, an account that may trigger payouts but not change the configuration.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_operator(&mut self, new_operator: Option<AccountId>) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            let old = self.operator;
+            self.operator = new_operator;
+            self.env().emit_event(OperatorChanged { old, new: new_operator });
+            Ok(())
+        }
+
+        /// Returns the admin, i.e. the designated payee.
+        #[ink(message)]
+        pub fn admin(&self) -> AccountId {
+            self.designated_payee
+        }
+
+        /// Returns the designated payee, the account authorized to configure the contract and
+        /// trigger payouts. Same as `admin`.
+        #[ink(message)]
+        pub fn get_designated_payee(&self) -> AccountId {
+            self.designated_payee
+        }
+
+        /// Returns the number of registered payees.
+        #[ink(message)]
+        pub fn payee_count(&self) -> u32 {
+            self.payees.len() as u32
+        }
+
+        /// Grants `role` to `account`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if !self.roles.contains((role, account)) {
+                self.roles.insert((role, account), &());
+                self.env().emit_event(RoleGranted { role, account, sender: self.env().caller() });
+            }
+            Ok(())
+        }
+
+        /// Revokes `role` from `account`. The designated payee always keeps every role.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if self.roles.contains((role, account)) {
+                self.roles.remove((role, account));
+                self.env().emit_event(RoleRevoked { role, account, sender: self.env().caller() });
+            }
+            Ok(())
+        }
+
+        /// Returns whether `account` holds `role`.
+        ///
+        /// The designated payee holds every role; once the admin role was renounced, nobody
+        /// does.
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            if self.is_permissionless {
+                return false;
+            }
+            account == self.designated_payee || self.roles.contains((role, account))
+        }
+
+        /// Returns the operator, if any.
+        #[ink(message)]
+        pub fn operator(&self) -> Option<AccountId> {
+            self.operator
+        }
+
+        /// Suspends deposits and payouts, e.g. during incident response.
+        ///
+        /// While paused, every deposit and payout message returns `ContractPaused`. Pending
+        /// claims and pull-payment withdrawals stay available to the payees.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `ContractPaused`: If the contract is already paused.
+        ///
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.ensure_not_paused()?;
+            self.paused = true;
+            self.env().emit_event(Paused { account: self.env().caller() });
+            Ok(())
+        }
+
+        /// Resumes deposits and payouts suspended by `pause`. Does nothing if not paused.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if self.paused {
+                self.paused = false;
+                self.env().emit_event(Unpaused { account: self.env().caller() });
+            }
+            Ok(())
+        }
+
+        /// Returns whether deposits and payouts are suspended.
+        #[ink(message)]
+        pub fn paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Schedules the withdrawal of the distributable balance to `to`, e.g. when the payees'
+        /// keys are lost and the funds would otherwise be stuck.
+        ///
+        /// The withdrawal becomes executable `emergency_delay` blocks later. The
+        /// `EmergencyWithdrawalScheduled` event gives the payees that time to object. Scheduling
+        /// again replaces the previous withdrawal and restarts the delay.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `InvalidAccount`: If `to` is the all-zero account.
+        ///
+        #[ink(message)]
+        pub fn schedule_emergency_withdraw(&mut self, to: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if to == AccountId::from([0; 32]) {
+                return Err(Error::InvalidAccount);
+            }
+            let executable_at = self.env().block_number().saturating_add(self.emergency_delay());
+            self.emergency_withdrawal = Some(EmergencyWithdrawal { to, executable_at });
+            self.env().emit_event(EmergencyWithdrawalScheduled { to, executable_at });
+            Ok(())
+        }
+
+        /// Cancels the scheduled emergency withdrawal.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `NoEmergencyWithdrawal`: If no emergency withdrawal is scheduled.
+        ///
+        #[ink(message)]
+        pub fn cancel_emergency_withdraw(&mut self) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            let withdrawal = self.emergency_withdrawal.take().ok_or(Error::NoEmergencyWithdrawal)?;
+            self.env().emit_event(EmergencyWithdrawalCancelled { to: withdrawal.to });
+            Ok(())
+        }
+
+        /// Executes the scheduled emergency withdrawal, transferring `distributable()` to its
+        /// recipient. Returns the amount transferred.
+        ///
+        /// Pending claims and the other encumbered funds stay in the contract for their payees.
+        ///
+        /// Anyone may call this once the delay has elapsed; the funds always go to the scheduled
+        /// recipient.
+        ///
+        /// # Errors
+        ///
+        /// * `NoEmergencyWithdrawal`: If no emergency withdrawal is scheduled.
+        /// * `TimelockActive`: If the withdrawal's delay has not elapsed yet.
+        /// * `ReentrancyGuardLocked`: If called while a payout is in progress.
+        /// * `NothingDistributable`: If nothing is distributable.
+        /// * `TransferFailed`: If the transfer fails; the withdrawal stays scheduled.
+        ///
+        #[ink(message)]
+        pub fn emergency_withdraw(&mut self) -> Result<Balance, Error> {
+            let withdrawal = self.emergency_withdrawal.clone().ok_or(Error::NoEmergencyWithdrawal)?;
+            if self.env().block_number() < withdrawal.executable_at {
+                return Err(Error::TimelockActive);
+            }
+            self.ensure_reentrancy_guard_not_locked()?;
+            let amount = self.distributable();
+            if amount == 0 {
+                return Err(Error::NothingDistributable);
+            }
+
+            self.with_reentrancy_guard(|contract| contract.transfer_to(withdrawal.to, amount))?;
+            self.emergency_withdrawal = None;
+            self.env().emit_event(EmergencyWithdrawn { to: withdrawal.to, amount });
+            Ok(amount)
+        }
+
+        /// Returns the scheduled emergency withdrawal, if any.
+        #[ink(message)]
+        pub fn emergency_withdrawal(&self) -> Option<EmergencyWithdrawal> {
+            self.emergency_withdrawal.clone()
+        }
+
+        /// Sets the number of blocks between scheduling and executing an emergency withdrawal.
+        ///
+        /// A longer delay applies at once. A shorter one only takes effect once the current delay
+        /// has passed, so the admin cannot shorten the delay and sweep the balance right away.
+        /// An already scheduled withdrawal keeps the block it was scheduled for.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `DelayTooShort`: If `emergency_delay` is below `MIN_EMERGENCY_DELAY`.
+        ///
+        #[ink(message)]
+        pub fn set_emergency_delay(&mut self, emergency_delay: BlockNumber) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if emergency_delay < MIN_EMERGENCY_DELAY {
+                return Err(Error::DelayTooShort);
+            }
+            let current = self.emergency_delay();
+            self.emit_config_changed(ConfigField::EmergencyDelay, &current, &emergency_delay);
+            if emergency_delay >= current {
+                self.emergency_delay = emergency_delay;
+                self.pending_emergency_delay = None;
+            } else {
+                let effective_at = self.env().block_number().saturating_add(current);
+                self.emergency_delay = current;
+                self.pending_emergency_delay = Some((emergency_delay, effective_at));
+            }
+            Ok(())
+        }
+
+        /// Returns the number of blocks between scheduling and executing an emergency withdrawal.
+        #[ink(message)]
+        pub fn emergency_delay(&self) -> BlockNumber {
+            match self.pending_emergency_delay {
+                Some((delay, effective_at)) if self.env().block_number() >= effective_at => delay,
+                _ => self.emergency_delay,
+            }
+        }
+
+        /// Returns the shorter emergency delay waiting for the current one to pass and the block
+        /// it takes effect at, if any.
+        #[ink(message)]
+        pub fn pending_emergency_delay(&self) -> Option<(BlockNumber, BlockNumber)> {
+            self.pending_emergency_delay
+                .filter(|(_, effective_at)| self.env().block_number() < *effective_at)
+        }
+
+        /// Replaces the code of this contract with the code uploaded under `code_hash`.
+        ///
+        /// The storage is kept as it is, so the new code must be able to read the current
+        /// storage layout. The new code takes effect from the next call.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `UpgradeFailed`: If the chain rejects the code hash.
+        ///
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.env().set_code_hash(&code_hash).map_err(|_| Error::UpgradeFailed)?;
+            self.env().emit_event(CodeUpgraded { code_hash, sender: self.env().caller() });
+            Ok(())
+        }
+
+        /// Ends the life of the contract: runs a final payout to the payees, pushes every pending
+        /// claim to its payee, then removes the contract and sends the residual balance to
+        /// `beneficiary`.
+        ///
+        /// The residual is the reserve plus any rounding dust. An empty distributable balance
+        /// skips the final payout.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `InvalidAccount`: If `beneficiary` is the all-zero account.
+        /// * `TokenUndistributed`: If the contract still holds a registered PSP22 token or token
+        ///   claims are pending, which would be lost with the contract; the contract is kept.
+        /// * `TokenCallFailed`: If the balance query to a registered token contract fails.
+        /// * `FundsEncumbered`: If anything but the reserve is still set aside afterwards, e.g.
+        ///   a claim whose transfer failed, a frozen payee's accrued share, vesting credits, a
+        ///   running stream, a pool balance or a committed plan; the contract is kept.
+        /// * Any error of `trigger_payout` other than `NothingDistributable`; the contract is
+        ///   kept.
+        ///
+        #[ink(message)]
+        pub fn terminate(&mut self, beneficiary: AccountId) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if beneficiary == AccountId::from([0; 32]) {
+                return Err(Error::InvalidAccount);
+            }
+            self.ensure_tokens_paid_out()?;
+            let final_payout = match self.payout_native() {
+                Ok(total) => total,
+                Err(Error::NothingDistributable) => 0,
+                Err(error) => return Err(error),
+            };
+            for account in self.retry_queue.clone() {
+                // A claim that cannot be settled stays pending and blocks the termination below.
+                let _ = self.release_pending(account);
+            }
+            if self.encumbered() > self.reserve {
+                return Err(Error::FundsEncumbered);
+            }
+            self.env().emit_event(Terminated { beneficiary, final_payout });
+            self.env().terminate_contract(beneficiary)
+        }
+
+        /// Helper function to check that no registered token is left in the contract, neither as
+        /// a balance still to be split nor as a parked token claim.
+        fn ensure_tokens_paid_out(&self) -> Result<(), Error> {
+            for token in self.registered_tokens.iter() {
+                if self.token_total_pending.get(token).unwrap_or(0) > 0 || self.token_balance(*token)? > 0 {
+                    return Err(Error::TokenUndistributed);
+                }
+            }
+            Ok(())
+        }
+
+        /// Brings the storage written by an older version to the layout of the current code.
+        ///
+        /// Call this once right after an `upgrade`. Layout changes are applied one version at
+        /// a time, oldest first, and the storage version is then set to `VERSION`, so the
+        /// migration cannot run twice.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `AlreadyMigrated`: If the storage is already at the current `VERSION`.
+        ///
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            let from = self.storage_version();
+            if from >= VERSION {
+                return Err(Error::AlreadyMigrated);
+            }
+            // No layout change so far: later versions add their steps here.
+            self.version.set(&VERSION);
+            self.env().emit_event(Migrated { from, to: VERSION });
+            Ok(())
+        }
+
+        /// Returns the `VERSION` whose layout the storage was last written with.
+        #[ink(message)]
+        pub fn storage_version(&self) -> u32 {
+            self.version.get().unwrap_or(0)
+        }
+
+        /// Renounces the admin role for good, making payouts permissionless.
+        ///
+        /// Afterwards anyone may trigger the full split of the native balance (`trigger_payout`
+        /// and its lossy and batched variants), while every admin and configuration message
+        /// returns `Unauthorized`. The other payout messages stay with the operator. This cannot
+        /// be undone.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the `designated_payee`.
+        /// * `ContractPaused`: If the contract is paused, since nobody could unpause it afterwards.
+        ///
+        #[ink(message)]
+        pub fn renounce_designation(&mut self) -> Result<(), Error> {
+            self.ensure_caller_is_designated_payee()?;
+            self.ensure_not_paused()?;
+            self.is_permissionless = true;
+            self.pending_designated_payee = None;
+            self.env().emit_event(DesignationRenounced { designated_payee: self.designated_payee });
+            Ok(())
+        }
+
+        /// Returns whether the admin role was renounced and payouts are permissionless.
+        #[ink(message)]
+        pub fn is_permissionless(&self) -> bool {
+            self.is_permissionless
+        }
+
+        /// Opens a proposal to replace the designated payee with `candidate` and returns its id.
+        ///
+        /// This lets the payees recover the contract if the designated payee key is lost. The
+        /// proposer's vote is counted right away; see `vote_for_designated_payee`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not a payee, was only added by a deposit in
+        ///   contributions mode, or the admin role was renounced.
+        ///
+        #[ink(message)]
+        pub fn propose_new_designated_payee(&mut self, candidate: AccountId) -> Result<u64, Error> {
+            let proposer = self.env().caller();
+            if !self.is_voting_payee(proposer) || self.is_permissionless {
+                return Err(Error::Unauthorized);
+            }
+            let proposal_id = self.next_proposal_id;
+            self.next_proposal_id = proposal_id.saturating_add(1);
+            self.designated_payee_proposals.insert(proposal_id, &DesignatedPayeeProposal {
+                candidate,
+                proposed_at: self.env().block_number(),
+                votes: 0,
+            });
+            self.env().emit_event(DesignatedPayeeProposed { proposal_id, proposer, candidate });
+            self.vote_for_designated_payee(proposal_id)?;
+            Ok(proposal_id)
+        }
+
+        /// Votes for the designated payee proposal `proposal_id`.
+        ///
+        /// Each payee votes at most once per proposal; voting again has no further effect. Votes
+        /// are weighted by shares, and payees added by a deposit in contributions mode do not
+        /// vote. As soon as the voters hold strictly more than half of the voting shares and
+        /// there are at least `MIN_RECOVERY_VOTERS` of them, the candidate becomes the designated
+        /// payee, a `DesignatedPayeeChanged` event is emitted and the proposal is closed. The
+        /// votes of a removed payee are dropped.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not a voting payee, or the admin role was renounced.
+        /// * `ProposalNotFound`: If no proposal with this id is open.
+        /// * `ProposalExpired`: If the proposal was opened more than `recovery_period` blocks ago.
+        ///
+        #[ink(message)]
+        pub fn vote_for_designated_payee(&mut self, proposal_id: u64) -> Result<(), Error> {
+            let voter = self.env().caller();
+            if !self.is_voting_payee(voter) || self.is_permissionless {
+                return Err(Error::Unauthorized);
+            }
+            let mut proposal = self.designated_payee_proposals
+                .get(proposal_id)
+                .ok_or(Error::ProposalNotFound)?;
+            if self.recovery_period > 0
+                && self.env().block_number() > proposal.proposed_at.saturating_add(self.recovery_period)
+            {
+                return Err(Error::ProposalExpired);
+            }
+            if self.designated_payee_votes.contains((proposal_id, voter)) {
+                return Ok(());
+            }
+            self.designated_payee_votes.insert((proposal_id, voter), &());
+            let mut voted = self.voted_proposals.get(voter).unwrap_or_default();
+            voted.retain(|id| self.designated_payee_proposals.contains(id));
+            voted.push(proposal_id);
+            self.voted_proposals.insert(voter, &voted);
+
+            let (votes, voters) = self.tally_votes(proposal_id);
+            proposal.votes = votes;
+            self.env().emit_event(DesignatedPayeeVoted { proposal_id, voter, votes });
+
+            if votes.saturating_mul(2) > self.voting_shares() && voters >= MIN_RECOVERY_VOTERS {
+                self.designated_payee_proposals.remove(proposal_id);
+                self.replace_designated_payee(proposal.candidate);
+            } else {
+                self.designated_payee_proposals.insert(proposal_id, &proposal);
+            }
+            Ok(())
+        }
+
+        /// Returns the combined shares and the number of the voting payees that voted for
+        /// `proposal_id`.
+        fn tally_votes(&self, proposal_id: u64) -> (u128, u32) {
+            self.payees
+                .iter()
+                .filter(|payee| self.is_voting_payee(**payee))
+                .filter(|payee| self.designated_payee_votes.contains((proposal_id, **payee)))
+                .fold((0, 0), |(votes, voters), payee| {
+                    (votes.saturating_add(self.shares_of(*payee)), voters.saturating_add(1))
+                })
+        }
+
+        /// Returns the combined shares of the payees allowed to vote on designated payee proposals.
+        fn voting_shares(&self) -> u128 {
+            self.payees
+                .iter()
+                .filter(|payee| self.is_voting_payee(**payee))
+                .fold(0, |total: u128, payee| total.saturating_add(self.shares_of(*payee)))
+        }
+
+        /// Drops the votes `account` gave on designated payee proposals, e.g. when it stops being
+        /// a payee.
+        fn forget_votes(&mut self, account: AccountId) {
+            for proposal_id in self.voted_proposals.take(account).unwrap_or_default() {
+                self.designated_payee_votes.remove((proposal_id, account));
+            }
+        }
+
+        /// Helper function to check if `account` is a payee that may vote on designated payee
+        /// proposals, i.e. one not added by a deposit in contributions mode.
+        fn is_voting_payee(&self, account: AccountId) -> bool {
+            self.payees.contains(&account) && !self.contributors.contains(account)
+        }
+
+        /// Returns the open designated payee proposal `proposal_id`, if any.
+        #[ink(message)]
+        pub fn designated_payee_proposal(&self, proposal_id: u64) -> Option<DesignatedPayeeProposal> {
+            self.designated_payee_proposals.get(proposal_id)
+        }
+
+        /// Sets the number of blocks a designated payee proposal stays open (0 disables expiry).
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_recovery_period(&mut self, recovery_period: BlockNumber) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::RecoveryPeriod, &self.recovery_period, &recovery_period);
+            self.recovery_period = recovery_period;
+            Ok(())
+        }
+
+        /// Returns the number of blocks a designated payee proposal stays open.
+        #[ink(message)]
+        pub fn recovery_period(&self) -> BlockNumber {
+            self.recovery_period
+        }
+
+        /// Configures the approval committee required before each payout.
+        ///
+        /// Once configured, a payout only runs after at least `threshold` distinct approvers
+        /// have called `approve_payout` for the current payout nonce. A threshold of 0 disables
+        /// the requirement.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        /// * `InvalidThreshold`: If `threshold` exceeds the number of approvers.
+        ///
+        #[ink(message)]
+        pub fn set_approvers(&mut self, approvers: Vec<AccountId>, threshold: u32) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            if threshold as usize > approvers.len() {
+                return Err(Error::InvalidThreshold);
+            }
+            // Approvals given by the previous committee no longer count.
+            self.consume_approvals();
+            self.emit_config_changed(
+                ConfigField::Approvers,
+                &(&self.approvers, self.approval_threshold),
+                &(&approvers, threshold)
+            );
+            self.approvers = approvers;
+            self.approval_threshold = threshold;
+            Ok(())
+        }
+
+        /// Approves the payout round `payout_id`, which must be the current payout nonce.
+        ///
+        /// Naming the round keeps an approval from landing on a later round than the signer
+        /// reviewed. Approving twice for the same round has no further effect.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not an approver.
+        /// * `NonceMismatch`: If `payout_id` is not the current payout nonce.
+        ///
+        #[ink(message)]
+        pub fn approve_payout(&mut self, payout_id: u64) -> Result<(), Error> {
+            let approver = self.env().caller();
+            if !self.approvers.contains(&approver) {
+                return Err(Error::Unauthorized);
+            }
+            if payout_id != self.payout_nonce {
+                return Err(Error::NonceMismatch);
+            }
+            let nonce = self.payout_nonce;
+            if self.approvals.contains((nonce, approver)) {
+                return Ok(());
+            }
+            let approvals = self.approval_count.get(nonce).unwrap_or(0).saturating_add(1);
+            self.approvals.insert((nonce, approver), &());
+            self.approval_count.insert(nonce, &approvals);
+            self.env().emit_event(PayoutApproved { approver, nonce, approvals });
+            Ok(())
+        }
+
+        /// Returns the number of approvals given for the current payout round.
+        #[ink(message)]
+        pub fn payout_approvals(&self) -> u32 {
+            self.approval_count.get(self.payout_nonce).unwrap_or(0)
+        }
+
+        /// Returns the approval committee and the required number of approvals.
+        #[ink(message)]
+        pub fn approvers(&self) -> (Vec<AccountId>, u32) {
+            (self.approvers.clone(), self.approval_threshold)
+        }
+
+        /// Helper function to check that the current payout round has enough approvals.
+        fn ensure_payout_approved(&self) -> Result<(), Error> {
+            if self.payout_approvals() < self.approval_threshold {
+                return Err(Error::InsufficientApprovals);
+            }
+            Ok(())
+        }
+
+        /// Consumes the approvals of a payout outside the payout rounds, e.g. of a pool or a
+        /// token, and moves on to the next payout nonce.
+        fn advance_payout_nonce(&mut self) {
+            self.consume_approvals();
+            self.payout_nonce = self.payout_nonce.saturating_add(1);
+        }
+
+        /// Removes the approvals given for the current payout round.
+        fn consume_approvals(&mut self) {
+            let nonce = self.payout_nonce;
+            for approver in self.approvers.iter() {
+                self.approvals.remove((nonce, *approver));
+            }
+            self.approval_count.remove(nonce);
+        }
+
+        /// Emits a `ConfigChanged` event with the encoded `old` and `new` values of `field`.
+        fn emit_config_changed<T: scale::Encode>(&self, field: ConfigField, old: &T, new: &T) {
+            self.env().emit_event(ConfigChanged {
+                field,
+                old: old.encode(),
+                new: new.encode(),
+            });
+        }
+
+        /// Sets the account that receives rounding remainders; `None` adds them to the first
+        /// payee's share again.
+        ///
+        /// The recipient does not need to be a payee. Same as `set_remainder_policy` with
+        /// `Treasury(recipient)`, or `FirstPayee` for `None`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_remainder_recipient(&mut self, recipient: Option<AccountId>) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::RemainderRecipient, &self.remainder_recipient(), &recipient);
+            self.remainder_policy = recipient.map_or(RemainderPolicy::FirstPayee, RemainderPolicy::Treasury);
+            Ok(())
+        }
+
+        /// Returns the account that receives rounding remainders, if it is not the first payee
+        /// and remainders are not carried over.
+        #[ink(message)]
+        pub fn remainder_recipient(&self) -> Option<AccountId> {
+            match self.remainder_policy {
+                RemainderPolicy::DesignatedPayee => Some(self.designated_payee),
+                RemainderPolicy::Treasury(account) => Some(account),
+                RemainderPolicy::FirstPayee | RemainderPolicy::CarryOver | RemainderPolicy::RandomPayee => None,
+            }
+        }
+
+        /// Sets where rounding remainders go.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_remainder_policy(&mut self, policy: RemainderPolicy) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::RemainderPolicy, &self.remainder_policy, &policy);
+            self.remainder_policy = policy;
+            Ok(())
+        }
+
+        /// Returns where rounding remainders go.
+        #[ink(message)]
+        pub fn remainder_policy(&self) -> RemainderPolicy {
+            self.remainder_policy
+        }
+
+        /// Returns the remainder the last payout round carried over to the next one, which is
+        /// part of `distributable()`. Zero unless the remainder policy is `CarryOver`.
+        #[ink(message)]
+        pub fn carried_dust(&self) -> Balance {
+            self.carried_dust
+        }
+
+        /// Sets the largest value a single deposit may carry; `None` removes the limit.
+        ///
+        /// A rejected deposit returns `DepositTooLarge`, which reverts the call on-chain, so the
+        /// transferred value goes back to the sender.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_max_deposit(&mut self, max_deposit: Option<Balance>) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::MaxDeposit, &self.max_deposit, &max_deposit);
+            self.max_deposit = max_deposit;
+            Ok(())
+        }
+
+        /// Returns the largest value a single deposit may carry, if limited.
+        #[ink(message)]
+        pub fn max_deposit(&self) -> Option<Balance> {
+            self.max_deposit
+        }
+
+        /// Sets the smallest value a deposit must carry; values below 1 are raised to 1.
+        ///
+        /// Smaller non-zero deposits return `BelowMinimumDeposit`, zero deposits still return
+        /// `ZeroShare`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_min_deposit(&mut self, min_deposit: Balance) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            let min_deposit = min_deposit.max(1);
+            self.emit_config_changed(ConfigField::MinDeposit, &self.min_deposit, &min_deposit);
+            self.min_deposit = min_deposit;
+            Ok(())
+        }
+
+        /// Returns the smallest value a deposit must carry.
+        #[ink(message)]
+        pub fn min_deposit(&self) -> Balance {
+            self.min_deposit
+        }
+
+        /// Sets the balance kept in the contract and excluded from payouts.
+        ///
+        /// The reserve starts at the chain's minimum balance (existential deposit), so a payout
+        /// never drains the contract account below it and gets the account reaped. Setting it to
+        /// 0 lets payouts split the whole balance.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_reserve(&mut self, reserve: Balance) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::Reserve, &self.reserve, &reserve);
+            self.reserve = reserve;
+            Ok(())
+        }
+
+        /// Returns the balance kept in the contract and excluded from payouts.
+        #[ink(message)]
+        pub fn reserve(&self) -> Balance {
+            self.reserve
+        }
+
+        /// Sets the last block in which deposits are accepted; `None` removes the deadline.
+        ///
+        /// Payouts remain possible after the deadline.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller does not have the `Admin` role.
+        ///
+        #[ink(message)]
+        pub fn set_deposit_deadline(&mut self, deadline: Option<BlockNumber>) -> Result<(), Error> {
+            self.ensure_caller_has_role(Role::Admin)?;
+            self.emit_config_changed(ConfigField::DepositDeadline, &self.deposit_deadline, &deadline);
+            self.deposit_deadline = deadline;
+            Ok(())
+        }
+
+        /// Returns whether deposits are currently accepted, together with the deposit deadline.
+        #[ink(message)]
+        pub fn deposits_open(&self) -> (bool, Option<BlockNumber>) {
+            (self.ensure_deposits_open().is_ok(), self.deposit_deadline)
+        }
+
+        /// Helper function to check that the deposit deadline has not passed.
+        fn ensure_deposits_open(&self) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            if let Some(deadline) = self.deposit_deadline {
+                if self.env().block_number() > deadline {
+                    return Err(Error::DepositsClosed);
+                }
+            }
+            Ok(())
+        }
+
+        /// Helper function to check that a deposit of `value` is within the minimum and maximum
+        /// deposit.
+        fn ensure_deposit_within_limits(&self, value: Balance) -> Result<(), Error> {
+            if value < self.min_deposit {
+                return Err(Error::BelowMinimumDeposit);
+            }
+            match self.max_deposit {
+                Some(max_deposit) if value > max_deposit => Err(Error::DepositTooLarge),
+                _ => Ok(()),
+            }
+        }
+
+        /// Helper function to check if the caller may trigger a full split of the native balance:
+        /// anyone once the admin role was renounced or the trigger deadline has passed, the
+        /// admin or operator otherwise.
+        fn ensure_caller_can_trigger_full_payout(&self) -> Result<(), Error> {
+            if self.is_permissionless || self.trigger_deadline_passed() {
+                return Ok(());
+            }
+            self.ensure_caller_can_trigger_payout()
+        }
+
+        /// Helper function to check if the caller may trigger payouts (admin or operator).
+        fn ensure_caller_can_trigger_payout(&self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if Some(caller) != self.operator && !self.has_role(Role::PayoutTrigger, caller) {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check if the trigger deadline has passed, letting anyone trigger
+        /// payouts.
+        fn trigger_deadline_passed(&self) -> bool {
+            self.permissionless_trigger_at()
+                .is_some_and(|block| self.env().block_number() >= block)
+        }
+
+        /// Helper function to check that the caller holds `role`.
+        fn ensure_caller_has_role(&self, role: Role) -> Result<(), Error> {
+            if !self.has_role(role, self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check if the caller is the designated payee and the role was not
+        /// renounced.
+        fn ensure_caller_is_designated_payee(&self) -> Result<(), Error> {
+            if self.is_permissionless || self.env().caller() != self.designated_payee {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check that every one of `payees` with a share in `payouts` receives
+        /// at least `min_payout_per_payee`, counting separate remainder entries towards the share.
+        fn ensure_above_payout_threshold(&self, payees: &[AccountId], payouts: &[PayoutInfo]) -> Result<(), Error> {
+            if self.min_payout_per_payee == 0 {
+                return Ok(());
+            }
+            for payee in payees.iter() {
+                let share = payouts
+                    .iter()
+                    .filter(|info| info.payee == *payee)
+                    .fold(0, |total: Balance, info| total.saturating_add(info.amount));
+                if share > 0 && share < self.min_payout_per_payee {
+                    return Err(Error::BelowThreshold);
+                }
+            }
+            Ok(())
+        }
+
+        /// Helper function to check that a payout of `transfers` transfers fits in a single call.
+        fn ensure_within_transfer_limit(&self, transfers: usize) -> Result<(), Error> {
+            if self.max_transfers_per_call > 0 && transfers > self.max_transfers_per_call as usize {
+                return Err(Error::TooManyTransfers);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check that no batched payout round is in progress.
+        fn ensure_no_payout_batch(&self) -> Result<(), Error> {
+            if self.payout_batch.is_some() {
+                return Err(Error::PayoutInProgress);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check that the contract is not paused.
+        fn ensure_not_paused(&self) -> Result<(), Error> {
+            if self.paused {
+                return Err(Error::ContractPaused);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check that the payout cooldown has elapsed and that no payout ran
+        /// in the current payout period.
+        fn ensure_cooldown_elapsed(&self) -> Result<(), Error> {
+            if self.env().block_number() < self.next_payout_at() {
+                return Err(Error::CooldownActive);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check the reentrancy guard.
+        fn ensure_reentrancy_guard_not_locked(&self) -> Result<(), Error> {
+            if self.locked {
+                return Err(Error::ReentrancyGuardLocked);
+            }
+            Ok(())
+        }
+
+        /// Helper function to run `f` with the reentrancy guard held.
+        ///
+        /// The guard is released again whether `f` succeeds or fails, and nested use fails with
+        /// `ReentrancyGuardLocked`.
+        fn with_reentrancy_guard<T>(
+            &mut self,
+            f: impl FnOnce(&mut Self) -> Result<T, Error>
+        ) -> Result<T, Error> {
+            self.ensure_reentrancy_guard_not_locked()?;
+            self.locked = true;
+            let result = f(self);
+            self.locked = false;
+            result
+        }
+    }
+
+    impl PaymentSplit for PaymentSplitter {
+        /// Allows anyone to deposit funds into the contract.
+        ///
+        /// The deposited amount is added to the contract's balance.
+        /// Emits a `Deposit` event when funds are received, recording the depositor and the amount.
+        /// In `SplitMode::Contributions` the deposit is recorded as the caller's contribution and
+        /// the caller becomes a payee. With `auto_split` the deposited value is split and
+        /// transferred to the payees right away; shares that cannot be transferred are parked
+        /// as pending claims.
+        ///
+        /// # Errors
+        ///
+        /// * `ContractPaused`: If the contract is paused.
+        /// * `DepositsClosed`: If the deposit deadline has passed.
+        /// * `ZeroShare`: If the transferred value is zero.
+        /// * `BelowMinimumDeposit`: If the transferred value is below the minimum deposit.
+        /// * `DepositTooLarge`: If the transferred value exceeds the maximum deposit.
+        /// * `TooManyPayees`: If a new depositor would exceed `MAX_PAYEES`.
+        /// * `ReentrancyGuardLocked`: With `auto_split`, if called while a payout is in progress.
+        ///
+        #[ink(message)]
+        fn deposit(&mut self) -> Result<(), Error> {
+            self.receive_deposit(self.env().caller(), self.env().transferred_value())
+        }
+
+        /// Triggers the actual payout process based on the payout distribution calculated by `calculate_payout`.
+        ///
+        /// Only the `designated_payee` or the operator is authorized to call this function, unless
+        /// the trigger deadline has passed (see `set_trigger_deadline`); the caller of such a
+        /// permissionless payout receives the trigger bounty, if any. Transfers the funds to each payee based on the `PayoutInfo` provided. If the transfer
+        /// to a payee fails, the amount is parked as a pending claim the payee can `claim` later.
+        ///
+        /// Returns the transfers that went through, in payee order; parked shares and the shares
+        /// of frozen payees are not included.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized`: If the caller is not the operator and does not have the `PayoutTrigger`
+        ///   role, and the trigger deadline has not passed.
+        /// * `ContractPaused`: If the contract is paused.
+        /// * `CooldownActive`: If the payout cooldown since the last payout has not elapsed, or a
+        ///   payout already ran in the current payout period.
+        /// * `BelowThreshold`: If a payee's share would be below `min_payout_per_payee`.
+        /// * `BalanceChanged`: If the balance dropped after the payout was calculated; nothing
+        ///   is transferred.
+        /// * `TransferFailed`: If the trigger bounty cannot be transferred.
+        ///
+        #[ink(message)]
+        fn trigger_payout(&mut self) -> Result<Vec<PayoutInfo>, Error> {
+            self.ensure_caller_can_trigger_full_payout()?;
+            let report = self.execute_native_payout_with_bounty()?;
+            Ok(report
+                .into_iter()
+                .filter_map(|(payee, result)| result.ok().map(|amount| PayoutInfo { payee, amount }))
+                .collect())
+        }
+
+        /// Returns the registered payees.
+        #[ink(message)]
+        fn get_payees(&self) -> Vec<AccountId> {
+            self.payees.clone()
+        }
+
+        /// Returns what `calculate_payout` would assign to `payee` if the payout ran now,
+        /// including the remainder handling.
+        ///
+        /// # Errors
+        ///
+        /// * `PayeeNotFound`: If the account is not a registered payee.
+        /// * Any error `calculate_payout` would return for the current balance.
+        ///
+        #[ink(message)]
+        fn payee_share(&self, payee: AccountId) -> Result<Balance, Error> {
+            if !self.payees.contains(&payee) {
+                return Err(Error::PayeeNotFound);
+            }
+            let payouts = self.next_payouts()?;
+            Ok(payouts
+                .iter()
+                .filter(|payout| payout.payee == payee)
+                .fold(0, |share: Balance, payout| share.saturating_add(payout.amount)))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::distribution::compute_weighted_distribution;
+        use ink::env::{
+            test::{
+                default_accounts,
+                set_caller,
+                set_value_transferred,
+                get_account_balance,
+                set_account_balance,
+                advance_block,
+            },
+            DefaultEnvironment,
+        };
+        use ink::codegen::Env;
+        use secp256k1::{ Message, PublicKey, SecretKey, SECP256K1 };
+        use std::cell::RefCell;
+
+        thread_local! {
+            // Accounts whose incoming transfers fail, see `reject_transfers_to`.
+            static REJECTED_ACCOUNTS: RefCell<Vec<AccountId>> = const { RefCell::new(Vec::new()) };
+        }
+
+        // Makes every subsequent native transfer to `account` fail, simulating a payee account
+        // that cannot receive funds (the off-chain environment never rejects transfers).
+        fn reject_transfers_to(account: AccountId) {
+            REJECTED_ACCOUNTS.with(|rejected| rejected.borrow_mut().push(account));
+        }
+
+        // Lets transfers to `account` succeed again after `reject_transfers_to`.
+        fn accept_transfers_to(account: AccountId) {
+            REJECTED_ACCOUNTS.with(|rejected| rejected.borrow_mut().retain(|a| *a != account));
+        }
+
+        impl PaymentSplitter {
+            // Stands in for the contract's transfer primitive, failing for the accounts passed to
+            // `reject_transfers_to` as the off-chain environment never rejects a transfer.
+            pub(super) fn transfer_to(&self, to: AccountId, amount: Balance) -> Result<(), Error> {
+                if REJECTED_ACCOUNTS.with(|rejected| rejected.borrow().contains(&to)) {
+                    return Err(Error::TransferFailed);
+                }
+                self.env().transfer(to, amount).map_err(|_| Error::TransferFailed)
+            }
+        }
+
+        // Helper function to get the current balance of an account.
         fn get_balance(account: AccountId) -> u128 {
             get_account_balance::<ink::env::DefaultEnvironment>(account).expect(
                 "Cannot get account balance"
@@ -214,100 +6023,4803 @@ mod payment_splitter {
         }
 
         #[ink::test]
-        fn trigger_payout_unauthorized() {
+        fn trigger_payout_unauthorized() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees.clone(), accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            contract.deposit().unwrap();
+
+            // Act - Payout
+            set_caller::<DefaultEnvironment>(accounts.bob); // Bob is not the designated_payee
+            let result = contract.trigger_payout();
+
+            // Assert
+            assert_eq!(result, Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn basic_workflow() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees.clone(), accounts.alice);
+            contract.set_reserve(0).unwrap();
+
+            // Set initial values
+            let initial_contract_balance = 1000000;
+            let bob_balance = 2000010;
+            let charlie_balance = 3000010;
+            let alice_deposit = 121;
+            let balance_plus_deposit = initial_contract_balance + alice_deposit;
+            let expected_bob_received = 500061;
+            let expected_charlie_received = 500060;
+
+            // Set initial balances
+            set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                initial_contract_balance
+            );
+            set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, bob_balance);
+            set_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie, charlie_balance);
+
+            // Act - Deposit
+            set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            set_value_transferred::<ink::env::DefaultEnvironment>(alice_deposit);
+            contract.deposit().unwrap();
+
+            // Update contract balance after Alice deposit
+            set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                balance_plus_deposit
+            );
+
+            //Get balances before payout
+            let contract_balance_before_split = get_balance(contract.env().account_id());
+            ink::env::debug_println!(
+                "---- Contract balance before split: {}",
+                contract_balance_before_split
+            );
+
+            // Assert - Deposit (still Alice as caller)
+            assert_eq!(get_balance(contract.env().account_id()), balance_plus_deposit);
+
+            // Calculate Payout
+            let payout_info = contract.calculate_payout().unwrap();
+            ink::env::debug_println!("---- payout info 0: {:?}", payout_info[0].amount);
+            ink::env::debug_println!("---- payout info 1: {:?}", payout_info[1].amount);
+
+            // Assert Payout Calculations
+            assert_eq!(payout_info.len(), 2);
+            assert_eq!(payout_info[0].payee, accounts.bob);
+            assert_eq!(payout_info[0].amount, expected_bob_received);
+            assert_eq!(payout_info[1].payee, accounts.charlie);
+            assert_eq!(payout_info[1].amount, expected_charlie_received);
+
+            // Trigger Payout
+            let executed = contract.trigger_payout().unwrap();
+
+            // Assert - the executed transfers match the calculation
+            assert_eq!(executed, payout_info);
+
+            //Get balances after payout
+            let contract_balance_after = get_balance(contract.env().account_id());
+            ink::env::debug_println!(
+                "---- Contract balance after split: {}",
+                contract_balance_after
+            );
+
+            //Update balances after payout (should be 0 after payout)
+            set_account_balance::<ink::env::DefaultEnvironment>(contract.env().account_id(), 0);
+
+            // Assert - Payout
+            assert_eq!(get_balance(contract.env().account_id()), 0);
+            assert_eq!(get_balance(accounts.bob), bob_balance + expected_bob_received);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + expected_charlie_received);
+            ink::env::debug_println!("---- Bob balance: {}", get_balance(accounts.bob));
+            ink::env::debug_println!("---- Charlie balance: {}", get_balance(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn trigger_payout_with_nonce_rejects_replay() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            assert_eq!(contract.payout_nonce(), 0);
+
+            // Act - First submission
+            let first = contract.trigger_payout_with_nonce(0);
+
+            // Refill the contract so only the nonce can stop the replay
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let replay = contract.trigger_payout_with_nonce(0);
+
+            // Assert
+            assert_eq!(first, Ok(()));
+            assert_eq!(replay, Err(Error::NonceMismatch));
+            assert_eq!(contract.payout_nonce(), 1);
+            assert_eq!(get_balance(contract.env().account_id()), 2000000);
+            assert_eq!(contract.trigger_payout_with_nonce(1), Ok(()));
+            assert_eq!(contract.payout_nonce(), 2);
+        }
+
+        #[ink::test]
+        fn trigger_payout_respects_cooldown() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let config = SplitterConfig { payout_cooldown: 3, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Act - First payout, then an immediate retry
+            assert!(contract.trigger_payout().is_ok());
+            let start = contract.env().block_number();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Assert
+            assert_eq!(contract.next_payout_block(), start + 3);
+            assert_eq!(contract.trigger_payout(), Err(Error::CooldownActive));
+
+            // Still locked one block before the cooldown ends
+            advance_block::<DefaultEnvironment>();
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.trigger_payout(), Err(Error::CooldownActive));
+
+            advance_block::<DefaultEnvironment>();
+            assert!(contract.trigger_payout().is_ok());
+            assert_eq!(contract.next_payout_block(), start + 6);
+        }
+
+        #[ink::test]
+        fn payout_cooldown_can_be_changed() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            let contract_id = contract.env().account_id();
+            assert_eq!(contract.payout_cooldown(), 0);
+
+            // Act
+            assert_eq!(contract.set_payout_cooldown(2), Ok(()));
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            assert!(contract.trigger_payout().is_ok());
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+
+            // Assert
+            assert_eq!(contract.payout_cooldown(), 2);
+            assert_eq!(contract.trigger_payout(), Err(Error::CooldownActive));
+
+            // Lifting the cooldown applies right away
+            assert_eq!(contract.set_payout_cooldown(0), Ok(()));
+            assert!(contract.trigger_payout().is_ok());
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_payout_cooldown(100), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn trigger_payout_runs_once_per_period() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let config = SplitterConfig { payout_period: 4, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            assert_eq!(contract.next_payout_at(), 0);
+
+            // Act - first payout, then a retry in the same period
+            assert!(contract.trigger_payout().is_ok());
+            let period_start = contract.env().block_number() / 4 * 4;
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+
+            // Assert
+            assert_eq!(contract.next_payout_at(), period_start + 4);
+            assert_eq!(contract.trigger_payout(), Err(Error::CooldownActive));
+
+            // Act - move to the first block of the next period
+            while contract.env().block_number() < period_start + 4 {
+                advance_block::<DefaultEnvironment>();
+            }
+
+            // Assert
+            assert!(contract.trigger_payout().is_ok());
+            assert_eq!(contract.next_payout_at(), period_start + 8);
+
+            // Disabling the period lifts the restriction
+            assert_eq!(contract.set_payout_period(0), Ok(()));
+            assert_eq!(contract.payout_period(), 0);
+            assert_eq!(contract.next_payout_at(), contract.env().block_number());
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_payout_period(10), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn anyone_can_trigger_payout_after_deadline() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            assert_eq!(contract.permissionless_trigger_at(), None);
+            assert_eq!(contract.set_trigger_deadline(3), Ok(()));
+            assert_eq!(contract.trigger_deadline(), 3);
+            let deadline = contract.env().block_number() + 3;
+            assert_eq!(contract.permissionless_trigger_at(), Some(deadline));
+
+            // Act & Assert - before the deadline only the authorized accounts may trigger
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.trigger_payout(), Err(Error::Unauthorized));
+            assert_eq!(contract.set_trigger_deadline(0), Err(Error::Unauthorized));
+
+            // Act - the balance sits undistributed past the deadline
+            while contract.env().block_number() < deadline {
+                advance_block::<DefaultEnvironment>();
+            }
+
+            // Assert - only the full split is opened up, not the other payout messages
+            assert_eq!(contract.commit_payout(), Err(Error::Unauthorized));
+            assert_eq!(contract.trigger_payout_amount(1000000), Err(Error::Unauthorized));
+            assert_eq!(contract.trigger_token_payout(accounts.frank), Err(Error::Unauthorized));
+
+            // Assert - anyone may trigger, and the payout restarts the deadline
+            assert!(contract.trigger_payout().is_ok());
+            let next_deadline = contract.env().block_number() + 3;
+            assert_eq!(contract.permissionless_trigger_at(), Some(next_deadline));
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            assert_eq!(contract.trigger_payout(), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn permissionless_payout_pays_trigger_bounty() {
+            // Arrange - a 1% bounty, and a deadline that has already passed
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 10000000);
+            assert_eq!(contract.set_trigger_bounty_bps(TOTAL_BASIS_POINTS + 1), Err(Error::InvalidBasisPoints));
+            assert_eq!(contract.set_trigger_bounty_bps(100), Ok(()));
+            assert_eq!(contract.trigger_bounty_bps(), 100);
+            contract.set_trigger_deadline(1).unwrap();
+            advance_block::<DefaultEnvironment>();
+            let eve_balance = get_balance(accounts.eve);
+
+            // Assert - a payout that fails validation pays no bounty
+            contract.set_min_payout_per_payee(10000000).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.trigger_payout(), Err(Error::BelowThreshold));
+            assert_eq!(get_balance(accounts.eve), eve_balance);
+            assert_eq!(get_balance(contract_id), 10000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_min_payout_per_payee(0).unwrap();
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            let payouts = contract.trigger_payout().unwrap();
+
+            // Assert - the caller got 1% and the payees split the rest
+            assert_eq!(get_balance(accounts.eve), eve_balance + 100000);
+            assert_eq!(payouts, vec![
+                PayoutInfo { payee: accounts.bob, amount: 4950000 },
+                PayoutInfo { payee: accounts.charlie, amount: 4950000 },
+            ]);
+            assert_eq!(get_balance(contract_id), 0);
+            use ink::env::Event;
+            let bounties: Vec<TriggerBountyPaid> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == TriggerBountyPaid::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <TriggerBountyPaid as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(
+                bounties.iter().map(|event| (event.caller, event.amount)).collect::<Vec<_>>(),
+                vec![(accounts.eve, 100000)]
+            );
+
+            // Act - an authorized payout before the deadline pays no bounty
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract_id, 10000000);
+            let payouts = contract.trigger_payout().unwrap();
+
+            // Assert
+            assert_eq!(payouts, vec![
+                PayoutInfo { payee: accounts.bob, amount: 5000000 },
+                PayoutInfo { payee: accounts.charlie, amount: 5000000 },
+            ]);
+        }
+
+        #[ink::test]
+        fn token_registry_management() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            let token = accounts.django;
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act - Register
+            assert_eq!(contract.register_token(token), Ok(()));
+
+            // Assert
+            assert_eq!(contract.registered_tokens(), vec![token]);
+            assert_eq!(contract.register_token(token), Err(Error::TokenAlreadyRegistered));
+
+            // Only the designated payee manages the registry
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.register_token(accounts.eve), Err(Error::Unauthorized));
+            assert_eq!(contract.unregister_token(token), Err(Error::Unauthorized));
+
+            // Act - Unregister
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unregister_token(token), Ok(()));
+
+            // Assert
+            assert!(contract.registered_tokens().is_empty());
+            assert_eq!(contract.unregister_token(token), Err(Error::TokenNotRegistered));
+            assert_eq!(contract.trigger_token_payout(token), Err(Error::TokenNotRegistered));
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.trigger_token_payout(token), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn list_assets_reports_native_and_tokens() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            assert!(contract.trigger_payout().is_ok());
+
+            // Act
+            assert_eq!(contract.add_asset(accounts.django), Ok(()));
+            assert_eq!(contract.add_asset(accounts.eve), Ok(()));
+
+            // Assert
+            assert_eq!(contract.list_assets(), vec![
+                AssetInfo { token: None, total_received: 0, total_distributed: 2000000 },
+                AssetInfo { token: Some(accounts.django), total_received: 0, total_distributed: 0 },
+                AssetInfo { token: Some(accounts.eve), total_received: 0, total_distributed: 0 },
+            ]);
+            assert_eq!(contract.add_asset(accounts.django), Err(Error::TokenAlreadyRegistered));
+
+            // Act - Remove
+            assert_eq!(contract.remove_asset(accounts.django), Ok(()));
+
+            // Assert
+            assert_eq!(contract.registered_tokens(), vec![accounts.eve]);
+            assert_eq!(contract.remove_asset(accounts.django), Err(Error::TokenNotRegistered));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.add_asset(accounts.django), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn deposit_token_rejects_invalid_deposits() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            let token = accounts.django;
+
+            // Assert - only registered tokens are accepted
+            assert_eq!(contract.deposit_token(token, 1000), Err(Error::TokenNotRegistered));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.add_asset(token).unwrap();
+            assert_eq!(contract.deposit_token(token, 0), Err(Error::ZeroShare));
+            assert_eq!(contract.token_deposited_by(token, accounts.alice), 0);
+
+            // Assert - the deposit deadline applies to tokens too
+            let deadline = contract.env().block_number();
+            contract.set_deposit_deadline(Some(deadline)).unwrap();
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.deposit_token(token, 1000), Err(Error::DepositsClosed));
+        }
+
+        #[ink::test]
+        fn rescue_token_guards_managed_assets() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            let token = accounts.django;
+
+            // Assert - admin only
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.rescue_token(token, accounts.bob, 1000), Err(Error::Unauthorized));
+
+            // Assert - registered tokens belong to the payees
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.add_asset(token).unwrap();
+            assert_eq!(contract.rescue_token(token, accounts.eve, 1000), Err(Error::AssetManaged));
+
+            // Assert - undistributed deposits and token claims keep the token registered
+            contract.token_received.insert(token, &1000);
+            assert_eq!(contract.remove_asset(token), Err(Error::TokenUndistributed));
+            contract.token_distributed.insert(token, &1000);
+            contract.park_token_payment(token, accounts.bob, 10);
+            assert_eq!(contract.remove_asset(token), Err(Error::TokenUndistributed));
+            contract.token_total_pending.remove(token);
+
+            contract.remove_asset(token).unwrap();
+            assert_eq!(contract.rescue_token(token, AccountId::from([0; 32]), 1000), Err(Error::InvalidAccount));
+            assert_eq!(contract.rescue_token(token, accounts.eve, 0), Err(Error::ZeroShare));
+        }
+
+        #[ink::test]
+        fn token_payouts_pass_the_payout_gates() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            let token = accounts.django;
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.add_asset(token).unwrap();
+            contract.set_approvers(vec![accounts.bob], 1).unwrap();
+            contract.set_payout_cooldown(5).unwrap();
+
+            // Assert - approvals are required
+            assert_eq!(contract.trigger_token_payout(token), Err(Error::InsufficientApprovals));
+            assert_eq!(contract.trigger_payout_all_assets(), Err(Error::InsufficientApprovals));
+
+            // Assert - the cooldown applies between two payouts of the token
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            contract.approve_payout(contract.payout_nonce()).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.token_last_payout.insert(token, &contract.env().block_number());
+            assert_eq!(contract.trigger_token_payout(token), Err(Error::CooldownActive));
+
+            // Assert - and so does the pause
+            contract.pause().unwrap();
+            assert_eq!(contract.trigger_token_payout(token), Err(Error::ContractPaused));
+        }
+
+        #[ink::test]
+        fn withheld_token_shares_wait_as_token_claims() {
+            // Arrange - shares of Bob and Charlie parked by a token payout
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            let token = accounts.django;
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.park_token_payment(token, accounts.bob, 1000);
+            contract.park_token_payment(token, accounts.charlie, 1000);
+            let now = contract.env().block_number();
+
+            // Assert
+            assert_eq!(contract.token_pending_claim_of(token, accounts.bob), 1000);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.claim_token(token), Err(Error::ZeroShare));
+
+            // Assert - frozen, claim-locked and vesting payees cannot withdraw them yet
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.freeze_payee(accounts.bob).unwrap();
+            contract.set_claim_lock(accounts.charlie, now + 2).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_token(token), Err(Error::PayeeFrozen));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.claim_token(token), Err(Error::ClaimLocked));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.unfreeze_payee(accounts.bob).unwrap();
+            contract.set_vesting_schedule(accounts.bob, VestingSchedule { start: now, cliff: 0, duration: 10 }).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_token(token), Err(Error::PayeeVesting));
+            assert_eq!(contract.token_pending_claim_of(token, accounts.bob), 1000);
+        }
+
+        #[ink::test]
+        fn trigger_payout_all_assets_reports_native_result() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000001);
+
+            // Act
+            let results = contract.trigger_payout_all_assets().unwrap();
+
+            // Assert
+            assert_eq!(results, vec![AssetPayoutResult { token: None, result: Ok(2000001) }]);
+
+            // An empty native balance is reported rather than aborting the call
+            let results = contract.trigger_payout_all_assets().unwrap();
+            assert_eq!(results, vec![AssetPayoutResult { token: None, result: Err(Error::NothingDistributable) }]);
+        }
+
+        #[ink::test]
+        fn child_splitter_configuration() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            assert_eq!(contract.set_child_splitter(accounts.bob, true), Ok(()));
+            assert_eq!(contract.set_parent_splitter(Some(accounts.django)), Ok(()));
+
+            // Assert
+            assert!(contract.is_child_splitter(accounts.bob));
+            assert!(!contract.is_child_splitter(accounts.charlie));
+            assert_eq!(contract.set_child_splitter(accounts.bob, false), Ok(()));
+            assert!(!contract.is_child_splitter(accounts.bob));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_child_splitter(accounts.bob, true), Err(Error::Unauthorized));
+            assert_eq!(contract.set_parent_splitter(None), Err(Error::Unauthorized));
+            assert_eq!(contract.notify_and_distribute(), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn notify_and_distribute_from_parent() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_parent_splitter(Some(accounts.django)).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let bob_balance = get_balance(accounts.bob);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.django);
+            let result = contract.notify_and_distribute();
+
+            // Assert
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+        }
+
+        // Helper function to deploy a splitter with two tier-1 payees (Bob, Charlie) and two
+        // tier-2 payees (Django, Eve), with Alice as designated payee and caller.
+        fn tiered_contract(bob_fixed: Balance, charlie_fixed: Balance) -> PaymentSplitter {
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.django, accounts.charlie, accounts.eve];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_payee_tier(accounts.bob, PayeeTier::Fixed(bob_fixed)).unwrap();
+            contract.set_payee_tier(accounts.charlie, PayeeTier::Fixed(charlie_fixed)).unwrap();
+            contract
+        }
+
+        #[ink::test]
+        fn tiered_payout_underfunded() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = tiered_contract(1400000, 600000);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 1000003);
+
+            // Act
+            let payout_info = contract.calculate_payout().unwrap();
+
+            // Assert - fixed amounts pro-rated, rounding remainder to the first tier-1 payee
+            assert_eq!(payout_info, vec![
+                PayoutInfo { payee: accounts.bob, amount: 700003 },
+                PayoutInfo { payee: accounts.charlie, amount: 300000 },
+            ]);
+        }
+
+        #[ink::test]
+        fn tiered_payout_exactly_funded() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = tiered_contract(1400000, 600000);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Act
+            let payout_info = contract.calculate_payout().unwrap();
+
+            // Assert
+            assert_eq!(payout_info, vec![
+                PayoutInfo { payee: accounts.bob, amount: 1400000 },
+                PayoutInfo { payee: accounts.charlie, amount: 600000 },
+            ]);
+        }
+
+        #[ink::test]
+        fn tiered_payout_surplus() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = tiered_contract(1400000, 600000);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000 + 1001);
+            let eve_balance = get_balance(accounts.eve);
+
+            // Act
+            let payout_info = contract.calculate_payout().unwrap();
+            contract.trigger_payout().unwrap();
+
+            // Assert - the surplus is split among tier-2 payees, remainder to the first of them
+            assert_eq!(payout_info, vec![
+                PayoutInfo { payee: accounts.bob, amount: 1400000 },
+                PayoutInfo { payee: accounts.django, amount: 501 },
+                PayoutInfo { payee: accounts.charlie, amount: 600000 },
+                PayoutInfo { payee: accounts.eve, amount: 500 },
+            ]);
+            assert_eq!(get_balance(accounts.eve), eve_balance + 500);
+            assert_eq!(get_balance(contract.env().account_id()), 0);
+        }
+
+        #[ink::test]
+        fn set_payee_tier_validation() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = tiered_contract(1400000, 600000);
+
+            // Assert
+            assert_eq!(contract.payee_tier(accounts.bob), PayeeTier::Fixed(1400000));
+            assert_eq!(contract.payee_tier(accounts.django), PayeeTier::Residual);
+            assert_eq!(contract.set_payee_tier(accounts.bob, PayeeTier::Fixed(0)), Err(Error::ZeroShare));
+            assert_eq!(
+                contract.set_payee_tier(accounts.frank, PayeeTier::Fixed(1)),
+                Err(Error::PayeeNotFound)
+            );
+            assert_eq!(contract.set_payee_tier(accounts.bob, PayeeTier::Residual), Ok(()));
+            assert_eq!(contract.payee_tier(accounts.bob), PayeeTier::Residual);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.set_payee_tier(accounts.bob, PayeeTier::Fixed(1)),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn owed_mode_pays_across_rounds() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let config = SplitterConfig { split_mode: SplitMode::Owed, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_owed(accounts.bob, 1500000).unwrap();
+            contract.set_owed(accounts.charlie, 1000000).unwrap();
+            let charlie_balance = get_balance(accounts.charlie);
+
+            // Act - First round only partially covers Charlie
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let first = contract.calculate_payout().unwrap();
+            contract.trigger_payout().unwrap();
+
+            // Assert
+            assert_eq!(first, vec![
+                PayoutInfo { payee: accounts.bob, amount: 1500000 },
+                PayoutInfo { payee: accounts.charlie, amount: 500000 },
+            ]);
+            assert_eq!(contract.owed(accounts.bob), 0);
+            assert_eq!(contract.owed(accounts.charlie), 500000);
+
+            // Act - A deposit arrives, the second round settles Charlie and keeps the surplus
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(1200000);
+            contract.deposit().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 1200000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.trigger_payout().unwrap();
+
+            // Assert
+            assert_eq!(contract.owed(accounts.charlie), 0);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 1000000);
+            assert_eq!(get_balance(contract.env().account_id()), 700000);
+            assert_eq!(contract.calculate_payout(), Err(Error::NothingOwed));
+        }
+
+        #[ink::test]
+        fn set_owed_validation() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+
+            // Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_owed(accounts.bob, 1), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_owed(accounts.django, 1), Err(Error::PayeeNotFound));
+            assert_eq!(contract.split_mode(), SplitMode::Proportional);
+        }
+
+        #[ink::test]
+        fn pools_are_paid_out_independently() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let pool_id = contract.create_pool(vec![accounts.django, accounts.eve]).unwrap();
+            let django_balance = get_balance(accounts.django);
+            let bob_balance = get_balance(accounts.bob);
+
+            // Act - Fund the default pool with 2000000 and the new pool with 1000001
+            set_caller::<DefaultEnvironment>(accounts.frank);
+            set_value_transferred::<DefaultEnvironment>(2000000);
+            contract.deposit_to_pool(0).unwrap();
+            set_value_transferred::<DefaultEnvironment>(1000001);
+            contract.deposit_to_pool(pool_id).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000001);
+
+            // Assert
+            assert_eq!(contract.pool_balance(0), Ok(2000000));
+            assert_eq!(contract.pool_balance(pool_id), Ok(1000001));
+
+            // Act - Pay out only the new pool
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.trigger_pool_payout(pool_id).unwrap();
+
+            // Assert - every transfer is reported like in a regular payout
+            use ink::env::Event;
+            let released: Vec<PaymentReleased> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == PaymentReleased::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <PaymentReleased as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(
+                released.iter().map(|event| (event.payee, event.amount)).collect::<Vec<_>>(),
+                vec![(accounts.django, 500001), (accounts.eve, 500000)]
+            );
+            assert_eq!(contract.released(accounts.eve), 500000);
+            assert_eq!(get_balance(accounts.django), django_balance + 500001);
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+            assert_eq!(contract.pool_balance(pool_id), Ok(0));
+            assert_eq!(contract.pool_balance(0), Ok(2000000));
+
+            // Act - The default pool only distributes its own funds
+            contract.trigger_payout().unwrap();
+
+            // Assert
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(contract.env().account_id()), 0);
+        }
+
+        #[ink::test]
+        fn pool_errors() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+
+            // Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.create_pool(vec![accounts.bob]), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.create_pool(Vec::new()), Err(Error::NoPayees));
+            assert_eq!(
+                contract.create_pool(vec![accounts.bob, accounts.charlie, accounts.bob]),
+                Err(Error::DuplicatePayee)
+            );
+            let too_many: Vec<AccountId> = (0..=MAX_PAYEES).map(|i| AccountId::from([i as u8 + 1; 32])).collect();
+            assert_eq!(contract.create_pool(too_many), Err(Error::TooManyPayees));
+            assert_eq!(contract.trigger_pool_payout(7), Err(Error::PoolNotFound));
+            assert_eq!(contract.pool_balance(7), Err(Error::PoolNotFound));
+            set_value_transferred::<DefaultEnvironment>(10);
+            assert_eq!(contract.deposit_to_pool(7), Err(Error::PoolNotFound));
+
+            // An empty pool has nothing to split
+            let pool_id = contract.create_pool(vec![accounts.bob]).unwrap();
+            assert_eq!(contract.pool_payees(pool_id), Ok(vec![accounts.bob]));
+            assert_eq!(contract.trigger_pool_payout(pool_id), Err(Error::ZeroShare));
+        }
+
+        #[ink::test]
+        fn pool_payouts_pass_the_payout_gates() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let pool_id = contract.create_pool(vec![accounts.django, accounts.eve]).unwrap();
+            set_value_transferred::<DefaultEnvironment>(2000000);
+            contract.deposit_to_pool(pool_id).unwrap();
+            contract.set_approvers(vec![accounts.bob, accounts.charlie], 1).unwrap();
+            contract.set_payout_cooldown(5).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 4000000);
+
+            // Assert - approvals are required and consumed
+            assert_eq!(contract.trigger_pool_payout(pool_id), Err(Error::InsufficientApprovals));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            contract.approve_payout(contract.payout_nonce()).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let nonce = contract.payout_nonce();
+            assert_eq!(contract.trigger_pool_payout(pool_id), Ok(()));
+            assert_eq!(contract.payout_nonce(), nonce + 1);
+            assert_eq!(contract.payout_approvals(), 0);
+
+            // Assert - the cooldown applies between two payouts of the pool
+            set_value_transferred::<DefaultEnvironment>(2000000);
+            contract.deposit_to_pool(pool_id).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            contract.approve_payout(contract.payout_nonce()).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.trigger_pool_payout(pool_id), Err(Error::CooldownActive));
+
+            // Assert - and so does the pause
+            contract.pause().unwrap();
+            assert_eq!(contract.trigger_pool_payout(pool_id), Err(Error::ContractPaused));
+        }
+
+        #[ink::test]
+        fn designated_payee_and_payee_count_queries() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Assert
+            assert_eq!(contract.get_designated_payee(), accounts.alice);
+            assert_eq!(contract.payee_count(), 2);
+
+            // Act
+            contract.add_payee(accounts.django, 1).unwrap();
+            contract.set_admin(accounts.eve).unwrap();
+
+            // Assert
+            assert_eq!(contract.get_designated_payee(), accounts.eve);
+            assert_eq!(contract.payee_count(), 3);
+        }
+
+        #[ink::test]
+        fn change_designated_payee_rotates_authority() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.change_designated_payee(accounts.eve), Err(Error::Unauthorized));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.change_designated_payee(accounts.django), Ok(()));
+
+            // Assert - only the new wallet can trigger payouts
+            assert_eq!(contract.get_designated_payee(), accounts.django);
+            assert_eq!(contract.trigger_payout(), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert!(contract.trigger_payout().is_ok());
+            assert_eq!(ink::env::test::recorded_events().count(), 4);
+        }
+
+        #[ink::test]
+        fn two_step_designation_transfer() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act - a mistyped proposal is cancelled
+            contract.propose_designated_payee(accounts.frank).unwrap();
+            assert_eq!(contract.pending_designated_payee(), Some(accounts.frank));
+            assert_eq!(contract.cancel_proposal(), Ok(()));
+
+            // Assert - Frank can no longer accept
+            set_caller::<DefaultEnvironment>(accounts.frank);
+            assert_eq!(contract.accept_designation(), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.cancel_proposal(), Err(Error::ProposalNotFound));
+
+            // Act - propose Django, who accepts
+            contract.propose_designated_payee(accounts.django).unwrap();
+            assert_eq!(contract.get_designated_payee(), accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.accept_designation(), Err(Error::Unauthorized));
+            assert_eq!(contract.propose_designated_payee(accounts.eve), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.accept_designation(), Ok(()));
+
+            // Assert
+            assert_eq!(contract.get_designated_payee(), accounts.django);
+            assert_eq!(contract.pending_designated_payee(), None);
+            assert_eq!(contract.accept_designation(), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn roles_separate_payee_management_from_payouts() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert!(contract.has_role(Role::Admin, accounts.alice));
+
+            // Act
+            contract.grant_role(Role::PayeeManager, accounts.django).unwrap();
+            contract.grant_role(Role::PayoutTrigger, accounts.eve).unwrap();
+
+            // Assert - the payee manager manages payees only
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.add_payee(accounts.frank, 1), Ok(()));
+            assert_eq!(contract.set_reserve(1), Err(Error::Unauthorized));
+            assert_eq!(contract.trigger_payout(), Err(Error::Unauthorized));
+            assert_eq!(contract.grant_role(Role::Admin, accounts.django), Err(Error::Unauthorized));
+
+            // Assert - the payout trigger triggers payouts only
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.remove_payee(accounts.frank), Err(Error::Unauthorized));
+            assert!(contract.trigger_payout().is_ok());
+
+            // Act - revoking takes effect immediately
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.revoke_role(Role::PayoutTrigger, accounts.eve).unwrap();
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+
+            // Assert - a granted admin configures but cannot hand over the designation
+            assert!(!contract.has_role(Role::PayoutTrigger, accounts.eve));
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.trigger_payout(), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_reserve(1), Ok(()));
+            assert_eq!(contract.set_admin(accounts.bob), Err(Error::Unauthorized));
+            assert_eq!(contract.revoke_role(Role::Admin, accounts.alice), Ok(()));
+            assert!(contract.has_role(Role::Admin, accounts.alice));
+        }
+
+        #[ink::test]
+        fn admin_and_operator_permissions() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let config = SplitterConfig { operator: Some(accounts.django), ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Assert - Operator can trigger payouts but not configure
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert!(contract.calculate_payout().is_ok());
+            assert_eq!(contract.create_pool(vec![accounts.bob]), Err(Error::Unauthorized));
+            assert_eq!(contract.set_operator(None), Err(Error::Unauthorized));
+            assert_eq!(contract.set_admin(accounts.django), Err(Error::Unauthorized));
+            assert!(contract.trigger_payout().is_ok());
+
+            // Assert - Anyone else can only preview
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.calculate_payout(), Err(Error::NothingDistributable));
+            assert_eq!(contract.trigger_payout(), Err(Error::Unauthorized));
+            assert_eq!(contract.register_token(accounts.frank), Err(Error::Unauthorized));
+
+            // Assert - Admin can do both
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            assert_eq!(contract.register_token(accounts.frank), Ok(()));
+            assert!(contract.trigger_payout().is_ok());
+        }
+
+        #[ink::test]
+        fn rotate_admin_and_operator() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            assert_eq!(contract.set_operator(Some(accounts.django)), Ok(()));
+            assert_eq!(contract.set_admin(accounts.eve), Ok(()));
+
+            // Assert
+            assert_eq!(contract.operator(), Some(accounts.django));
+            assert_eq!(contract.admin(), accounts.eve);
+            assert_eq!(ink::env::test::recorded_events().count(), 2);
+
+            // The previous admin lost all rights, the new one gained them
+            assert_eq!(contract.set_operator(None), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.set_operator(None), Ok(()));
+            assert_eq!(contract.operator(), None);
+        }
+
+        #[ink::test]
+        fn trigger_payout_lossy_reports_failures() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000000);
+            let bob_balance = get_balance(accounts.bob);
+            let charlie_balance = get_balance(accounts.charlie);
+            reject_transfers_to(accounts.charlie);
+
+            // Act
+            let report = contract.trigger_payout_lossy().unwrap();
+
+            // Assert
+            assert_eq!(report, vec![
+                (accounts.bob, Ok(1000000)),
+                (accounts.charlie, Err(Error::TransferFailed)),
+                (accounts.django, Ok(1000000)),
+            ]);
+            assert_eq!(report.iter().filter(|(_, result)| result.is_err()).count(), 1);
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance);
+            assert_eq!(get_balance(contract.env().account_id()), 1000000);
+
+            // The reentrancy lock has been released
+            assert!(!contract.locked);
+        }
+
+        #[ink::test]
+        fn failed_transfers_are_parked_and_claimable() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            let bob_balance = get_balance(accounts.bob);
+            let charlie_balance = get_balance(accounts.charlie);
+            reject_transfers_to(accounts.charlie);
+
+            // Act - First round, Charlie's transfer fails
+            let executed = contract.trigger_payout();
+
+            // Assert - the parked share is not reported as transferred
+            assert_eq!(
+                executed,
+                Ok(vec![
+                    PayoutInfo { payee: accounts.bob, amount: 1000000 },
+                    PayoutInfo { payee: accounts.django, amount: 1000000 },
+                ])
+            );
+
+            // Assert
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 1000000);
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(contract_id), 1000000);
+
+            // Act - Deposit and pay out again; the parked funds are not split again
+            accept_transfers_to(accounts.charlie);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            set_value_transferred::<DefaultEnvironment>(3000000);
+            contract.deposit().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 4000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert!(contract.trigger_payout().is_ok());
+
+            // Assert
+            assert_eq!(get_balance(accounts.bob), bob_balance + 2000000);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 1000000);
+            assert_eq!(get_balance(contract_id), 1000000);
+
+            // Act - Charlie claims the parked amount
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.claim(), Ok(1000000));
+
+            // Assert
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 0);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 2000000);
+            assert_eq!(get_balance(contract_id), 0);
+            assert_eq!(contract.claim(), Err(Error::ZeroShare));
+        }
+
+        #[ink::test]
+        fn release_for_pushes_funds_to_the_payee() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            reject_transfers_to(accounts.charlie);
+            contract.trigger_payout().unwrap();
+            accept_transfers_to(accounts.charlie);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000000);
+            let charlie_balance = get_balance(accounts.charlie);
+            let eve_balance = get_balance(accounts.eve);
+            assert_eq!(contract.releasable(accounts.charlie), 2000000);
+
+            // Act - An unrelated account pushes Charlie's pending claim and current share
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            let result = contract.release_for(accounts.charlie);
+
+            // Assert
+            assert_eq!(result, Ok(2000000));
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 2000000);
+            assert_eq!(get_balance(accounts.eve), eve_balance);
+            assert_eq!(contract.releasable(accounts.bob), 1000000);
+            assert_eq!(contract.release_for(accounts.charlie), Err(Error::ZeroShare));
+            assert_eq!(contract.release_for(accounts.frank), Err(Error::PayeeNotFound));
+        }
+
+        #[ink::test]
+        fn claim_lock_up_period() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let unlock_block = contract.env().block_number() + 2;
+            contract.set_claim_lock(accounts.charlie, unlock_block).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let charlie_balance = get_balance(accounts.charlie);
+
+            // Act - Charlie's share is parked during the lock-up
+            let report = contract.trigger_payout_lossy().unwrap();
+
+            // Assert
+            assert_eq!(report[1], (accounts.charlie, Err(Error::ClaimLocked)));
+            assert_eq!(contract.claim_unlock_block(accounts.charlie), Some(unlock_block));
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 1000000);
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.claim(), Err(Error::ClaimLocked));
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.release_for(accounts.charlie), Err(Error::ClaimLocked));
+
+            // Act - At the unlock block the claim goes through
+            advance_block::<DefaultEnvironment>();
+            let result = contract.claim();
+
+            // Assert
+            assert_eq!(result, Ok(1000000));
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 1000000);
+        }
+
+        #[ink::test]
+        fn set_claim_lock_validation() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+
+            // Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_claim_lock(accounts.bob, 10), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_claim_lock(accounts.django, 10), Err(Error::PayeeNotFound));
+            assert_eq!(contract.claim_unlock_block(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn deposits_close_after_deadline() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let config = SplitterConfig { deposit_deadline: Some(1), ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(2000000);
+
+            // Act - Deposits up to and including the deadline block are accepted
+            assert_eq!(contract.deposit(), Ok(()));
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.deposits_open(), (true, Some(1)));
+            assert_eq!(contract.deposit(), Ok(()));
+            advance_block::<DefaultEnvironment>();
+
+            // Assert
+            assert_eq!(contract.deposits_open(), (false, Some(1)));
+            assert_eq!(contract.deposit(), Err(Error::DepositsClosed));
+
+            // Payouts still work
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 4000000);
+            assert!(contract.trigger_payout().is_ok());
+
+            // Only the admin moves the deadline
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.set_deposit_deadline(None), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_deposit_deadline(None), Ok(()));
+            assert_eq!(contract.deposit(), Ok(()));
+        }
+
+        #[ink::test]
+        fn payout_requires_approvals() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let approvers = vec![accounts.django, accounts.eve, accounts.frank];
+            contract.set_approvers(approvers, 2).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Act - Below the threshold, a duplicate approval does not count
+            let payout_id = contract.payout_nonce();
+            set_caller::<DefaultEnvironment>(accounts.django);
+            contract.approve_payout(payout_id).unwrap();
+            contract.approve_payout(payout_id).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Assert
+            assert_eq!(contract.payout_approvals(), 1);
+            assert_eq!(contract.trigger_payout(), Err(Error::InsufficientApprovals));
+
+            // Act - Exactly at the threshold
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.approve_payout(payout_id + 1), Err(Error::NonceMismatch));
+            contract.approve_payout(payout_id).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Assert
+            assert!(contract.trigger_payout().is_ok());
+
+            // The nonce rolled over, so the approvals were consumed and the old id is stale
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            assert_eq!(contract.payout_approvals(), 0);
+            assert_eq!(contract.trigger_payout(), Err(Error::InsufficientApprovals));
+            set_caller::<DefaultEnvironment>(accounts.frank);
+            assert_eq!(contract.approve_payout(payout_id), Err(Error::NonceMismatch));
+        }
+
+        #[ink::test]
+        fn approval_committee_validation() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Assert
+            assert_eq!(contract.set_approvers(vec![accounts.django], 2), Err(Error::InvalidThreshold));
+            contract.set_approvers(vec![accounts.django], 1).unwrap();
+            assert_eq!(contract.approvers(), (vec![accounts.django], 1));
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.approve_payout(0), Err(Error::Unauthorized));
+            assert_eq!(contract.set_approvers(Vec::new(), 0), Err(Error::Unauthorized));
+        }
+
+        // Helper function to derive the ECDSA account of a secret key, as `trigger_payout_signed`
+        // does from a recovered public key.
+        fn ecdsa_account(secret: &SecretKey) -> AccountId {
+            let public_key = PublicKey::from_secret_key(SECP256K1, secret).serialize();
+            let mut account = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut account);
+            AccountId::from(account)
+        }
+
+        // Helper function to sign a 32-byte message hash into a 65-byte recoverable signature.
+        fn sign(secret: &SecretKey, message_hash: [u8; 32]) -> [u8; 65] {
+            let message = Message::from_digest_slice(&message_hash).unwrap();
+            let (recovery_id, compact) = SECP256K1
+                .sign_ecdsa_recoverable(&message, secret)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            signature
+        }
+
+        #[ink::test]
+        fn trigger_payout_signed_by_offline_key() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let signer_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+            let config = SplitterConfig {
+                payout_signer: Some(ecdsa_account(&signer_key)),
+                ..Default::default()
+            };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let bob_balance = get_balance(accounts.bob);
+            let signature = sign(&signer_key, contract.signed_payout_hash(0));
+
+            // Act - A relayer submits the signed payout
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            let result = contract.trigger_payout_signed(0, signature);
+
+            // Assert
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(contract.payout_nonce(), 1);
+
+            // The same signature cannot be replayed
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            assert_eq!(contract.trigger_payout_signed(0, signature), Err(Error::NonceMismatch));
+        }
+
+        #[ink::test]
+        fn trigger_payout_signed_rejects_wrong_signer() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let signer_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+            let other_key = SecretKey::from_slice(&[0x43; 32]).unwrap();
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let signature = sign(&other_key, contract.signed_payout_hash(0));
+
+            // Assert - No signer configured yet
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.trigger_payout_signed(0, signature), Err(Error::Unauthorized));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_payout_signer(Some(ecdsa_account(&signer_key))).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.eve);
+
+            // Assert
+            assert_eq!(contract.trigger_payout_signed(0, signature), Err(Error::InvalidSignature));
+            assert_eq!(contract.payout_nonce(), 0);
+        }
+
+        #[ink::test]
+        fn contract_info_reflects_configuration() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let config = SplitterConfig {
+                payout_cooldown: 5,
+                operator: Some(accounts.django),
+                deposit_deadline: Some(100),
+                ..Default::default()
+            };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            contract.set_reserve(500000).unwrap();
+            contract.pause().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2500000);
+
+            // Act
+            let info = contract.contract_info();
+
+            // Assert
+            assert_eq!(info, ContractInfo {
+                version: VERSION,
+                designated_payee: accounts.alice,
+                operator: Some(accounts.django),
+                payee_count: 2,
+                balance: 2500000,
+                distributable: 2000000,
+                split_mode: SplitMode::Proportional,
+                payout_nonce: 0,
+                payout_cooldown: 5,
+                deposit_deadline: Some(100),
+                approval_threshold: 0,
+                paused: true,
+                reserve: 500000,
+            });
+        }
+
+        #[ink::test]
+        fn duplicate_payees_are_merged() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.bob];
+            let config = SplitterConfig { merge_duplicate_payees: true, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let bob_balance = get_balance(accounts.bob);
+
+            // Act
+            contract.trigger_payout().unwrap();
+
+            // Assert - Bob gets a single share
+            assert_eq!(contract.get_payees(), vec![accounts.bob, accounts.charlie]);
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+        }
+
+        #[ink::test]
+        fn duplicate_payees_are_kept_by_default() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.bob];
+
+            // Act
+            let contract = PaymentSplitter::new(payees.clone(), accounts.alice);
+
+            // Assert
+            assert_eq!(contract.get_payees(), payees);
+        }
+
+        #[ink::test]
+        fn add_payees_batch() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            let added = contract.add_payees(vec![(accounts.charlie, 1), (accounts.django, 1)]);
+
+            // Assert
+            assert_eq!(added, Ok(2));
+            assert_eq!(contract.get_payees(), vec![accounts.bob, accounts.charlie, accounts.django]);
+            assert_eq!(ink::env::test::recorded_events().count(), 2);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.add_payees(vec![(accounts.eve, 1)]), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn add_payee_with_share() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            let added = contract.add_payee(accounts.charlie, 2);
+
+            // Assert - Charlie weighs twice as much as Bob from the next payout on
+            assert_eq!(added, Ok(()));
+            assert_eq!(contract.get_payees(), vec![accounts.bob, accounts.charlie]);
+            assert_eq!(contract.total_shares(), 3);
+            use ink::env::Event;
+            let added: Vec<PayeeAdded> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == PayeeAdded::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <PayeeAdded as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(added.len(), 1);
+            assert_eq!((added[0].payee, added[0].share), (accounts.charlie, 2));
+            assert_eq!(
+                contract.calculate_payout(),
+                Ok(vec![
+                    PayoutInfo { payee: accounts.bob, amount: 1000000 },
+                    PayoutInfo { payee: accounts.charlie, amount: 2000000 },
+                ])
+            );
+
+            assert_eq!(contract.add_payee(accounts.charlie, 1), Err(Error::DuplicatePayee));
+            assert_eq!(contract.add_payee(accounts.django, 0), Err(Error::ZeroShare));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.add_payee(accounts.eve, 1), Err(Error::Unauthorized));
+            assert_eq!(contract.get_payees(), vec![accounts.bob, accounts.charlie]);
+        }
+
+        #[ink::test]
+        fn remove_payee_keeps_allocated_funds_claimable() {
+            // Arrange - Charlie has a parked share and Django an accrued one
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.freeze_payee(accounts.django).unwrap();
+            reject_transfers_to(accounts.charlie);
+            contract.trigger_payout().unwrap();
+            accept_transfers_to(accounts.charlie);
+
+            // Act
+            assert_eq!(contract.remove_payee(accounts.charlie), Ok(()));
+            assert_eq!(contract.remove_payee(accounts.django), Ok(()));
+
+            // Assert - both allocations are pending claims, nothing is redistributed
+            assert_eq!(contract.get_payees(), vec![accounts.bob]);
+            assert_eq!(contract.total_shares(), 1);
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 1000000);
+            assert_eq!(contract.pending_claim_of(accounts.django), 1000000);
+            assert_eq!(contract.accrued(accounts.django), 0);
+            assert_eq!(contract.distributable(), 0);
+
+            // Act - the removed payees can still collect, Django once unfrozen
+            let charlie_balance = get_balance(accounts.charlie);
+            assert_eq!(contract.release_for(accounts.charlie), Ok(1000000));
+            assert_eq!(contract.release_for(accounts.django), Err(Error::PayeeFrozen));
+            contract.unfreeze_payee(accounts.django).unwrap();
+            let django_balance = get_balance(accounts.django);
+            assert_eq!(contract.release_for(accounts.django), Ok(1000000));
+
+            // Assert
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 1000000);
+            assert_eq!(get_balance(accounts.django), django_balance + 1000000);
+            assert_eq!(contract.remove_payee(accounts.charlie), Err(Error::PayeeNotFound));
+            assert_eq!(contract.remove_payee(accounts.bob), Err(Error::NoPayees));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.remove_payee(accounts.bob), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn add_payees_rejects_duplicates_atomically() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            let internal = contract.add_payees(vec![(accounts.charlie, 1), (accounts.eve, 1), (accounts.charlie, 2)]);
+            let existing = contract.add_payees(vec![(accounts.charlie, 1), (accounts.bob, 1)]);
+
+            // Assert
+            assert_eq!(internal, Err(Error::DuplicatePayee));
+            assert_eq!(existing, Err(Error::DuplicatePayee));
+            assert_eq!(contract.get_payees(), vec![accounts.bob]);
+            assert_eq!(ink::env::test::recorded_events().count(), 0);
+        }
+
+        #[ink::test]
+        fn payee_batches_apply_atomically() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 6000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act - a zero weight rejects the whole batch
+            let rejected = contract.add_payees(vec![(accounts.charlie, 2), (accounts.django, 0)]);
+            let added = contract.add_payees(vec![(accounts.charlie, 2), (accounts.django, 3), (accounts.eve, 1)]);
+
+            // Assert
+            assert_eq!(rejected, Err(Error::ZeroShare));
+            assert_eq!(added, Ok(3));
+            assert_eq!(contract.total_shares(), 7);
+
+            // Act - an unknown or repeated account rejects the whole batch
+            assert_eq!(contract.remove_payees(vec![accounts.eve, accounts.frank]), Err(Error::PayeeNotFound));
+            assert_eq!(contract.remove_payees(vec![accounts.eve, accounts.eve]), Err(Error::DuplicatePayee));
+            assert_eq!(
+                contract.remove_payees(vec![accounts.bob, accounts.charlie, accounts.django, accounts.eve]),
+                Err(Error::NoPayees)
+            );
+            assert_eq!(contract.get_payees().len(), 4);
+            let removed = contract.remove_payees(vec![accounts.eve, accounts.bob]);
+
+            // Assert - Charlie and Django split 2:3
+            assert_eq!(removed, Ok(2));
+            assert_eq!(contract.get_payees(), vec![accounts.charlie, accounts.django]);
+            assert_eq!(contract.total_shares(), 5);
+            assert_eq!(
+                contract.calculate_payout(),
+                Ok(vec![
+                    PayoutInfo { payee: accounts.charlie, amount: 2400000 },
+                    PayoutInfo { payee: accounts.django, amount: 3600000 },
+                ])
+            );
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.remove_payees(vec![accounts.django]), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn add_payees_respects_cap() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let batch: Vec<(AccountId, u128)> = (0..MAX_PAYEES as u8)
+                .map(|i| {
+                    let mut raw = [0xAA; 32];
+                    raw[0] = i;
+                    (AccountId::from(raw), 1)
+                })
+                .collect();
+
+            // Act
+            let result = contract.add_payees(batch.clone());
+
+            // Assert
+            assert_eq!(result, Err(Error::TooManyPayees));
+            assert_eq!(contract.get_payees(), vec![accounts.bob]);
+            assert_eq!(contract.add_payees(batch[..(MAX_PAYEES as usize) - 1].to_vec()), Ok(MAX_PAYEES - 1));
+        }
+
+        #[ink::test]
+        fn payee_shares_add_up_to_balance() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees.clone(), accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 1000001);
+
+            // Act
+            let shares: Vec<Balance> = payees
+                .iter()
+                .map(|payee| contract.payee_share(*payee).unwrap())
+                .collect();
+
+            // Assert
+            assert_eq!(shares, vec![333335, 333333, 333333]);
+            assert_eq!(shares.iter().sum::<Balance>(), get_balance(contract.env().account_id()));
+            assert_eq!(contract.payee_share(accounts.eve), Err(Error::PayeeNotFound));
+        }
+
+        #[ink::test]
+        fn contributions_mode_pays_pro_rata() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { split_mode: SplitMode::Contributions, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(Vec::new(), accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1000000);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_value_transferred::<DefaultEnvironment>(2000000);
+            contract.deposit().unwrap();
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            contract.deposit().unwrap();
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            contract.deposit().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 4000000);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let result = contract.trigger_payout();
+
+            // Assert
+            assert_eq!(
+                result,
+                Ok(vec![
+                    PayoutInfo { payee: accounts.bob, amount: 3000000 },
+                    PayoutInfo { payee: accounts.charlie, amount: 1000000 },
+                ])
+            );
+            assert_eq!(get_balance(accounts.bob), 4000000);
+            assert_eq!(get_balance(accounts.charlie), 2000000);
+            assert_eq!(contract.contribution_of(accounts.bob), 0);
+            assert_eq!(contract.contribution_of(accounts.charlie), 0);
+        }
+
+        #[ink::test]
+        fn contributions_mode_drops_contributors_after_payout() {
+            // Arrange - a configured payee, and as many contributors as fit
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { split_mode: SplitMode::Contributions, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(vec![accounts.bob], accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            for i in 1..MAX_PAYEES {
+                let depositor = AccountId::from([i as u8 + 100; 32]);
+                set_account_balance::<DefaultEnvironment>(depositor, 1000000);
+                set_caller::<DefaultEnvironment>(depositor);
+                contract.deposit().unwrap();
+            }
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.deposit(), Err(Error::TooManyPayees));
+            assert_eq!(contract.get_payees().len(), MAX_PAYEES as usize);
+
+            // Act
+            set_account_balance::<DefaultEnvironment>(contract_id, (MAX_PAYEES as Balance - 1) * 1000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.trigger_payout().unwrap();
+
+            // Assert - only the configured payee is left, and new depositors are accepted again
+            assert_eq!(contract.get_payees(), vec![accounts.bob]);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.deposit(), Ok(()));
+            assert_eq!(contract.get_payees(), vec![accounts.bob, accounts.eve]);
+        }
+
+        #[ink::test]
+        fn contributions_mode_remainder_goes_to_first_payee() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { split_mode: SplitMode::Contributions, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(Vec::new(), accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            contract.deposit().unwrap();
+            // Funds sent without `deposit` are not a contribution.
+            set_account_balance::<DefaultEnvironment>(contract_id, 1500000);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let share = contract.payee_share(accounts.bob);
+            let result = contract.trigger_payout();
+
+            // Assert
+            assert_eq!(share, Ok(1500000));
+            assert_eq!(result, Ok(vec![PayoutInfo { payee: accounts.bob, amount: 1500000 }]));
+            assert_eq!(get_balance(accounts.bob), 2500000);
+            assert_eq!(contract.trigger_payout(), Err(Error::NothingDistributable));
+        }
+
+        /// Returns the round ids of the recorded `PaymentReleased` and `PayoutTriggered` events.
+        fn recorded_round_ids() -> (Vec<u64>, Vec<u64>) {
+            use ink::env::Event;
+            let mut released = Vec::new();
+            let mut triggered = Vec::new();
+            for event in ink::env::test::recorded_events() {
+                let signature = event.topics[0].as_slice();
+                if Some(signature) == PaymentReleased::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]) {
+                    let decoded = <PaymentReleased as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+                    released.push(decoded.round_id);
+                } else if Some(signature) == PayoutTriggered::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]) {
+                    let decoded = <PayoutTriggered as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+                    triggered.push(decoded.round_id);
+                }
+            }
+            (released, triggered)
+        }
+
+        #[ink::test]
+        fn payout_events_carry_round_id() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            let contract_id = contract.env().account_id();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.current_round(), 1);
+
+            // Act
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            contract.trigger_payout().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            contract.trigger_payout().unwrap();
+
+            // Assert
+            let (released, triggered) = recorded_round_ids();
+            assert_eq!(released, vec![1, 1, 2, 2]);
+            assert_eq!(triggered, vec![1, 2]);
+            assert_eq!(contract.current_round(), 3);
+        }
+
+        #[ink::test]
+        fn auto_split_forwards_deposits() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { auto_split: true, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(
+                vec![accounts.bob, accounts.charlie],
+                accounts.alice,
+                config
+            );
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1000000);
+            set_account_balance::<DefaultEnvironment>(contract_id, 1000101);
+            reject_transfers_to(accounts.charlie);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(101);
+            let result = contract.deposit();
+            accept_transfers_to(accounts.charlie);
+
+            // Assert
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1000051);
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 50);
+            assert_eq!(get_balance(contract_id), 1000050);
+            assert_eq!(contract.payout_nonce(), 0);
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.claim(), Ok(50));
+            assert_eq!(get_balance(accounts.charlie), 1000050);
+            assert_eq!(get_balance(contract_id), 1000000);
+        }
+
+        #[ink::test]
+        fn remainder_goes_to_remainder_recipient() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { remainder_recipient: Some(accounts.django), ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(
+                vec![accounts.bob, accounts.charlie],
+                accounts.alice,
+                config
+            );
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.django, 1000000);
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000001);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            let payout_info = contract.calculate_payout().unwrap();
+            contract.trigger_payout().unwrap();
+
+            // Assert - the recipient is not a payee but gets the remainder as its own entry
+            assert_eq!(
+                payout_info,
+                vec![
+                    PayoutInfo { payee: accounts.bob, amount: 1000000 },
+                    PayoutInfo { payee: accounts.charlie, amount: 1000000 },
+                    PayoutInfo { payee: accounts.django, amount: 1 }
+                ]
+            );
+            assert_eq!(get_balance(accounts.bob), 2000000);
+            assert_eq!(get_balance(accounts.charlie), 2000000);
+            assert_eq!(get_balance(accounts.django), 1000001);
+        }
+
+        #[ink::test]
+        fn remainder_recipient_without_remainder() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            contract.set_remainder_recipient(Some(accounts.django)).unwrap();
+            let payout_info = contract.calculate_payout().unwrap();
+
+            // Assert - no extra entry when the balance divides evenly
+            assert_eq!(contract.remainder_recipient(), Some(accounts.django));
+            assert_eq!(
+                payout_info,
+                vec![
+                    PayoutInfo { payee: accounts.bob, amount: 1000000 },
+                    PayoutInfo { payee: accounts.charlie, amount: 1000000 }
+                ]
+            );
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_remainder_recipient(None), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn lifetime_totals() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1000000);
+
+            // Act - first cycle
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(2000000);
+            contract.deposit().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.trigger_payout().unwrap();
+
+            // Assert
+            assert_eq!(contract.total_received(), 2000000);
+            assert_eq!(contract.total_distributed(), 2000000);
+            assert_eq!(contract.untracked_balance(), 0);
+
+            // Act - second cycle, with 500000 sent to the contract without `deposit`
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            contract.deposit().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 1500000);
+            assert_eq!(contract.untracked_balance(), 500000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.trigger_payout().unwrap();
+
+            // Assert
+            assert_eq!(contract.total_received(), 3000000);
+            assert_eq!(contract.total_distributed(), 3500000);
+            assert_eq!(contract.untracked_balance(), 500000);
+        }
+
+        #[ink::test]
+        fn export_state_matches_storage() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(
+                vec![accounts.bob, accounts.charlie, accounts.django],
+                accounts.alice
+            );
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.django, 1000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_operator(Some(accounts.eve)).unwrap();
+            contract.set_payee_tier(accounts.bob, PayeeTier::Fixed(1000000)).unwrap();
+            contract.update_share(accounts.charlie, 3).unwrap();
+            set_value_transferred::<DefaultEnvironment>(3000000);
+            contract.deposit().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            reject_transfers_to(accounts.charlie);
+            contract.trigger_payout().unwrap();
+            accept_transfers_to(accounts.charlie);
+            contract.pause().unwrap();
+
+            // Act
+            let encoded = scale::Encode::encode(&contract.export_state(1, 1));
+            let snapshot = <StateSnapshot as scale::Decode>::decode(&mut &encoded[..]).unwrap();
+
+            // Assert
+            assert_eq!(snapshot.designated_payee, accounts.alice);
+            assert_eq!(snapshot.operator, Some(accounts.eve));
+            assert_eq!(snapshot.payee_count, 3);
+            assert_eq!(snapshot.payees.len(), 1);
+            assert_eq!(snapshot.payees[0].account, accounts.charlie);
+            assert_eq!(snapshot.payees[0].tier, PayeeTier::Residual);
+            assert_eq!(snapshot.payees[0].share, 3);
+            assert_eq!(snapshot.payees[0].pending_claim, 1500000);
+            assert_eq!(snapshot.balance, get_balance(contract_id));
+            assert_eq!(snapshot.distributable, 0);
+            assert_eq!(snapshot.total_pending, 1500000);
+            assert_eq!(snapshot.pooled_balance, 0);
+            assert_eq!(snapshot.split_mode, contract.split_mode());
+            assert_eq!(snapshot.remainder_recipient, None);
+            assert_eq!(snapshot.payout_nonce, contract.payout_nonce());
+            assert_eq!(snapshot.round_id, contract.current_round());
+            assert!(snapshot.paused);
+            assert_eq!(snapshot.reserve, contract.reserve());
+            assert_eq!(snapshot.total_received, contract.total_received());
+            assert_eq!(snapshot.total_distributed, 1500000);
+
+            assert_eq!(contract.export_state(0, 10).payees.len(), 3);
+            assert!(contract.export_state(3, 10).payees.is_empty());
+        }
+
+        #[ink::test]
+        fn payment_hook_configuration() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let unauthorized = contract.set_payment_hook(accounts.bob, true);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_payment_hook(accounts.charlie, true).unwrap();
+
+            // Assert
+            assert_eq!(unauthorized, Err(Error::Unauthorized));
+            assert!(!contract.has_payment_hook(accounts.bob));
+            assert!(contract.has_payment_hook(accounts.charlie));
+
+            contract.set_payment_hook(accounts.charlie, false).unwrap();
+            assert!(!contract.has_payment_hook(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn deposit_for_attributes_to_beneficiary() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(1000);
+
+            // Act
+            let result = contract.deposit_for(accounts.eve);
+            let zero_account = contract.deposit_for(AccountId::from([0; 32]));
+            set_value_transferred::<DefaultEnvironment>(0);
+            let zero_value = contract.deposit_for(accounts.eve);
+
+            // Assert
+            assert_eq!(result, Ok(()));
+            assert_eq!(zero_account, Err(Error::InvalidAccount));
+            assert_eq!(zero_value, Err(Error::ZeroShare));
+            assert_eq!(contract.deposited_by(accounts.eve), 1000);
+            assert_eq!(contract.deposited_by(accounts.django), 0);
+            assert_eq!(contract.total_received(), 1000);
+
+            let event = ink::env::test::recorded_events().next().unwrap();
+            let decoded = <Deposit as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert_eq!(decoded.from, accounts.eve);
+            assert_eq!(decoded.caller, accounts.django);
+        }
+
+        #[ink::test]
+        fn replace_payee_keeps_position_and_claims() {
+            // Arrange - Bob has a pending claim from a failed transfer
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.eve, 1000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            reject_transfers_to(accounts.bob);
+            contract.trigger_payout().unwrap();
+            accept_transfers_to(accounts.bob);
+
+            // Act
+            let result = contract.replace_payee(accounts.bob, accounts.eve);
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000001);
+            contract.trigger_payout().unwrap();
+
+            // Assert - Eve took Bob's place, including the remainder-bearing first share
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.get_payees(), vec![accounts.eve, accounts.charlie]);
+            assert_eq!(contract.pending_claim_of(accounts.bob), 0);
+            assert_eq!(contract.pending_claim_of(accounts.eve), 1000000);
+            assert_eq!(get_balance(accounts.eve), 2000001);
+            assert_eq!(get_balance(accounts.charlie), 3000000);
+
+            assert_eq!(contract.replace_payee(accounts.bob, accounts.django), Err(Error::PayeeNotFound));
+            assert_eq!(contract.replace_payee(accounts.eve, accounts.charlie), Err(Error::DuplicatePayee));
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.replace_payee(accounts.eve, accounts.django), Err(Error::Unauthorized));
+        }
+
+        /// Returns the names of the recorded configuration events, in order.
+        fn recorded_config_events() -> Vec<&'static str> {
+            use ink::env::Event;
+            let names = [
+                (PayeeAdded::SIGNATURE_TOPIC, "PayeeAdded"),
+                (PayeeRemoved::SIGNATURE_TOPIC, "PayeeRemoved"),
+                (PayeeReplaced::SIGNATURE_TOPIC, "PayeeReplaced"),
+                (DesignatedPayeeChanged::SIGNATURE_TOPIC, "DesignatedPayeeChanged"),
+                (OperatorChanged::SIGNATURE_TOPIC, "OperatorChanged"),
+                (ConfigChanged::SIGNATURE_TOPIC, "ConfigChanged"),
+            ];
+            ink::env::test::recorded_events()
+                .filter_map(|event| {
+                    names
+                        .iter()
+                        .find(|(topic, _)| topic.as_ref().map(|t| &t[..]) == Some(&event.topics[0][..]))
+                        .map(|(_, name)| *name)
+                })
+                .collect()
+        }
+
+        #[ink::test]
+        fn configuration_changes_emit_events() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            contract.add_payees(vec![(accounts.charlie, 1)]).unwrap();
+            contract.replace_payee(accounts.charlie, accounts.django).unwrap();
+            contract.set_payee_tier(accounts.django, PayeeTier::Fixed(10)).unwrap();
+            contract.set_operator(Some(accounts.eve)).unwrap();
+            contract.set_deposit_deadline(Some(10)).unwrap();
+            contract.set_admin(accounts.frank).unwrap();
+
+            // Assert
+            assert_eq!(
+                recorded_config_events(),
+                vec![
+                    "PayeeAdded",
+                    "PayeeReplaced",
+                    "ConfigChanged",
+                    "OperatorChanged",
+                    "ConfigChanged",
+                    "DesignatedPayeeChanged"
+                ]
+            );
+            let event = ink::env::test::recorded_events().nth(4).unwrap();
+            let decoded = <ConfigChanged as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert_eq!(decoded.field, ConfigField::DepositDeadline);
+            assert_eq!(decoded.old, scale::Encode::encode(&None::<BlockNumber>));
+            assert_eq!(decoded.new, scale::Encode::encode(&Some::<BlockNumber>(10)));
+        }
+
+        #[ink::test]
+        fn deposits_above_cap_are_rejected() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { max_deposit: Some(1000), ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(vec![accounts.bob], accounts.alice, config);
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 1000000);
+            set_caller::<DefaultEnvironment>(accounts.django);
+
+            // Act
+            set_value_transferred::<DefaultEnvironment>(1000);
+            let at_cap = contract.deposit();
+            set_value_transferred::<DefaultEnvironment>(1001);
+            let over_cap = contract.deposit();
+            let over_cap_for = contract.deposit_for(accounts.eve);
+
+            // Assert
+            assert_eq!(at_cap, Ok(()));
+            assert_eq!(over_cap, Err(Error::DepositTooLarge));
+            assert_eq!(over_cap_for, Err(Error::DepositTooLarge));
+            assert_eq!(contract.total_received(), 1000);
+            assert_eq!(get_balance(contract_id), 1000000);
+
+            assert_eq!(contract.set_max_deposit(None), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_max_deposit(None).unwrap();
+            assert_eq!(contract.max_deposit(), None);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.deposit(), Ok(()));
+        }
+
+        #[ink::test]
+        fn validate_payout_flags_dust_shares() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            let minimum_balance = contract.env().minimum_balance();
+
+            // Act - each share is below the minimum balance
+            set_account_balance::<DefaultEnvironment>(contract_id, minimum_balance + 1);
+            let too_small = contract.validate_payout();
+            set_account_balance::<DefaultEnvironment>(contract_id, minimum_balance * 4);
+            let large_enough = contract.validate_payout();
+
+            // Assert
+            assert_eq!(
+                too_small,
+                vec![
+                    (accounts.bob, Err(Error::BelowMinimumBalance)),
+                    (accounts.charlie, Err(Error::BelowMinimumBalance))
+                ]
+            );
+            assert_eq!(
+                large_enough,
+                vec![(accounts.bob, Ok(minimum_balance * 2)), (accounts.charlie, Ok(minimum_balance * 2))]
+            );
+            assert_eq!(get_balance(contract_id), minimum_balance * 4);
+        }
+
+        #[ink::test]
+        fn payout_plans_can_be_cancelled() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { dispute_window: 3, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(
+                vec![accounts.bob, accounts.charlie],
+                accounts.alice,
+                config
+            );
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act - commit and cancel
+            let first = contract.commit_payout().unwrap();
+            let cancelled = contract.cancel_payout_plan(first);
+            let executed_cancelled = contract.execute_payout(first);
+
+            // Assert
+            assert_eq!(cancelled, Ok(()));
+            assert_eq!(executed_cancelled, Err(Error::PlanNotFound));
+            assert_eq!(contract.cancel_payout_plan(first), Err(Error::PlanNotFound));
+
+            // Act - commit again and execute once the dispute window has passed
+            let second = contract.commit_payout().unwrap();
+            let too_early = contract.execute_payout(second);
+            for _ in 0..3 {
+                advance_block::<DefaultEnvironment>();
+            }
+            let executed = contract.execute_payout(second);
+
+            // Assert
+            assert_eq!(too_early, Err(Error::DisputeWindowActive));
+            assert_eq!(
+                executed,
+                Ok(vec![(accounts.bob, Ok(1000000)), (accounts.charlie, Ok(1000000))])
+            );
+            assert_eq!(get_balance(accounts.bob), 2000000);
+            assert_eq!(get_balance(accounts.charlie), 2000000);
+            assert_eq!(contract.payout_plan(second), None);
+        }
+
+        #[ink::test]
+        fn poke_runs_scheduled_payouts() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig {
+                payout_interval: 5,
+                poke_min_balance: 1000000,
+                poke_tip: 1000,
+                ..Default::default()
+            };
+            let mut contract = PaymentSplitter::new_with_config(
+                vec![accounts.bob, accounts.charlie],
+                accounts.alice,
+                config
+            );
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(accounts.bob, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1000000);
+            set_account_balance::<DefaultEnvironment>(accounts.django, 1000000);
+
+            // The manual payout starts the interval clock
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.trigger_payout().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2001000);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.django);
+            let too_early = contract.poke();
+            for _ in 0..5 {
+                advance_block::<DefaultEnvironment>();
+            }
+            let due = contract.poke();
+
+            // Assert
+            assert_eq!(too_early, Ok(false));
+            assert_eq!(due, Ok(true));
+            assert_eq!(get_balance(accounts.django), 1001000);
+            assert_eq!(get_balance(accounts.bob), 3000000);
+            assert_eq!(get_balance(accounts.charlie), 3000000);
+            assert_eq!(contract.poke(), Ok(false));
+        }
+        #[ink::test]
+        fn payout_only_splits_unencumbered_balance() {
+            // Arrange - Charlie's share is parked as a pending claim and a reserve is kept
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            reject_transfers_to(accounts.charlie);
+            contract.trigger_payout().unwrap();
+            accept_transfers_to(accounts.charlie);
+            assert_eq!(contract.set_reserve(1000000), Ok(()));
+            set_account_balance::<DefaultEnvironment>(contract_id, 4000000);
+            let bob_balance = get_balance(accounts.bob);
+            let charlie_balance = get_balance(accounts.charlie);
+
+            // Assert - only the free portion is distributable
+            assert_eq!(contract.encumbered(), 2000000);
+            assert_eq!(contract.distributable(), 2000000);
+
+            // Act
+            assert!(contract.trigger_payout().is_ok());
+
+            // Assert - the payout left the claim and the reserve untouched
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 1000000);
+            assert_eq!(get_balance(contract_id), 2000000);
+            assert_eq!(contract.distributable(), 0);
+            assert_eq!(contract.trigger_payout(), Err(Error::NothingDistributable));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.claim(), Ok(1000000));
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 2000000);
+            assert_eq!(get_balance(contract_id), 1000000);
+        }
+        #[ink::test]
+        fn deposits_below_minimum_are_rejected() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            assert_eq!(contract.min_deposit(), 1);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.set_min_deposit(100), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_min_deposit(100), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.django);
+
+            // Act
+            set_value_transferred::<DefaultEnvironment>(100);
+            let at_minimum = contract.deposit();
+            set_value_transferred::<DefaultEnvironment>(99);
+            let below_minimum = contract.deposit();
+            let below_minimum_for = contract.deposit_for(accounts.eve);
+            set_value_transferred::<DefaultEnvironment>(0);
+            let empty = contract.deposit();
+
+            // Assert
+            assert_eq!(at_minimum, Ok(()));
+            assert_eq!(below_minimum, Err(Error::BelowMinimumDeposit));
+            assert_eq!(below_minimum_for, Err(Error::BelowMinimumDeposit));
+            assert_eq!(empty, Err(Error::ZeroShare));
+            assert_eq!(contract.total_received(), 100);
+            assert_eq!(contract.min_deposit(), 100);
+        }
+        #[ink::test]
+        fn deposit_exact_refunds_surplus() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 10000000);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            let django_balance = get_balance(accounts.django);
+
+            // Act - exact, over and under payment
+            set_value_transferred::<DefaultEnvironment>(1000);
+            let exact = contract.deposit_exact(1000);
+            set_value_transferred::<DefaultEnvironment>(1500);
+            let over = contract.deposit_exact(1000);
+            set_value_transferred::<DefaultEnvironment>(999);
+            let under = contract.deposit_exact(1000);
+
+            // Assert - only the expected amounts are recorded and the surplus went back
+            assert_eq!(exact, Ok(()));
+            assert_eq!(over, Ok(()));
+            assert_eq!(under, Err(Error::InsufficientDeposit));
+            assert_eq!(contract.deposited_by(accounts.django), 2000);
+            assert_eq!(contract.total_received(), 2000);
+            assert_eq!(get_balance(accounts.django), django_balance + 500);
+            assert_eq!(get_balance(contract_id), 10000000 - 500);
+
+            use ink::env::Event;
+            let deposited: Vec<Balance> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == Deposit::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <Deposit as scale::Decode>::decode(&mut &event.data[..]).unwrap().value)
+                .collect();
+            assert_eq!(deposited, vec![1000, 1000]);
+        }
+        #[ink::test]
+        fn deposits_of_pages_through_receipts() {
+            // Arrange - Django makes four deposits in consecutive blocks
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            let first_block = contract.env().block_number();
+            for amount in [100, 200, 300, 400] {
+                set_value_transferred::<DefaultEnvironment>(amount);
+                contract.deposit().unwrap();
+                advance_block::<DefaultEnvironment>();
+            }
+
+            // Act
+            let first_page = contract.deposits_of(accounts.django, 0, 2);
+            let second_page = contract.deposits_of(accounts.django, 2, 2);
+            let past_end = contract.deposits_of(accounts.django, 4, 2);
+
+            // Assert
+            let receipt = |deposit_id: u32, amount: Balance| Receipt {
+                deposit_id,
+                block: first_block + deposit_id,
+                amount,
+            };
+            assert_eq!(contract.deposit_count(accounts.django), 4);
+            assert_eq!(first_page, vec![receipt(0, 100), receipt(1, 200)]);
+            assert_eq!(second_page, vec![receipt(2, 300), receipt(3, 400)]);
+            assert_eq!(past_end, vec![]);
+            assert_eq!(contract.deposits_of(accounts.eve, 0, 2), vec![]);
+            assert_eq!(contract.deposits_of(accounts.django, 0, u32::MAX).len(), 4);
+        }
+        #[ink::test]
+        fn release_to_pays_one_payee_early_without_overpaying() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            let bob_balance = get_balance(accounts.bob);
+            let charlie_balance = get_balance(accounts.charlie);
+            let django_balance = get_balance(accounts.django);
+
+            // Act - Bob gets his cut early
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.release_to(accounts.bob), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.release_to(accounts.eve), Err(Error::PayeeNotFound));
+            assert_eq!(contract.release_to(accounts.bob), Ok(1000000));
+            assert_eq!(contract.release_to(accounts.bob), Err(Error::ZeroShare));
+
+            // Assert - the other shares are unchanged
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(contract.payee_share(accounts.bob), Ok(0));
+            assert_eq!(contract.payee_share(accounts.charlie), Ok(1000000));
+
+            // Act - the full round pays everyone else their share of the same funds
+            assert!(contract.trigger_payout().is_ok());
+
+            // Assert
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 1000000);
+            assert_eq!(get_balance(accounts.django), django_balance + 1000000);
+            assert_eq!(get_balance(contract_id), 0);
+            assert_eq!(contract.total_distributed(), 3000000);
+
+            // Act - the next round splits equally again
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            assert!(contract.trigger_payout().is_ok());
+
+            // Assert
+            assert_eq!(get_balance(accounts.bob), bob_balance + 2000000);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 2000000);
+        }
+        #[ink::test]
+        fn frozen_payee_accrues_and_catches_up() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            let bob_balance = get_balance(accounts.bob);
+            let charlie_balance = get_balance(accounts.charlie);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.freeze_payee(accounts.charlie), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.freeze_payee(accounts.eve), Err(Error::PayeeNotFound));
+            assert_eq!(contract.freeze_payee(accounts.charlie), Ok(()));
+
+            // Act - Charlie's share accrues instead of being transferred
+            let report = contract.trigger_payout_lossy().unwrap();
+
+            // Assert
+            assert_eq!(report[1], (accounts.charlie, Err(Error::PayeeFrozen)));
+            assert!(contract.frozen(accounts.charlie));
+            assert_eq!(contract.accrued(accounts.charlie), 1000000);
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance);
+            assert_eq!(contract.distributable(), 0);
+            assert_eq!(contract.release_to(accounts.charlie), Err(Error::PayeeFrozen));
+
+            // Act - the next payout does not split the accrual again
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            contract.trigger_payout().unwrap();
+            assert_eq!(contract.accrued(accounts.charlie), 2000000);
+            assert_eq!(get_balance(accounts.bob), bob_balance + 2000000);
+
+            // Act - unfreezing pays the catch-up
+            assert_eq!(contract.unfreeze_payee(accounts.charlie), Ok(()));
+
+            // Assert
+            assert!(!contract.frozen(accounts.charlie));
+            assert_eq!(contract.accrued(accounts.charlie), 0);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 2000000);
+            assert_eq!(get_balance(contract_id), 0);
+            assert_eq!(contract.unfreeze_payee(accounts.charlie), Err(Error::PayeeNotFound));
+
+            use ink::env::Event;
+            let frozen_events = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == PayeeFrozen::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .count();
+            let released: Vec<Balance> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == PayeeUnfrozen::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <PayeeUnfrozen as scale::Decode>::decode(&mut &event.data[..]).unwrap().released)
+                .collect();
+            assert_eq!(frozen_events, 1);
+            assert_eq!(released, vec![2000000]);
+        }
+        #[ink::test]
+        fn new_funded_records_initial_deposit() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(5000000);
+
+            // Act
+            let contract = PaymentSplitter::new_funded(vec![accounts.bob, accounts.charlie], accounts.alice);
+
+            // Assert
+            let recorded_deposits = || -> Vec<(AccountId, Balance)> {
+                use ink::env::Event;
+                ink::env::test::recorded_events()
+                    .filter(|event| Some(event.topics[0].as_slice()) == Deposit::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                    .map(|event| <Deposit as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                    .map(|deposit| (deposit.from, deposit.value))
+                    .collect()
+            };
+            assert_eq!(recorded_deposits(), vec![(accounts.django, 5000000)]);
+            assert_eq!(contract.total_received(), 5000000);
+            assert_eq!(contract.deposited_by(accounts.django), 5000000);
+            assert_eq!(contract.deposit_count(accounts.django), 1);
+
+            // Act - without value nothing is recorded
+            set_value_transferred::<DefaultEnvironment>(0);
+            let contract = PaymentSplitter::new_funded(vec![accounts.bob], accounts.alice);
+
+            // Assert
+            assert_eq!(contract.total_received(), 0);
+            assert_eq!(recorded_deposits().len(), 1);
+        }
+        #[ink::test]
+        fn payout_rejects_balance_drop_after_calculation() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let bob_balance = get_balance(accounts.bob);
+            let snapshot = contract.distributable();
+            let payout_info = contract.payouts_from(snapshot).unwrap();
+
+            // Act - the balance drops between the calculation and the first transfer
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            let result = contract.ensure_snapshot_covered(snapshot, &payout_info);
+
+            // Assert
+            assert_eq!(result, Err(Error::BalanceChanged));
+
+            // Act - a balance increase in between is harmless
+            let snapshot = contract.distributable();
+            let payout_info = contract.payouts_from(snapshot).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2500000);
+            assert_eq!(contract.ensure_snapshot_covered(snapshot, &payout_info), Ok(()));
+            assert!(contract.pay_out_snapshot(snapshot).is_ok());
+
+            // Assert - the payout used the snapshot and left the new funds for later
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(contract_id), 500000);
+        }
+        #[ink::test]
+        fn payment_hook_gas_limit_is_configurable() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            assert_eq!(contract.payment_hook_gas_limit(), PAYMENT_HOOK_GAS_LIMIT);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let unauthorized = contract.set_payment_hook_gas_limit(1_000_000_000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let unlimited = contract.set_payment_hook_gas_limit(0);
+            let updated = contract.set_payment_hook_gas_limit(1_000_000_000);
+
+            // Assert
+            assert_eq!(unauthorized, Err(Error::Unauthorized));
+            assert_eq!(unlimited, Err(Error::ZeroShare));
+            assert_eq!(updated, Ok(()));
+            assert_eq!(contract.payment_hook_gas_limit(), 1_000_000_000);
+        }
+        #[ink::test]
+        fn renounced_designation_makes_payouts_permissionless() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.renounce_designation(), Err(Error::Unauthorized));
+            assert_eq!(contract.trigger_payout(), Err(Error::Unauthorized));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.renounce_designation(), Ok(()));
+
+            // Assert - a random account can pay out
+            assert!(contract.is_permissionless());
+            let bob_balance = get_balance(accounts.bob);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert!(contract.trigger_payout().is_ok());
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+
+            // Assert - the configuration is frozen, even for the former admin
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_admin(accounts.alice), Err(Error::Unauthorized));
+            assert_eq!(contract.set_operator(Some(accounts.alice)), Err(Error::Unauthorized));
+            assert_eq!(contract.set_max_deposit(Some(1)), Err(Error::Unauthorized));
+            assert_eq!(contract.add_payees(vec![(accounts.django, 1)]), Err(Error::Unauthorized));
+            assert_eq!(contract.renounce_designation(), Err(Error::Unauthorized));
+            assert!(contract.is_permissionless());
+        }
+        // Distinct accounts for the distribution tests.
+        fn distribution_payees(count: u8) -> Vec<AccountId> {
+            (0..count).map(|i| AccountId::from([i.wrapping_add(1); 32])).collect()
+        }
+
+        // Balances around the edges for `count` payees: 0, 1, u128::MAX and n*k-1, n*k, n*k+1.
+        fn edge_balances(count: u8) -> Vec<Balance> {
+            let n = count as Balance;
+            let mut balances = vec![0, 1, Balance::MAX];
+            for k in [1, 7, 1_000_000, Balance::MAX / n] {
+                let nk = n * k;
+                balances.extend([nk - 1, nk]);
+                balances.extend(nk.checked_add(1));
+            }
+            balances
+        }
+
+        #[test]
+        fn compute_distribution_splits_every_unit() {
+            for count in [1, 2, 97] {
+                let payees = distribution_payees(count);
+                for total in edge_balances(count) {
+                    // Act
+                    let result = compute_distribution(total, &payees, None);
+
+                    // Assert - all or nothing: either everybody gets something and the amounts
+                    // add up to the total, or the total is too small to split
+                    if total < count as Balance {
+                        assert_eq!(result, Err(Error::ZeroShare), "total {} among {}", total, count);
+                        continue;
+                    }
+                    let payout_info = result.unwrap();
+                    let sum = payout_info.iter().fold(0, |sum: Balance, info| sum + info.amount);
+                    assert_eq!(sum, total, "total {} among {}", total, count);
+                    assert_eq!(payout_info.len(), count as usize);
+                    assert!(payout_info.iter().all(|info| info.amount > 0));
+                    assert!(payout_info.iter().zip(payees.iter()).all(|(info, payee)| info.payee == *payee));
+                }
+            }
+            assert_eq!(compute_distribution(1000, &[], None), Err(Error::NoPayees));
+        }
+
+        #[test]
+        fn compute_distribution_pays_remainder_to_recipient() {
+            let recipient = AccountId::from([0xEE; 32]);
+            for count in [1, 2, 97] {
+                let payees = distribution_payees(count);
+                for total in edge_balances(count).into_iter().filter(|total| *total >= count as Balance) {
+                    // Act
+                    let payout_info = compute_distribution(total, &payees, Some(recipient)).unwrap();
+
+                    // Assert - equal shares, and the remainder as a separate entry if any
+                    let share = total / count as Balance;
+                    let remainder = total % count as Balance;
+                    let sum = payout_info.iter().fold(0, |sum: Balance, info| sum + info.amount);
+                    assert_eq!(sum, total);
+                    assert!(payout_info[..count as usize].iter().all(|info| info.amount == share));
+                    match remainder {
+                        0 => assert_eq!(payout_info.len(), count as usize),
+                        _ => assert_eq!(payout_info.last(), Some(&PayoutInfo { payee: recipient, amount: remainder })),
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn compute_tiered_distribution_matches_equal_split_without_fixed_amounts() {
+            for count in [1, 2, 97] {
+                let payees = distribution_payees(count);
+                let tiers: Vec<(AccountId, Option<Balance>, u128)> = payees.iter().map(|payee| (*payee, None, 1)).collect();
+                for total in edge_balances(count) {
+                    assert_eq!(
+                        compute_tiered_distribution(total, &tiers, None),
+                        compute_distribution(total, &payees, None)
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn compute_tiered_distribution_never_exceeds_total() {
+            for count in [2, 97] {
+                let payees = distribution_payees(count);
+                // The first payee is tier 1 with a fixed amount, the rest are tier 2.
+                for fixed in [1, 1_000_000, u64::MAX as Balance] {
+                    let tiers: Vec<(AccountId, Option<Balance>, u128)> = payees
+                        .iter()
+                        .enumerate()
+                        .map(|(i, payee)| (*payee, if i == 0 { Some(fixed) } else { None }, 1))
+                        .collect();
+                    for total in edge_balances(count).into_iter().filter(|total| *total > 0) {
+                        // Act
+                        let payout_info = compute_tiered_distribution(total, &tiers, None).unwrap();
+
+                        // Assert - the fixed amount comes first, only dust too small for the
+                        // tier-2 payees stays undistributed
+                        let sum = payout_info.iter().fold(0, |sum: Balance, info| sum + info.amount);
+                        let tier_two = count as Balance - 1;
+                        assert!(sum <= total);
+                        assert!(total - sum < tier_two, "total {} fixed {} among {}", total, fixed, count);
+                        assert_eq!(payout_info[0].amount, fixed.min(total));
+                        assert!(payout_info.iter().all(|info| info.amount > 0));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn compute_weighted_distribution_splits_every_unit() {
+            for count in [2, 97] {
+                let payees = distribution_payees(count);
+                // Weights 1, 2, 3, ... so no two payees weigh the same.
+                let weights: Vec<(AccountId, u128)> = payees
+                    .iter()
+                    .enumerate()
+                    .map(|(i, payee)| (*payee, i as u128 + 1))
+                    .collect();
+                let total_weight = count as u128 * (count as u128 + 1) / 2;
+                for total in edge_balances(count).into_iter().filter(|total| *total >= total_weight) {
+                    // Act
+                    let payout_info = compute_weighted_distribution(total, &weights, None).unwrap();
+
+                    // Assert - pro-rata shares adding up to the total, the remainder on the
+                    // first payee
+                    let sum = payout_info.iter().fold(0, |sum: Balance, info| sum + info.amount);
+                    assert_eq!(sum, total, "total {} among {}", total, count);
+                    assert_eq!(payout_info.len(), count as usize);
+                    for (info, (payee, weight)) in payout_info.iter().zip(weights.iter()).skip(1) {
+                        assert_eq!(info.payee, *payee);
+                        assert_eq!(info.amount, (total / total_weight) * weight + (total % total_weight) * weight / total_weight);
+                    }
+                }
+            }
+            assert_eq!(compute_weighted_distribution(1000, &[], None), Err(Error::NoPayees));
+        }
+
+        #[test]
+        fn compute_weighted_distribution_edge_weights() {
+            let payees = distribution_payees(3);
+            let recipient = AccountId::from([0xEE; 32]);
+
+            // Equal weights fall back to the equal split
+            let equal: Vec<(AccountId, u128)> = payees.iter().map(|payee| (*payee, 5)).collect();
+            assert_eq!(compute_weighted_distribution(1001, &equal, None), compute_distribution(1001, &payees, None));
+
+            // Weight 0 receives nothing, all weights 0 is an error
+            let weights = vec![(payees[0], 3), (payees[1], 0), (payees[2], 1)];
+            assert_eq!(
+                compute_weighted_distribution(1001, &weights, Some(recipient)),
+                Ok(vec![
+                    PayoutInfo { payee: payees[0], amount: 750 },
+                    PayoutInfo { payee: payees[2], amount: 250 },
+                    PayoutInfo { payee: recipient, amount: 1 },
+                ])
+            );
+            let zero: Vec<(AccountId, u128)> = payees.iter().map(|payee| (*payee, 0)).collect();
+            assert_eq!(compute_weighted_distribution(1000, &zero, None), Err(Error::ZeroShare));
+
+            // Too small to give the lightest payee something
+            assert_eq!(compute_weighted_distribution(3, &weights, None), Err(Error::ZeroShare));
+
+            // Huge weights do not overflow on the whole-quotient part
+            let heavy = vec![(payees[0], u64::MAX as u128), (payees[1], 1)];
+            let payout_info = compute_weighted_distribution(Balance::MAX, &heavy, None).unwrap();
+            assert_eq!(payout_info.iter().fold(0, |sum: Balance, info| sum + info.amount), Balance::MAX);
+        }
+
+        #[test]
+        fn compute_contribution_distribution_splits_every_unit() {
+            for count in [1, 2, 97] {
+                let payees = distribution_payees(count);
+                let contributions: Vec<(AccountId, Balance)> = payees
+                    .iter()
+                    .enumerate()
+                    .map(|(i, payee)| (*payee, i as Balance + 1))
+                    .collect();
+                for total in edge_balances(count).into_iter().filter(|total| *total > 0 && *total <= u64::MAX as Balance) {
+                    // Act
+                    let payout_info = compute_contribution_distribution(total, &contributions, None).unwrap();
+
+                    // Assert
+                    let sum = payout_info.iter().fold(0, |sum: Balance, info| sum + info.amount);
+                    assert_eq!(sum, total, "total {} among {}", total, count);
+                    assert!(payout_info.iter().all(|info| info.amount > 0));
+                }
+                assert_eq!(compute_contribution_distribution(0, &contributions, None), Err(Error::ZeroShare));
+            }
+        }
+
+        #[test]
+        fn compute_owed_distribution_pays_in_order() {
+            let payees = distribution_payees(3);
+            let owed = vec![(payees[0], 5), (payees[1], 0), (payees[2], 10)];
+
+            assert_eq!(
+                compute_owed_distribution(8, &owed),
+                Ok(vec![PayoutInfo { payee: payees[0], amount: 5 }, PayoutInfo { payee: payees[2], amount: 3 }])
+            );
+            assert_eq!(
+                compute_owed_distribution(Balance::MAX, &owed),
+                Ok(vec![PayoutInfo { payee: payees[0], amount: 5 }, PayoutInfo { payee: payees[2], amount: 10 }])
+            );
+            assert_eq!(compute_owed_distribution(0, &owed), Err(Error::ZeroShare));
+            assert_eq!(compute_owed_distribution(8, &[(payees[0], 0)]), Err(Error::NothingOwed));
+            assert_eq!(compute_owed_distribution(8, &[]), Err(Error::NoPayees));
+        }
+        #[ink::test]
+        fn reentrancy_guard_is_always_released() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+
+            // Act
+            let nested = contract.with_reentrancy_guard(|contract| contract.with_reentrancy_guard(|_| Ok(())));
+            let nested_locked = contract.locked;
+            let failing: Result<(), Error> = contract.with_reentrancy_guard(|_| Err(Error::TransferFailed));
+            let failing_locked = contract.locked;
+            let inside = contract.with_reentrancy_guard(|contract| Ok(contract.locked));
+
+            // Assert
+            assert_eq!(nested, Err(Error::ReentrancyGuardLocked));
+            assert!(!nested_locked);
+            assert_eq!(failing, Err(Error::TransferFailed));
+            assert!(!failing_locked);
+            assert_eq!(inside, Ok(true));
+            assert!(!contract.locked);
+        }
+
+        #[ink::test]
+        fn failed_pool_transfer_is_parked() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let pool_id = contract.create_pool(vec![accounts.charlie, accounts.django]).unwrap();
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            contract.deposit_to_pool(pool_id).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            let django_balance = get_balance(accounts.django);
+            reject_transfers_to(accounts.charlie);
+
+            // Act
+            let result = contract.trigger_pool_payout(pool_id);
+            accept_transfers_to(accounts.charlie);
+
+            // Assert - Django is still paid and Charlie's share waits as a pending claim
+            assert_eq!(result, Ok(()));
+            assert!(!contract.locked);
+            assert_eq!(get_balance(accounts.django), django_balance + 500000);
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 500000);
+            assert_eq!(contract.pool_balance(pool_id), Ok(0));
+            assert_eq!(contract.distributable(), 2000000);
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.claim(), Ok(500000));
+        }
+        #[ink::test]
+        fn preview_for_amount_matches_calculate_payout() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(
+                vec![accounts.bob, accounts.charlie, accounts.django],
+                accounts.alice
+            );
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let assert_preview_matches = |contract: &mut PaymentSplitter| {
+                let balance = get_balance(contract_id);
+                assert_eq!(contract.preview_for_amount(balance), contract.calculate_payout());
+            };
+
+            // Act & Assert - equal split with a remainder
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000002);
+            assert_preview_matches(&mut contract);
+            assert_eq!(contract.preview_for_amount(3000002).unwrap()[0].amount, 1000002);
+
+            // Act & Assert - remainder recipient
+            contract.set_remainder_recipient(Some(accounts.eve)).unwrap();
+            assert_preview_matches(&mut contract);
+
+            // Act & Assert - tier-1 payee and a reserve
+            contract.set_payee_tier(accounts.bob, PayeeTier::Fixed(1500000)).unwrap();
+            contract.set_reserve(1000000).unwrap();
+            assert_preview_matches(&mut contract);
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            assert_preview_matches(&mut contract);
+
+            // Act & Assert - a hypothetical balance, independent of the real one
+            assert_eq!(
+                contract.preview_for_amount(5000000),
+                Ok(vec![
+                    PayoutInfo { payee: accounts.bob, amount: 1500000 },
+                    PayoutInfo { payee: accounts.charlie, amount: 1250000 },
+                    PayoutInfo { payee: accounts.django, amount: 1250000 },
+                ])
+            );
+            assert_eq!(contract.preview_for_amount(1000000), Err(Error::NothingDistributable));
+        }
+        #[ink::test]
+        fn split_tracked_only_leaves_preexisting_balance() {
+            // Arrange - the contract starts with an endowment that must not be split
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { split_tracked_only: true, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(
+                vec![accounts.bob, accounts.charlie],
+                accounts.alice,
+                config
+            );
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 5000000);
+            assert_eq!(contract.distributable(), 0);
+            let bob_balance = get_balance(accounts.bob);
+
+            // Act - deposit a known amount and pay out
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(2000000);
+            contract.deposit().unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 7000000);
+            assert_eq!(contract.accrued_since_last_payout(), 2000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert!(contract.trigger_payout().is_ok());
+
+            // Assert - only the deposit was distributed
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(contract_id), 5000000);
+            assert_eq!(contract.accrued_since_last_payout(), 0);
+            assert_eq!(contract.trigger_payout(), Err(Error::NothingDistributable));
+        }
+        #[ink::test]
+        fn set_payees_replaces_the_list_atomically() {
+            // Arrange - Bob has a tier and an owed amount
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.set_payee_tier(accounts.bob, PayeeTier::Fixed(500)).unwrap();
+            contract.set_owed(accounts.bob, 700).unwrap();
+
+            // Act - invalid lists are rejected and leave the old list intact
+            let duplicate = contract.set_payees(vec![(accounts.django, 1), (accounts.eve, 1), (accounts.django, 1)]);
+            let empty = contract.set_payees(vec![]);
+            let zero_account = contract.set_payees(vec![(accounts.django, 1), (AccountId::from([0; 32]), 1)]);
+            let zero_share = contract.set_payees(vec![(accounts.django, 1), (accounts.eve, 0)]);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let unauthorized = contract.set_payees(vec![(accounts.bob, 1)]);
+
+            // Assert
+            assert_eq!(duplicate, Err(Error::DuplicatePayee));
+            assert_eq!(empty, Err(Error::NoPayees));
+            assert_eq!(zero_account, Err(Error::InvalidAccount));
+            assert_eq!(zero_share, Err(Error::ZeroShare));
+            assert_eq!(unauthorized, Err(Error::Unauthorized));
+            assert_eq!(contract.get_payees(), vec![accounts.bob, accounts.charlie]);
+
+            // Act - swap two payees for three, Charlie now weighing 2, and pay out in the same
+            // block
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                contract.set_payees(vec![(accounts.charlie, 2), (accounts.django, 1), (accounts.eve, 1)]),
+                Ok(())
+            );
+            set_account_balance::<DefaultEnvironment>(contract_id, 4000000);
+            let bob_balance = get_balance(accounts.bob);
+            let charlie_balance = get_balance(accounts.charlie);
+            let eve_balance = get_balance(accounts.eve);
+            assert!(contract.trigger_payout().is_ok());
+
+            // Assert - the weights are the given ones
+            assert_eq!(contract.get_payees(), vec![accounts.charlie, accounts.django, accounts.eve]);
+            assert_eq!(contract.total_shares(), 4);
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 2000000);
+            assert_eq!(get_balance(accounts.eve), eve_balance + 1000000);
+            assert_eq!(get_balance(contract_id), 0);
+
+            // Assert - Bob left like a removed payee, so the tier and owed amount do not come
+            // back when Bob is added again
+            contract.add_payee(accounts.bob, 1).unwrap();
+            assert_eq!(contract.payee_tier(accounts.bob), PayeeTier::Residual);
+            assert_eq!(contract.owed(accounts.bob), 0);
+
+            use ink::env::Event;
+            let replaced: Vec<(u32, u32)> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == PayeesReplaced::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <PayeesReplaced as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .map(|event| (event.old_count, event.new_count))
+                .collect();
+            let removed = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == PayeeRemoved::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .count();
+            assert_eq!(replaced, vec![(2, 3)]);
+            assert_eq!(removed, 1);
+        }
+
+        #[ink::test]
+        fn payee_majority_replaces_designated_payee() {
+            use ink::env::Event;
+
+            // Arrange - three payees, so two votes are a majority
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+
+            // Assert - only payees may propose
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.propose_new_designated_payee(accounts.eve), Err(Error::Unauthorized));
+
+            // Act - Bob proposes Eve, which counts as his vote
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract.propose_new_designated_payee(accounts.eve).unwrap();
+
+            // Assert - one vote is not a majority yet
+            assert_eq!(contract.admin(), accounts.alice);
+            assert_eq!(contract.designated_payee_proposal(proposal_id).map(|proposal| proposal.votes), Some(1));
+
+            // Act - Charlie's vote completes the majority
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            contract.vote_for_designated_payee(proposal_id).unwrap();
+
+            // Assert - Eve took over and the proposal is closed
+            assert_eq!(contract.admin(), accounts.eve);
+            assert_eq!(contract.designated_payee_proposal(proposal_id), None);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.vote_for_designated_payee(proposal_id), Err(Error::ProposalNotFound));
+            let changes: Vec<DesignatedPayeeChanged> = ink::env::test::recorded_events()
+                .filter(|event| {
+                    Some(event.topics[0].as_slice()) ==
+                        DesignatedPayeeChanged::SIGNATURE_TOPIC.as_ref().map(|t| &t[..])
+                })
+                .map(|event| <DesignatedPayeeChanged as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].old, accounts.alice);
+            assert_eq!(changes[0].new, accounts.eve);
+        }
+
+        #[ink::test]
+        fn designated_payee_proposal_expires() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let config = SplitterConfig { recovery_period: 2, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract.propose_new_designated_payee(accounts.eve).unwrap();
+
+            // Act - let the recovery period pass
+            advance_block::<DefaultEnvironment>();
+            advance_block::<DefaultEnvironment>();
+            advance_block::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+
+            // Assert
+            assert_eq!(contract.vote_for_designated_payee(proposal_id), Err(Error::ProposalExpired));
+            assert_eq!(contract.admin(), accounts.alice);
+        }
+
+        #[ink::test]
+        fn designated_payee_votes_count_once() {
+            // Arrange - four payees, so a majority needs three votes
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django, accounts.frank];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract.propose_new_designated_payee(accounts.eve).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            contract.vote_for_designated_payee(proposal_id).unwrap();
+
+            // Act - both voters vote again
+            assert_eq!(contract.vote_for_designated_payee(proposal_id), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.vote_for_designated_payee(proposal_id), Ok(()));
+
+            // Assert - still two votes, no majority
+            assert_eq!(contract.designated_payee_proposal(proposal_id).map(|proposal| proposal.votes), Some(2));
+            assert_eq!(contract.admin(), accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.vote_for_designated_payee(proposal_id), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn designated_payee_votes_are_weighted_by_shares() {
+            // Arrange - Bob weighs 3 of 5, Charlie and Django 1 each
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let shares = vec![(accounts.bob, 3), (accounts.charlie, 1), (accounts.django, 1)];
+            let mut contract = PaymentSplitter::new_weighted(shares, accounts.alice);
+
+            // Act - two of three payees, but only 2 of 5 shares
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            let proposal_id = contract.propose_new_designated_payee(accounts.eve).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.django);
+            contract.vote_for_designated_payee(proposal_id).unwrap();
+
+            // Assert
+            assert_eq!(contract.designated_payee_proposal(proposal_id).map(|proposal| proposal.votes), Some(2));
+            assert_eq!(contract.admin(), accounts.alice);
+
+            // Act - Bob's shares complete the majority
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            contract.vote_for_designated_payee(proposal_id).unwrap();
+
+            // Assert
+            assert_eq!(contract.admin(), accounts.eve);
+        }
+
+        #[ink::test]
+        fn designated_payee_votes_need_several_current_payees() {
+            // Arrange - a single payee cannot take over on its own
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            contract.propose_new_designated_payee(accounts.bob).unwrap();
+            assert_eq!(contract.admin(), accounts.alice);
+
+            // Arrange - a payee votes, then is removed
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract.propose_new_designated_payee(accounts.eve).unwrap();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.remove_payee(accounts.bob).unwrap();
+            contract.add_payees(vec![(accounts.bob, 1)]).unwrap();
+
+            // Act - Charlie votes; Bob's earlier vote no longer counts
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            contract.vote_for_designated_payee(proposal_id).unwrap();
+
+            // Assert
+            assert_eq!(contract.designated_payee_proposal(proposal_id).map(|proposal| proposal.votes), Some(1));
+            assert_eq!(contract.admin(), accounts.alice);
+        }
+
+        #[ink::test]
+        fn contributors_cannot_vote_for_designated_payee() {
+            // Arrange - two payees, outnumbered by depositors in contributions mode
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let config = SplitterConfig { split_mode: SplitMode::Contributions, ..Default::default() };
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            let sybils: Vec<AccountId> = (0..5u8).map(|i| AccountId::from([i + 200; 32])).collect();
+            for sybil in sybils.iter() {
+                set_account_balance::<DefaultEnvironment>(*sybil, 1000000);
+                set_caller::<DefaultEnvironment>(*sybil);
+                contract.deposit().unwrap();
+            }
+            assert_eq!(contract.get_payees().len(), 7);
+
+            // Act & Assert - the depositors can neither propose nor vote
+            set_caller::<DefaultEnvironment>(sybils[0]);
+            assert_eq!(contract.propose_new_designated_payee(sybils[0]), Err(Error::Unauthorized));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract.propose_new_designated_payee(accounts.bob).unwrap();
+            for sybil in sybils.iter() {
+                set_caller::<DefaultEnvironment>(*sybil);
+                assert_eq!(contract.vote_for_designated_payee(proposal_id), Err(Error::Unauthorized));
+            }
+            assert_eq!(contract.admin(), accounts.alice);
+        }
+
+        #[ink::test]
+        fn weighted_payees_are_paid_pro_rata() {
+            // Arrange - Bob weighs 3, Charlie 1, and Bob is listed twice
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let shares = vec![(accounts.bob, 2), (accounts.charlie, 1), (accounts.bob, 1)];
+            let mut contract = PaymentSplitter::new_weighted(shares, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 4000001);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Assert - duplicates are merged by summing their weights
+            assert_eq!(contract.get_payees(), vec![accounts.bob, accounts.charlie]);
+            assert_eq!(contract.get_payees_with_shares(), vec![(accounts.bob, 3), (accounts.charlie, 1)]);
+            assert_eq!(contract.shares_of(accounts.bob), 3);
+            assert_eq!(contract.shares_of(accounts.charlie), 1);
+            assert_eq!(contract.shares_of(accounts.eve), 0);
+            assert_eq!(contract.total_shares(), 4);
+
+            // Act
+            let payout_info = contract.calculate_payout();
+
+            // Assert - three quarters for Bob plus the remainder, a quarter for Charlie
+            assert_eq!(
+                payout_info,
+                Ok(vec![
+                    PayoutInfo { payee: accounts.bob, amount: 3000001 },
+                    PayoutInfo { payee: accounts.charlie, amount: 1000000 },
+                ])
+            );
+
+            // Act - payees added later weigh 1
+            contract.add_payees(vec![(accounts.django, 1)]).unwrap();
+
+            // Assert
+            assert_eq!(contract.shares_of(accounts.django), 1);
+            assert_eq!(contract.total_shares(), 5);
+        }
+
+        #[ink::test]
+        fn basis_point_shares_must_sum_to_100_percent() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+
+            // Assert - 25% + 70% is rejected, and so is more than 100%
+            assert!(matches!(
+                PaymentSplitter::new_basis_points(vec![(accounts.bob, 2500), (accounts.charlie, 7000)], accounts.alice),
+                Err(Error::SharesMismatch)
+            ));
+            assert!(matches!(
+                PaymentSplitter::new_basis_points(vec![(accounts.bob, 2500), (accounts.charlie, 7600)], accounts.alice),
+                Err(Error::SharesMismatch)
+            ));
+
+            // Act - 25% + 75%
+            let mut contract = PaymentSplitter::new_basis_points(
+                vec![(accounts.bob, 2500), (accounts.charlie, 7500)],
+                accounts.alice
+            ).unwrap();
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 4000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Assert
+            assert_eq!(contract.basis_points_of(accounts.bob), 2500);
+            assert_eq!(contract.basis_points_of(accounts.charlie), 7500);
+            assert_eq!(contract.basis_points_of(accounts.eve), 0);
+            assert_eq!(
+                contract.calculate_payout(),
+                Ok(vec![
+                    PayoutInfo { payee: accounts.bob, amount: 1000000 },
+                    PayoutInfo { payee: accounts.charlie, amount: 3000000 },
+                ])
+            );
+        }
+
+        #[ink::test]
+        fn update_share_applies_from_the_next_round() {
+            use ink::env::Event;
+
+            // Arrange - equal weights, and a plan committed before the update
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let plan = contract.commit_payout().unwrap();
+
+            // Act - Charlie now weighs 3
+            assert_eq!(contract.update_share(accounts.charlie, 3), Ok(()));
+
+            // Assert - the committed plan still pays the old ratio
+            assert_eq!(
+                contract.execute_payout(plan),
+                Ok(vec![(accounts.bob, Ok(1000000)), (accounts.charlie, Ok(1000000))])
+            );
+
+            // Act - the second round uses the new ratio
+            set_account_balance::<DefaultEnvironment>(contract_id, 4000000);
+            let second = contract.trigger_payout();
+
+            // Assert
+            assert_eq!(
+                second,
+                Ok(vec![
+                    PayoutInfo { payee: accounts.bob, amount: 1000000 },
+                    PayoutInfo { payee: accounts.charlie, amount: 3000000 },
+                ])
+            );
+            assert_eq!(contract.total_shares(), 4);
+            let updates: Vec<SharesUpdated> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == SharesUpdated::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <SharesUpdated as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(updates.len(), 1);
+            assert_eq!((updates[0].payee, updates[0].old, updates[0].new), (accounts.charlie, 1, 3));
+
+            assert_eq!(contract.update_share(accounts.charlie, 0), Err(Error::ZeroShare));
+            assert_eq!(contract.update_share(accounts.eve, 1), Err(Error::PayeeNotFound));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.update_share(accounts.bob, 5), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn pull_payments_let_payees_withdraw_independently() {
+            // Arrange - Charlie rejects transfers, which must not hold up Bob and Django
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let config = SplitterConfig { pull_payments: true, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            reject_transfers_to(accounts.charlie);
+            let bob_balance = get_balance(accounts.bob);
+            let django_balance = get_balance(accounts.django);
+
+            // Act - anyone can push Bob his share, Django withdraws his own
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            let bob = contract.release(accounts.bob);
+            let charlie = contract.release(accounts.charlie);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            let django = contract.release_self();
+
+            // Assert
+            assert_eq!(bob, Ok(1000000));
+            assert_eq!(charlie, Err(Error::TransferFailed));
+            assert_eq!(django, Ok(1000000));
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(get_balance(accounts.django), django_balance + 1000000);
+            assert_eq!(contract.release_self(), Err(Error::ZeroShare));
+
+            // Act - new funds arrive; Bob is entitled to a third of them only
+            set_account_balance::<DefaultEnvironment>(contract_id, 4000000);
+            assert_eq!(contract.release(accounts.bob), Ok(1000000));
+
+            // Assert - Charlie's whole entitlement is still there once he accepts transfers
+            accept_transfers_to(accounts.charlie);
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.release_self(), Ok(2000000));
+            assert_eq!(contract.release(accounts.eve), Err(Error::PayeeNotFound));
+        }
+
+        #[ink::test]
+        fn releasable_and_released_accounting() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let config = SplitterConfig { pull_payments: true, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+
+            // Assert - nothing released yet
+            assert_eq!(contract.releasable(accounts.bob), 1000000);
+            assert_eq!(contract.released(accounts.bob), 0);
+            assert_eq!(contract.releasable(accounts.eve), 0);
+
+            // Act - Bob withdraws, then a push payout pays Charlie
+            contract.release(accounts.bob).unwrap();
+
+            // Assert
+            assert_eq!(contract.releasable(accounts.bob), 0);
+            assert_eq!(contract.released(accounts.bob), 1000000);
+            assert_eq!(contract.releasable(accounts.charlie), 1000000);
+
+            // Act - the push payout and a parked share count the same way
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            reject_transfers_to(accounts.bob);
+            contract.trigger_payout().unwrap();
+            accept_transfers_to(accounts.bob);
+
+            // Assert - Charlie got his whole 2000000, the rest of Bob's share is parked
+            assert_eq!(contract.released(accounts.charlie), 2000000);
+            assert_eq!(contract.releasable(accounts.bob), 1000000);
+            contract.release(accounts.bob).unwrap();
+            assert_eq!(contract.released(accounts.bob), 2000000);
+            assert_eq!(contract.releasable(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn release_requires_pull_payments() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Assert
+            assert!(!contract.pull_payments());
+            assert_eq!(contract.release(accounts.bob), Err(Error::PullPaymentsDisabled));
+        }
+
+        #[ink::test]
+        fn release_all_reports_each_asset() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees.clone(), accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Assert - only in pull-payment mode
+            assert_eq!(contract.release_all(accounts.bob), Err(Error::PullPaymentsDisabled));
+
+            let config = SplitterConfig { pull_payments: true, ..Default::default() };
+            let mut contract = PaymentSplitter::new_with_config(payees, accounts.alice, config);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Act
+            let results = contract.release_all(accounts.bob).unwrap();
+
+            // Assert
+            assert_eq!(results, vec![AssetPayoutResult { token: None, result: Ok(1000000) }]);
+            assert_eq!(contract.released(accounts.bob), 1000000);
+            assert_eq!(contract.release_all(accounts.eve), Err(Error::PayeeNotFound));
+
+            // A failure on the native balance is reported rather than aborting the call
+            let results = contract.release_all(accounts.bob).unwrap();
+            assert_eq!(results, vec![AssetPayoutResult { token: None, result: Err(Error::ZeroShare) }]);
+        }
+
+        #[ink::test]
+        fn pause_suspends_deposits_and_payouts() {
+            use ink::env::Event;
+
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+
+            // Assert - admin only
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.pause(), Err(Error::Unauthorized));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.pause(), Ok(()));
+
+            // Assert
+            assert!(contract.paused());
+            assert_eq!(contract.pause(), Err(Error::ContractPaused));
+            assert_eq!(contract.trigger_payout(), Err(Error::ContractPaused));
+            assert_eq!(contract.renounce_designation(), Err(Error::ContractPaused));
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            assert_eq!(contract.deposit(), Err(Error::ContractPaused));
+            let paused_events = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == Paused::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .count();
+            assert_eq!(paused_events, 1);
+
+            // Act - Unpause
+            assert_eq!(contract.unpause(), Ok(()));
+
+            // Assert
+            assert!(!contract.paused());
+            assert_eq!(contract.deposit(), Ok(()));
+            assert!(contract.trigger_payout().is_ok());
+        }
+
+        #[ink::test]
+        fn emergency_withdraw_waits_for_the_delay() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            let eve_before = get_balance(accounts.eve);
+            assert_eq!(contract.emergency_delay(), DEFAULT_EMERGENCY_DELAY);
+            assert_eq!(contract.emergency_withdraw(), Err(Error::NoEmergencyWithdrawal));
+
+            // Assert - admin only
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.schedule_emergency_withdraw(accounts.bob), Err(Error::Unauthorized));
+
+            // Act - a short delay, as if configured long ago
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.emergency_delay = 2;
+            let scheduled_at = contract.env().block_number();
+            assert_eq!(contract.schedule_emergency_withdraw(accounts.eve), Ok(()));
+
+            // Assert - not before the delay
+            assert_eq!(
+                contract.emergency_withdrawal(),
+                Some(EmergencyWithdrawal { to: accounts.eve, executable_at: scheduled_at + 2 })
+            );
+            advance_block::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.emergency_withdraw(), Err(Error::TimelockActive));
+
+            // Act - anyone may execute once it elapsed
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.emergency_withdraw(), Ok(2000000));
+
+            // Assert
+            assert_eq!(get_balance(accounts.eve), eve_before + 2000000);
+            assert_eq!(contract.emergency_withdrawal(), None);
+
+            // Act - Cancel
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.schedule_emergency_withdraw(accounts.eve).unwrap();
+            assert_eq!(contract.cancel_emergency_withdraw(), Ok(()));
+            assert_eq!(contract.cancel_emergency_withdraw(), Err(Error::NoEmergencyWithdrawal));
+        }
+
+        #[ink::test]
+        fn emergency_withdraw_leaves_pending_claims() {
+            // Arrange - Bob's share is parked as a pending claim
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            reject_transfers_to(accounts.bob);
+            contract.trigger_payout_lossy().unwrap();
+            accept_transfers_to(accounts.bob);
+            set_account_balance::<DefaultEnvironment>(contract_id, 1500000);
+            let eve_before = get_balance(accounts.eve);
+            contract.emergency_delay = 1;
+            contract.schedule_emergency_withdraw(accounts.eve).unwrap();
+            advance_block::<DefaultEnvironment>();
+
+            // Act
+            let swept = contract.emergency_withdraw();
+
+            // Assert - only the distributable part is swept
+            assert_eq!(swept, Ok(500000));
+            assert_eq!(get_balance(accounts.eve), eve_before + 500000);
+            assert_eq!(contract.pending_claim_of(accounts.bob), 1000000);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim(), Ok(1000000));
+        }
+
+        #[ink::test]
+        fn emergency_delay_has_a_floor_and_reductions_wait() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Assert - the floor
+            assert_eq!(contract.set_emergency_delay(MIN_EMERGENCY_DELAY - 1), Err(Error::DelayTooShort));
+
+            // Act - a longer delay applies at once
+            assert_eq!(contract.set_emergency_delay(DEFAULT_EMERGENCY_DELAY + 1), Ok(()));
+            assert_eq!(contract.emergency_delay(), DEFAULT_EMERGENCY_DELAY + 1);
+
+            // Act - a shorter one waits out the current delay
+            contract.emergency_delay = MIN_EMERGENCY_DELAY + 2;
+            let changed_at = contract.env().block_number();
+            assert_eq!(contract.set_emergency_delay(MIN_EMERGENCY_DELAY), Ok(()));
+
+            // Assert
+            let effective_at = changed_at + MIN_EMERGENCY_DELAY + 2;
+            assert_eq!(contract.emergency_delay(), MIN_EMERGENCY_DELAY + 2);
+            assert_eq!(contract.pending_emergency_delay(), Some((MIN_EMERGENCY_DELAY, effective_at)));
+            contract.schedule_emergency_withdraw(accounts.eve).unwrap();
+            assert_eq!(contract.emergency_withdrawal().unwrap().executable_at, effective_at);
+            for _ in 0..MIN_EMERGENCY_DELAY + 2 {
+                advance_block::<DefaultEnvironment>();
+            }
+            assert_eq!(contract.emergency_delay(), MIN_EMERGENCY_DELAY);
+            assert_eq!(contract.pending_emergency_delay(), None);
+        }
+
+        #[ink::test]
+        fn upgrade_requires_admin() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+
+            // Assert
+            assert_eq!(contract.upgrade(Hash::from([1; 32])), Err(Error::Unauthorized));
+
+            // Once the admin role is renounced, nobody can upgrade
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.renounce_designation().unwrap();
+            assert_eq!(contract.upgrade(Hash::from([1; 32])), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn migrate_runs_only_once() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            assert_eq!(contract.storage_version(), VERSION);
+
+            // Assert - admin only
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.migrate(), Err(Error::Unauthorized));
+
+            // Act - storage written by an older version
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            contract.version.set(&(VERSION - 1));
+            assert_eq!(contract.migrate(), Ok(()));
+
+            // Assert - stored under its own key
+            assert_eq!(contract.storage_version(), VERSION);
+            assert_eq!(
+                ink::env::get_contract_storage::<u32, u32>(&STORAGE_VERSION_KEY),
+                Ok(Some(VERSION))
+            );
+            assert_eq!(contract.migrate(), Err(Error::AlreadyMigrated));
+        }
+
+        #[ink::test]
+        fn terminate_pays_out_then_sweeps_the_residual() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let charlie_before = get_balance(accounts.charlie);
+
+            // Assert - admin only
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.terminate(accounts.eve), Err(Error::Unauthorized));
+
+            // Arrange - a claim of Bob's is parked from an earlier payout
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            reject_transfers_to(accounts.bob);
+            contract.trigger_payout_lossy().unwrap();
+
+            // Assert - refused while the claim cannot be settled
+            assert_eq!(contract.terminate(accounts.eve), Err(Error::FundsEncumbered));
+
+            // Act - the final payout runs and the claim is pushed to Bob before the reserve is
+            // swept
+            accept_transfers_to(accounts.bob);
+            contract.set_reserve(500000).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3500000);
+            let bob_before = get_balance(accounts.bob);
+            let should_terminate = move || contract.terminate(accounts.eve).unwrap();
+
+            // Assert
+            ink::env::test::assert_contract_termination::<DefaultEnvironment, _>(
+                should_terminate,
+                accounts.eve,
+                500000
+            );
+            assert_eq!(get_balance(accounts.bob), bob_before + 2000000);
+            assert_eq!(get_balance(accounts.charlie), charlie_before + 2000000);
+        }
+
+        #[ink::test]
+        fn terminate_refuses_while_token_claims_are_pending() {
+            // Arrange - a token claim of Bob's is parked
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let token = accounts.frank;
+            contract.register_token(token).unwrap();
+            contract.park_token_payment(token, accounts.bob, 300);
+
+            // Act
+            let result = contract.terminate(accounts.eve);
+
+            // Assert - the contract is kept so the claim is not lost
+            assert_eq!(result, Err(Error::TokenUndistributed));
+            assert_eq!(contract.token_pending_claim_of(token, accounts.bob), 300);
+        }
+
+        #[ink::test]
+        fn reserve_defaults_to_the_minimum_balance() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            let contract_id = contract.env().account_id();
+            let minimum_balance = contract.env().minimum_balance();
+            set_account_balance::<DefaultEnvironment>(contract_id, minimum_balance * 3);
+
+            // Act
+            let executed = contract.trigger_payout().unwrap();
+
+            // Assert - the payout leaves the minimum balance in the contract
+            assert_eq!(contract.reserve(), minimum_balance);
+            assert_eq!(
+                executed,
+                vec![
+                    PayoutInfo { payee: accounts.bob, amount: minimum_balance },
+                    PayoutInfo { payee: accounts.charlie, amount: minimum_balance }
+                ]
+            );
+            assert_eq!(get_balance(contract_id), minimum_balance);
+        }
+
+        #[ink::test]
+        fn retry_failed_drains_the_queue() {
+            // Arrange - Bob and Charlie reject the payout
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie, accounts.django], accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000000);
+            reject_transfers_to(accounts.charlie);
+            reject_transfers_to(accounts.bob);
+            contract.trigger_payout().unwrap();
+            assert_eq!(contract.retry_queue(), vec![accounts.bob, accounts.charlie]);
+
+            // Act - only Bob accepts transfers again
+            accept_transfers_to(accounts.bob);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            let report = contract.retry_failed().unwrap();
+
+            // Assert
+            assert_eq!(
+                report,
+                vec![(accounts.bob, Ok(1000000)), (accounts.charlie, Err(Error::TransferFailed))]
+            );
+            assert_eq!(contract.retry_queue(), vec![accounts.charlie]);
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 1000000);
+
+            // Act - Charlie accepts again
+            accept_transfers_to(accounts.charlie);
+            assert_eq!(contract.retry_failed(), Ok(vec![(accounts.charlie, Ok(1000000))]));
+
+            // Assert
+            assert!(contract.retry_queue().is_empty());
+            assert_eq!(contract.retry_failed(), Ok(Vec::new()));
+        }
+
+        #[ink::test]
+        fn batched_payout_resumes_from_the_cursor() {
+            use ink::env::Event;
+
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            let django_before = get_balance(accounts.django);
+            let round = contract.current_round();
+            assert_eq!(contract.trigger_payout_batch(0), Err(Error::InvalidBatchSize));
+
+            // Act - first batch
+            let report = contract.trigger_payout_batch(2).unwrap();
+
+            // Assert
+            assert_eq!(report, vec![(accounts.bob, Ok(1000000)), (accounts.charlie, Ok(1000000))]);
+            assert_eq!(contract.payout_batch().map(|batch| batch.cursor), Some(2));
+            assert_eq!(contract.distributable(), 0);
+            assert_eq!(contract.trigger_payout(), Err(Error::PayoutInProgress));
+            assert_eq!(contract.current_round(), round);
+
+            // Act - last batch
+            let report = contract.trigger_payout_batch(2).unwrap();
+
+            // Assert
+            assert_eq!(report, vec![(accounts.django, Ok(1000000))]);
+            assert_eq!(get_balance(accounts.django), django_before + 1000000);
+            assert_eq!(contract.payout_batch(), None);
+            assert_eq!(contract.current_round(), round + 1);
+            let completed: Vec<PayoutCompleted> = ink::env::test::recorded_events()
+                .filter(|event| Some(event.topics[0].as_slice()) == PayoutCompleted::SIGNATURE_TOPIC.as_ref().map(|t| &t[..]))
+                .map(|event| <PayoutCompleted as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(completed.len(), 1);
+            assert_eq!(completed[0].total, 3000000);
+            assert_eq!(completed[0].num_payees, 3);
+        }
+
+        #[ink::test]
+        fn max_transfers_per_call_limits_payouts() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000000);
+
+            // Assert - admin only
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_max_transfers_per_call(1), Err(Error::Unauthorized));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_max_transfers_per_call(2), Ok(()));
+
+            // Assert - a single-call payout would exceed the limit, batches are capped by it
+            assert_eq!(contract.max_transfers_per_call(), 2);
+            assert_eq!(contract.trigger_payout(), Err(Error::TooManyTransfers));
+            assert_eq!(contract.trigger_payout_batch(10).unwrap().len(), 2);
+            assert_eq!(contract.trigger_payout_batch(10).unwrap().len(), 1);
+            assert_eq!(contract.payout_batch(), None);
+
+            // Assert - pool payouts are limited too
+            let pool_id = contract.create_pool(vec![accounts.django, accounts.eve, accounts.frank]).unwrap();
+            set_value_transferred::<DefaultEnvironment>(3000000);
+            contract.deposit_to_pool(pool_id).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000000);
+            assert_eq!(contract.trigger_pool_payout(pool_id), Err(Error::TooManyTransfers));
+            assert_eq!(contract.pool_balance(pool_id), Ok(3000000));
+        }
+
+        #[ink::test]
+        fn payout_preview_matches_the_next_payout() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let shares = vec![(accounts.bob, 1), (accounts.charlie, 2)];
+            let mut contract = PaymentSplitter::new_weighted(shares, accounts.alice);
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 4000001);
+
+            // Act - any account may preview
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            let preview = contract.payout_preview().unwrap();
+
+            // Assert
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.trigger_payout(), Ok(preview));
+            assert_eq!(contract.payout_preview(), Err(Error::NothingDistributable));
+        }
+
+        #[ink::test]
+        fn remainder_policy_decides_where_dust_goes() {
+            // Arrange - 3 payees and a remainder of 2
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000002);
+            assert_eq!(contract.remainder_policy(), RemainderPolicy::FirstPayee);
+
+            // Act & Assert - designated payee
+            contract.set_remainder_policy(RemainderPolicy::DesignatedPayee).unwrap();
+            assert_eq!(contract.remainder_recipient(), Some(accounts.alice));
+            let payouts = contract.payout_preview().unwrap();
+            assert_eq!(payouts.last(), Some(&PayoutInfo { payee: accounts.alice, amount: 2 }));
+
+            // Act & Assert - treasury, also set through the remainder recipient
+            contract.set_remainder_recipient(Some(accounts.eve)).unwrap();
+            assert_eq!(contract.remainder_policy(), RemainderPolicy::Treasury(accounts.eve));
+
+            // Act - carry over
+            contract.set_remainder_policy(RemainderPolicy::CarryOver).unwrap();
+            let payouts = contract.trigger_payout().unwrap();
+
+            // Assert - the remainder stays for the next payout
+            assert_eq!(payouts.len(), 3);
+            assert!(payouts.iter().all(|info| info.amount == 1000000));
+            assert_eq!(get_balance(contract_id), 2);
+            assert_eq!(contract.remainder_recipient(), None);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_remainder_policy(RemainderPolicy::FirstPayee), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn carried_dust_is_split_with_the_next_payout() {
+            // Arrange - 3 payees, a remainder of 2 carried over
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            contract.set_remainder_policy(RemainderPolicy::CarryOver).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000002);
+
+            // Act
+            contract.trigger_payout().unwrap();
+
+            // Assert - the dust is kept and counted
+            assert_eq!(contract.carried_dust(), 2);
+            assert_eq!(contract.distributable(), 2);
+
+            // Act - a further 3000001 arrives, which with the dust splits evenly
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000003);
+            let payouts = contract.trigger_payout().unwrap();
+
+            // Assert
+            assert!(payouts.iter().all(|info| info.amount == 1000001));
+            assert_eq!(contract.carried_dust(), 0);
+            assert_eq!(get_balance(contract_id), 0);
+        }
+
+        #[ink::test]
+        fn committed_dust_is_carried_over_on_execution() {
+            // Arrange - 3 payees, a remainder of 2 carried over
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            contract.set_remainder_policy(RemainderPolicy::CarryOver).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000002);
+
+            // Act - a cancelled plan leaves the carried dust untouched
+            let cancelled = contract.commit_payout().unwrap();
+            assert_eq!(contract.carried_dust(), 0);
+            contract.cancel_payout_plan(cancelled).unwrap();
+            assert_eq!(contract.carried_dust(), 0);
+
+            // Act - the executed plan records it
+            let plan_id = contract.commit_payout().unwrap();
+            contract.execute_payout(plan_id).unwrap();
+
+            // Assert
+            assert_eq!(contract.carried_dust(), 2);
+            assert_eq!(contract.distributable(), 2);
+
+            // Assert - committing checks the pause and the cooldown
+            contract.set_payout_cooldown(5).unwrap();
+            assert_eq!(contract.commit_payout(), Err(Error::CooldownActive));
+            contract.pause().unwrap();
+            assert_eq!(contract.commit_payout(), Err(Error::ContractPaused));
+        }
+
+        #[ink::test]
+        fn payouts_wait_for_min_payout_per_payee() {
+            // Arrange - 3 payees sharing 3000000 with a minimum of 2000000 each
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+            contract.set_min_payout_per_payee(2000000).unwrap();
+            assert_eq!(contract.min_payout_per_payee(), 2000000);
+
+            // Act & Assert - the preview and the payout are held back
+            assert_eq!(contract.payout_preview(), Err(Error::BelowThreshold));
+            assert_eq!(contract.trigger_payout(), Err(Error::BelowThreshold));
+            assert_eq!(contract.trigger_payout_batch(1), Err(Error::BelowThreshold));
+            assert_eq!(get_balance(contract_id), 3000000);
+
+            // Act - the balance grows large enough
+            set_account_balance::<DefaultEnvironment>(contract_id, 6000000);
+
+            // Assert
+            assert!(contract.payout_preview().is_ok());
+            let payouts = contract.trigger_payout().unwrap();
+            assert!(payouts.iter().all(|info| info.amount == 2000000));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_min_payout_per_payee(0), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn pool_payouts_wait_for_min_payout_per_payee() {
+            // Arrange - a pool of 2 sharing 2000000 with a minimum of 1500000 each
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            let contract_id = contract.env().account_id();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let pool_id = contract.create_pool(vec![accounts.django, accounts.eve]).unwrap();
+            set_value_transferred::<DefaultEnvironment>(2000000);
+            contract.deposit_to_pool(pool_id).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            contract.set_min_payout_per_payee(1500000).unwrap();
+            let django_balance = get_balance(accounts.django);
+
+            // Act & Assert - the pool payout is held back
+            assert_eq!(contract.trigger_pool_payout(pool_id), Err(Error::BelowThreshold));
+            assert_eq!(contract.pool_balance(pool_id), Ok(2000000));
+
+            // Act - the pool grows large enough
+            set_value_transferred::<DefaultEnvironment>(1000000);
+            contract.deposit_to_pool(pool_id).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract_id, 3000000);
+
+            // Assert
+            assert_eq!(contract.trigger_pool_payout(pool_id), Ok(()));
+            assert_eq!(get_balance(accounts.django), django_balance + 1500000);
+        }
+
+        #[ink::test]
+        fn trigger_payout_amount_leaves_the_rest() {
             // Arrange
             let accounts = default_accounts::<DefaultEnvironment>();
             let payees = vec![accounts.bob, accounts.charlie];
-            let mut contract = PaymentSplitter::new(payees.clone(), accounts.alice);
-            set_caller::<DefaultEnvironment>(accounts.charlie);
-            set_value_transferred::<DefaultEnvironment>(100);
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 10000000);
+            let reserve = contract.reserve();
 
-            contract.deposit().unwrap();
+            // Act & Assert - the reserve cannot be paid out
+            assert_eq!(contract.trigger_payout_amount(0), Err(Error::ZeroShare));
+            assert_eq!(
+                contract.trigger_payout_amount(10000000 - reserve + 1),
+                Err(Error::InsufficientBalance)
+            );
 
-            // Act - Payout
-            set_caller::<DefaultEnvironment>(accounts.bob); // Bob is not the designated_payee
-            let result = contract.calculate_payout();
+            // Act
+            let payouts = contract.trigger_payout_amount(4000000).unwrap();
+
+            // Assert - only the amount was split
+            assert_eq!(payouts, vec![
+                PayoutInfo { payee: accounts.bob, amount: 2000000 },
+                PayoutInfo { payee: accounts.charlie, amount: 2000000 },
+            ]);
+            assert_eq!(get_balance(contract_id), 6000000);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.trigger_payout_amount(1000000), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn trigger_payout_for_pays_only_the_listed_payees() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 10000000);
+            let round_id = contract.current_round();
+
+            // Act & Assert - the subset is validated
+            assert_eq!(contract.trigger_payout_for(vec![], 1000000), Err(Error::NoPayees));
+            assert_eq!(contract.trigger_payout_for(vec![accounts.eve], 1000000), Err(Error::PayeeNotFound));
+            assert_eq!(
+                contract.trigger_payout_for(vec![accounts.bob, accounts.bob], 2000000),
+                Err(Error::DuplicatePayee)
+            );
+            assert_eq!(
+                contract.trigger_payout_for(vec![accounts.bob], 10000001),
+                Err(Error::InsufficientBalance)
+            );
+
+            // Act
+            let payouts = contract.trigger_payout_for(vec![accounts.bob, accounts.django], 4000000).unwrap();
+
+            // Assert - only Bob and Django were paid, and the round did not advance
+            assert_eq!(payouts, vec![
+                PayoutInfo { payee: accounts.bob, amount: 2000000 },
+                PayoutInfo { payee: accounts.django, amount: 2000000 },
+            ]);
+            assert_eq!(get_balance(contract_id), 6000000);
+            assert_eq!(contract.current_round(), round_id);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.trigger_payout_for(vec![accounts.bob], 1000000), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn trigger_payout_for_stays_restricted_when_permissionless() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            contract.renounce_designation().unwrap();
+
+            // Act - a payee tries to pay the whole balance to themselves
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let result = contract.trigger_payout_for(vec![accounts.bob], 2000000);
 
             // Assert
             assert_eq!(result, Err(Error::Unauthorized));
+            assert_eq!(get_balance(contract_id), 2000000);
         }
 
         #[ink::test]
-        fn basic_workflow() {
-            // Arrange
+        fn vesting_payee_releases_only_the_vested_part() {
+            // Arrange - Bob's shares vest over 4 blocks with a 2-block cliff
             let accounts = default_accounts::<DefaultEnvironment>();
             let payees = vec![accounts.bob, accounts.charlie];
-            let mut contract = PaymentSplitter::new(payees.clone(), accounts.alice);
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 2000000);
+            let schedule = VestingSchedule { start: contract.env().block_number(), cliff: 2, duration: 4 };
+            assert_eq!(contract.set_vesting_schedule(accounts.eve, schedule.clone()), Err(Error::PayeeNotFound));
+            assert_eq!(contract.set_vesting_schedule(accounts.bob, schedule.clone()), Ok(()));
+            assert_eq!(contract.vesting_schedule(accounts.bob), Some(schedule));
+            let bob_balance = get_balance(accounts.bob);
 
-            // Set initial values
-            let initial_contract_balance = 1000000;
-            let bob_balance = 2000010;
-            let charlie_balance = 3000010;
-            let alice_deposit = 121;
-            let balance_plus_deposit = initial_contract_balance + alice_deposit;
-            let expected_bob_received = 500061;
-            let expected_charlie_received = 500060;
+            // Act - the payout credits Bob's full share but only transfers Charlie's
+            let payouts = contract.trigger_payout().unwrap();
 
-            // Set initial balances
-            set_account_balance::<ink::env::DefaultEnvironment>(
-                contract.env().account_id(),
-                initial_contract_balance
-            );
-            set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, bob_balance);
-            set_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie, charlie_balance);
+            // Assert
+            assert_eq!(payouts, vec![PayoutInfo { payee: accounts.charlie, amount: 1000000 }]);
+            assert_eq!(get_balance(contract_id), 1000000);
+            assert_eq!(contract.distributable(), 0);
+            assert_eq!(contract.vested_amount(accounts.bob), 0);
+            assert_eq!(contract.release(accounts.bob), Err(Error::ZeroShare));
+            assert_eq!(contract.release(accounts.charlie), Err(Error::PullPaymentsDisabled));
 
-            // Act - Deposit
-            set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
-            set_value_transferred::<ink::env::DefaultEnvironment>(alice_deposit);
-            contract.deposit().unwrap();
+            // Act & Assert - half has vested once the cliff is reached
+            advance_block::<DefaultEnvironment>();
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.vested_amount(accounts.bob), 500000);
+            assert_eq!(contract.releasable(accounts.bob), 500000);
+            assert_eq!(contract.release(accounts.bob), Ok(500000));
+            assert_eq!(contract.releasable(accounts.bob), 0);
 
-            // Update contract balance after Alice deposit
-            set_account_balance::<ink::env::DefaultEnvironment>(
-                contract.env().account_id(),
-                balance_plus_deposit
-            );
+            // Act & Assert - everything has vested at the end of the schedule
+            advance_block::<DefaultEnvironment>();
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.release(accounts.bob), Ok(500000));
+            assert_eq!(contract.vested_amount(accounts.bob), 1000000);
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000000);
+            assert_eq!(contract.encumbered(), 0);
 
-            //Get balances before payout
-            let contract_balance_before_split = get_balance(contract.env().account_id());
-            ink::env::debug_println!(
-                "---- Contract balance before split: {}",
-                contract_balance_before_split
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.set_vesting_schedule(accounts.bob, VestingSchedule { start: 0, cliff: 0, duration: 0 }),
+                Err(Error::Unauthorized)
             );
+        }
 
-            // Assert - Deposit (still Alice as caller)
-            assert_eq!(get_balance(contract.env().account_id()), balance_plus_deposit);
+        #[ink::test]
+        fn vesting_schedule_cannot_relock_vested_credits() {
+            // Arrange - Bob's share has fully vested but is not withdrawn yet
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 2000000);
+            let start = contract.env().block_number();
+            contract.set_vesting_schedule(accounts.bob, VestingSchedule { start, cliff: 0, duration: 2 }).unwrap();
+            contract.trigger_payout().unwrap();
+            advance_block::<DefaultEnvironment>();
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.vested_amount(accounts.bob), 1000000);
 
-            // Calculate Payout
-            let payout_info = contract.calculate_payout().unwrap();
-            ink::env::debug_println!("---- payout info 0: {:?}", payout_info[0].amount);
-            ink::env::debug_println!("---- payout info 1: {:?}", payout_info[1].amount);
+            // Act - the admin tries to push the schedule's start back into the future
+            let relock = VestingSchedule { start: start + 100, cliff: 0, duration: 2 };
+            let result = contract.set_vesting_schedule(accounts.bob, relock.clone());
 
-            // Assert Payout Calculations
-            assert_eq!(payout_info.len(), 2);
-            assert_eq!(payout_info[0].payee, accounts.bob);
-            assert_eq!(payout_info[0].amount, expected_bob_received);
-            assert_eq!(payout_info[1].payee, accounts.charlie);
-            assert_eq!(payout_info[1].amount, expected_charlie_received);
+            // Assert - refused, the vested credits stay withdrawable
+            assert_eq!(result, Err(Error::VestingUnreleased));
+            assert_eq!(contract.releasable(accounts.bob), 1000000);
 
-            // Trigger Payout
-            contract.trigger_payout().unwrap();
+            // Act & Assert - once withdrawn, a new schedule starts from no credits
+            assert_eq!(contract.release(accounts.bob), Ok(1000000));
+            assert_eq!(contract.set_vesting_schedule(accounts.bob, relock), Ok(()));
+            assert_eq!(contract.vested_amount(accounts.bob), 0);
+            assert_eq!(contract.releasable(accounts.bob), 0);
+        }
 
-            //Get balances after payout
-            let contract_balance_after = get_balance(contract.env().account_id());
-            ink::env::debug_println!(
-                "---- Contract balance after split: {}",
-                contract_balance_after
-            );
+        #[ink::test]
+        fn stream_accrues_per_block_and_is_claimed() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            let contract_id = contract.env().account_id();
+            set_account_balance::<DefaultEnvironment>(contract_id, 5000000);
+            assert_eq!(contract.start_stream(0, 4), Err(Error::ZeroShare));
+            assert_eq!(contract.start_stream(5000001, 4), Err(Error::InsufficientBalance));
 
-            //Update balances after payout (should be 0 after payout)
-            set_account_balance::<ink::env::DefaultEnvironment>(contract.env().account_id(), 0);
+            // Act - stream 4000000 over 4 blocks
+            assert_eq!(contract.start_stream(4000000, 4), Ok(()));
 
-            // Assert - Payout
-            assert_eq!(get_balance(contract.env().account_id()), 0);
-            assert_eq!(get_balance(accounts.bob), bob_balance + expected_bob_received);
-            assert_eq!(get_balance(accounts.charlie), charlie_balance + expected_charlie_received);
-            ink::env::debug_println!("---- Bob balance: {}", get_balance(accounts.bob));
-            ink::env::debug_println!("---- Charlie balance: {}", get_balance(accounts.charlie));
+            // Assert - the allocation is set aside and nothing has accrued yet
+            assert_eq!(contract.distributable(), 1000000);
+            assert_eq!(contract.streamed_amount(accounts.bob), 0);
+            assert_eq!(contract.start_stream(1000000, 4), Err(Error::StreamActive));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_stream(), Err(Error::ZeroShare));
+
+            // Act & Assert - a quarter accrues per block
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.claimable_stream(accounts.bob), 500000);
+            assert_eq!(contract.claim_stream(), Ok(500000));
+            advance_block::<DefaultEnvironment>();
+            assert_eq!(contract.claim_stream(), Ok(500000));
+            assert_eq!(contract.streamed_amount(accounts.bob), 1000000);
+
+            // Act - the stream ends, and Charlie never claimed
+            for _ in 0..2 {
+                advance_block::<DefaultEnvironment>();
+            }
+            assert_eq!(contract.claim_stream(), Ok(1000000));
+            assert_eq!(contract.claimable_stream(accounts.charlie), 2000000);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.start_stream(1000000, 2), Ok(()));
+
+            // Assert - Charlie's unclaimed allocation became a pending claim
+            assert_eq!(contract.pending_claim_of(accounts.charlie), 2000000);
+            assert_eq!(contract.distributable(), 0);
+
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.claim_stream(), Err(Error::ZeroShare));
+            assert_eq!(contract.start_stream(1, 1), Err(Error::Unauthorized));
+        }
+
+        /// Answers `FETCH_RANDOM` with a fixed seed.
+        struct MockedRandomness([u8; 32]);
+
+        impl ink::env::test::ChainExtension for MockedRandomness {
+            fn ext_id(&self) -> u16 {
+                666
+            }
+
+            fn call(&mut self, _func_id: u16, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                scale::Encode::encode_to(&self.0, output);
+                0
+            }
+        }
+
+        #[ink::test]
+        fn random_payee_policy_pays_dust_to_the_drawn_payee() {
+            use ink::env::Event;
+
+            // Arrange - 3 payees, a remainder of 2 and a seed that draws the third payee
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000002);
+            let mut seed = [0; 32];
+            seed[0] = 5;
+            ink::env::test::register_chain_extension(MockedRandomness(seed));
+            contract.set_remainder_policy(RemainderPolicy::RandomPayee).unwrap();
+            let round_id = contract.current_round();
+
+            // Act
+            let payouts = contract.trigger_payout().unwrap();
+
+            // Assert - the remainder went to the drawn payee, and the draw was announced
+            assert_eq!(payouts.last(), Some(&PayoutInfo { payee: accounts.django, amount: 2 }));
+            let draws: Vec<_> = ink::env::test::recorded_events()
+                .filter(|event| {
+                    Some(event.topics[0].as_slice()) == RemainderRecipientDrawn::SIGNATURE_TOPIC.as_ref().map(|t| &t[..])
+                })
+                .map(|event| <RemainderRecipientDrawn as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(draws.len(), 1);
+            assert_eq!(draws[0].round_id, round_id);
+            assert_eq!(draws[0].pool_id, 0);
+            assert_eq!(draws[0].payee, accounts.django);
+            assert!(draws[0].random);
+        }
+
+        /// Answers `FETCH_RANDOM` with an error status, as a chain without randomness would.
+        struct UnavailableRandomness;
+
+        impl ink::env::test::ChainExtension for UnavailableRandomness {
+            fn ext_id(&self) -> u16 {
+                666
+            }
+
+            fn call(&mut self, _func_id: u16, _input: &[u8], _output: &mut Vec<u8>) -> u32 {
+                1
+            }
+        }
+
+        #[ink::test]
+        fn random_payee_policy_announces_the_fallback_without_randomness() {
+            use ink::env::Event;
+
+            // Arrange - 3 payees, a remainder of 2 and no randomness
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let payees = vec![accounts.bob, accounts.charlie, accounts.django];
+            let mut contract = PaymentSplitter::new(payees, accounts.alice);
+            contract.set_reserve(0).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000002);
+            ink::env::test::register_chain_extension(UnavailableRandomness);
+            contract.set_remainder_policy(RemainderPolicy::RandomPayee).unwrap();
+
+            // Act
+            let payouts = contract.trigger_payout().unwrap();
+
+            // Assert - the remainder went to the first payee, and the event says it was not drawn
+            assert_eq!(payouts.last(), Some(&PayoutInfo { payee: accounts.bob, amount: 2 }));
+            let draws: Vec<_> = ink::env::test::recorded_events()
+                .filter(|event| {
+                    Some(event.topics[0].as_slice()) == RemainderRecipientDrawn::SIGNATURE_TOPIC.as_ref().map(|t| &t[..])
+                })
+                .map(|event| <RemainderRecipientDrawn as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(draws.len(), 1);
+            assert_eq!(draws[0].payee, accounts.bob);
+            assert!(!draws[0].random);
+        }
+
+        #[ink::test]
+        fn random_payee_policy_draws_pool_dust_among_the_pool_payees() {
+            use ink::env::Event;
+
+            // Arrange - a seed that draws the second payee of two, or the third of three
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut contract = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], accounts.alice);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let pool_id = contract.create_pool(vec![accounts.django, accounts.eve, accounts.frank]).unwrap();
+            set_value_transferred::<DefaultEnvironment>(3000002);
+            contract.deposit_to_pool(pool_id).unwrap();
+            set_account_balance::<DefaultEnvironment>(contract.env().account_id(), 3000002);
+            let mut seed = [0; 32];
+            seed[0] = 5;
+            ink::env::test::register_chain_extension(MockedRandomness(seed));
+            contract.set_remainder_policy(RemainderPolicy::RandomPayee).unwrap();
+            let frank_balance = get_balance(accounts.frank);
+            let charlie_balance = get_balance(accounts.charlie);
+
+            // Act
+            contract.trigger_pool_payout(pool_id).unwrap();
+
+            // Assert - the remainder stays within the pool, and the draw was announced for it
+            assert_eq!(get_balance(accounts.frank), frank_balance + 1000002);
+            assert_eq!(get_balance(accounts.charlie), charlie_balance);
+            let draws: Vec<_> = ink::env::test::recorded_events()
+                .filter(|event| {
+                    Some(event.topics[0].as_slice()) == RemainderRecipientDrawn::SIGNATURE_TOPIC.as_ref().map(|t| &t[..])
+                })
+                .map(|event| <RemainderRecipientDrawn as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert_eq!(draws.len(), 1);
+            assert_eq!((draws[0].pool_id, draws[0].payee, draws[0].random), (pool_id, accounts.frank, true));
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::{ AccountKeyring, ChainBackend, ContractsBackend };
+        use psp22_token::psp22_token::{ Psp22Token, Psp22TokenRef };
+        use psp22_token::PSP22;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn child_splitter_forwards_to_leaf_payees<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            // Arrange - a child splitter paying Charlie and Dave
+            let alice = ink_e2e::account_id(AccountKeyring::Alice);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let dave = ink_e2e::account_id(AccountKeyring::Dave);
+            let eve = ink_e2e::account_id(AccountKeyring::Eve);
+
+            let mut child_constructor = PaymentSplitterRef::new(vec![charlie, dave], alice);
+            let child = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut child_constructor)
+                .submit().await
+                .expect("child instantiate failed");
+            let mut child_call = child.call_builder::<PaymentSplitter>();
+
+            // The parent splitter pays the child and Eve
+            let mut parent_constructor = PaymentSplitterRef::new(vec![child.account_id, eve], alice);
+            let parent = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut parent_constructor)
+                .submit().await
+                .expect("parent instantiate failed");
+            let mut parent_call = parent.call_builder::<PaymentSplitter>();
+
+            client
+                .call(&ink_e2e::alice(), &child_call.set_parent_splitter(Some(parent.account_id)))
+                .submit().await
+                .expect("set_parent_splitter failed");
+            client
+                .call(&ink_e2e::alice(), &parent_call.set_child_splitter(child.account_id, true))
+                .submit().await
+                .expect("set_child_splitter failed");
+            client
+                .call(&ink_e2e::alice(), &parent_call.set_reserve(0))
+                .submit().await
+                .expect("set_reserve failed");
+            client
+                .call(&ink_e2e::alice(), &child_call.set_reserve(0))
+                .submit().await
+                .expect("set_reserve failed");
+
+            let deposit = 4_000_000_000_000;
+            client
+                .call(&ink_e2e::bob(), &parent_call.deposit())
+                .value(deposit)
+                .submit().await
+                .expect("deposit failed");
+            let charlie_before = client.free_balance(charlie).await?;
+            let dave_before = client.free_balance(dave).await?;
+
+            // Act
+            client
+                .call(&ink_e2e::alice(), &parent_call.trigger_payout())
+                .submit().await
+                .expect("trigger_payout failed");
+
+            // Assert - the child's half reached the leaf payees
+            assert!(client.free_balance(charlie).await? >= charlie_before + deposit / 4);
+            assert!(client.free_balance(dave).await? >= dave_before + deposit / 4);
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn token_split_waits_for_min_payout_per_payee<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            // Arrange - 1000 tokens deposited for Charlie and Dave, with a minimum of 600 each
+            let alice = ink_e2e::account_id(AccountKeyring::Alice);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let dave = ink_e2e::account_id(AccountKeyring::Dave);
+
+            let mut token_constructor = Psp22TokenRef::new(1_000_000);
+            let token = client
+                .instantiate("psp22_token", &ink_e2e::alice(), &mut token_constructor)
+                .submit().await
+                .expect("token instantiate failed");
+            let mut token_call = token.call_builder::<Psp22Token>();
+
+            let mut splitter_constructor = PaymentSplitterRef::new(vec![charlie, dave], alice);
+            let splitter = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut splitter_constructor)
+                .submit().await
+                .expect("splitter instantiate failed");
+            let mut splitter_call = splitter.call_builder::<PaymentSplitter>();
+
+            client
+                .call(&ink_e2e::alice(), &splitter_call.register_token(token.account_id))
+                .submit().await
+                .expect("register_token failed");
+            client
+                .call(&ink_e2e::alice(), &token_call.approve(splitter.account_id, 1000))
+                .submit().await
+                .expect("approve failed");
+            client
+                .call(&ink_e2e::alice(), &splitter_call.deposit_token(token.account_id, 1000))
+                .submit().await
+                .expect("deposit_token failed");
+            client
+                .call(&ink_e2e::alice(), &splitter_call.set_min_payout_per_payee(600))
+                .submit().await
+                .expect("set_min_payout_per_payee failed");
+
+            // Act
+            let payout = client
+                .call(&ink_e2e::alice(), &splitter_call.trigger_token_payout(token.account_id))
+                .dry_run().await?
+                .return_value();
+
+            // Assert - the token shares of 500 are held back
+            assert_eq!(payout, Err(Error::BelowThreshold));
+            let charlie_tokens = client
+                .call(&ink_e2e::alice(), &token_call.balance_of(charlie))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(charlie_tokens, 0);
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn token_split_parks_a_rejected_transfer_until_claimed<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            // Arrange - a PSP22 token owned by Alice and a splitter paying Charlie and Dave
+            let alice = ink_e2e::account_id(AccountKeyring::Alice);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let dave = ink_e2e::account_id(AccountKeyring::Dave);
+
+            let mut token_constructor = Psp22TokenRef::new(1_000_000);
+            let token = client
+                .instantiate("psp22_token", &ink_e2e::alice(), &mut token_constructor)
+                .submit().await
+                .expect("token instantiate failed");
+            let mut token_call = token.call_builder::<Psp22Token>();
+
+            let mut splitter_constructor = PaymentSplitterRef::new(vec![charlie, dave], alice);
+            let splitter = client
+                .instantiate("payment_splitter", &ink_e2e::alice(), &mut splitter_constructor)
+                .submit().await
+                .expect("splitter instantiate failed");
+            let mut splitter_call = splitter.call_builder::<PaymentSplitter>();
+
+            client
+                .call(&ink_e2e::alice(), &splitter_call.register_token(token.account_id))
+                .submit().await
+                .expect("register_token failed");
+            client
+                .call(&ink_e2e::alice(), &token_call.approve(splitter.account_id, 1000))
+                .submit().await
+                .expect("approve failed");
+            client
+                .call(&ink_e2e::alice(), &splitter_call.deposit_token(token.account_id, 1000))
+                .submit().await
+                .expect("deposit_token failed");
+            // Transfers to Dave are rejected by the token
+            client
+                .call(&ink_e2e::alice(), &token_call.set_blocked(dave, true))
+                .submit().await
+                .expect("set_blocked failed");
+
+            // Act
+            let payout = client
+                .call(&ink_e2e::alice(), &splitter_call.trigger_payout_all_assets())
+                .submit().await
+                .expect("trigger_payout_all_assets failed")
+                .return_value()
+                .expect("payout round refused");
+
+            // Assert - the native balance had nothing to split, the token was split and Dave's
+            // share was parked
+            assert_eq!(payout.len(), 2);
+            assert_eq!(payout[0].token, None);
+            assert!(payout[0].result.is_err());
+            assert_eq!(payout[1], AssetPayoutResult { token: Some(token.account_id), result: Ok(500) });
+            let charlie_tokens = client
+                .call(&ink_e2e::alice(), &token_call.balance_of(charlie))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(charlie_tokens, 500);
+            let dave_claim = client
+                .call(&ink_e2e::alice(), &splitter_call.token_pending_claim_of(token.account_id, dave))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(dave_claim, 500);
+
+            // Act - Dave can receive tokens again and claims the parked share
+            client
+                .call(&ink_e2e::alice(), &token_call.set_blocked(dave, false))
+                .submit().await
+                .expect("set_blocked failed");
+            let claimed = client
+                .call(&ink_e2e::dave(), &splitter_call.claim_token(token.account_id))
+                .submit().await
+                .expect("claim_token failed")
+                .return_value();
+
+            // Assert
+            assert_eq!(claimed, Ok(500));
+            let dave_tokens = client
+                .call(&ink_e2e::alice(), &token_call.balance_of(dave))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(dave_tokens, 500);
+            let dave_claim = client
+                .call(&ink_e2e::alice(), &splitter_call.token_pending_claim_of(token.account_id, dave))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(dave_claim, 0);
+            Ok(())
+        }
+    }
+}
+
+/// The payout arithmetic, kept free of environment and storage access so that every payout
+/// path shares it and it can be tested in isolation.
+mod distribution {
+    use crate::payment_splitter::{ Error, PayoutInfo };
+    use crate::Balance;
+    use ink::prelude::vec::Vec;
+    use ink::primitives::AccountId;
+
+    /// Splits `total` equally among `payees`.
+    ///
+    /// The remainder after division is appended as an entry for `remainder_recipient` if one is
+    /// given, and added to the first payee's share otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * `NoPayees`: If `payees` is empty.
+    /// * `ZeroShare`: If `total` is too small to give every payee something.
+    ///
+    pub fn compute_distribution(
+        total: Balance,
+        payees: &[AccountId],
+        remainder_recipient: Option<AccountId>
+    ) -> Result<Vec<PayoutInfo>, Error> {
+        let num_payees = payees.len() as u128;
+        if num_payees == 0 {
+            return Err(Error::NoPayees);
+        }
+
+        // Calculate the share each payee should receive.
+        let share = total.checked_div(num_payees).ok_or(Error::ZeroShare)?;
+        if share == 0 {
+            return Err(Error::ZeroShare);
+        }
+
+        // Calculate the remainder after division.
+        let remainder = total.saturating_sub(share.checked_mul(num_payees).ok_or(Error::ZeroShare)?);
+
+        let mut payout_info: Vec<PayoutInfo> = payees
+            .iter()
+            .map(|payee| PayoutInfo { payee: *payee, amount: share })
+            .collect();
+        match remainder_recipient {
+            Some(recipient) if remainder > 0 => {
+                payout_info.push(PayoutInfo { payee: recipient, amount: remainder });
+            }
+            Some(_) => {}
+            None => {
+                payout_info[0].amount = share.checked_add(remainder).ok_or(Error::TransferFailed)?;
+            }
+        }
+        Ok(payout_info)
+    }
+
+    /// Splits `total` among `weights`, pairs of a payee and its weight, in proportion to the
+    /// weights.
+    ///
+    /// Payees with weight 0 receive nothing. The rounding remainder is appended as an entry for
+    /// `remainder_recipient` if one is given, and added to the first weighted payee's share
+    /// otherwise. With equal weights this is `compute_distribution`.
+    ///
+    /// # Errors
+    ///
+    /// * `NoPayees`: If `weights` is empty.
+    /// * `ZeroShare`: If every weight is 0, or `total` is too small to give every weighted
+    ///   payee something.
+    ///
+    pub fn compute_weighted_distribution(
+        total: Balance,
+        weights: &[(AccountId, u128)],
+        remainder_recipient: Option<AccountId>
+    ) -> Result<Vec<PayoutInfo>, Error> {
+        if weights.is_empty() {
+            return Err(Error::NoPayees);
+        }
+        let total_weight = weights
+            .iter()
+            .try_fold(0, |sum: u128, (_, weight)| sum.checked_add(*weight))
+            .ok_or(Error::ZeroShare)?;
+        if total_weight == 0 {
+            return Err(Error::ZeroShare);
+        }
+        if weights.iter().all(|(_, weight)| *weight == weights[0].1) {
+            let payees: Vec<AccountId> = weights.iter().map(|(payee, _)| *payee).collect();
+            return compute_distribution(total, &payees, remainder_recipient);
+        }
+
+        // total * weight / total_weight, split so that only the rounding part can overflow.
+        let quotient = total / total_weight;
+        let rest = total % total_weight;
+        let mut payout_info = Vec::new();
+        let mut allocated: Balance = 0;
+        for (payee, weight) in weights.iter().filter(|(_, weight)| *weight > 0) {
+            let amount = rest
+                .checked_mul(*weight)
+                .map(|scaled| scaled / total_weight)
+                .and_then(|rounded| quotient.checked_mul(*weight)?.checked_add(rounded))
+                .ok_or(Error::ZeroShare)?;
+            if amount == 0 {
+                return Err(Error::ZeroShare);
+            }
+            allocated = allocated.saturating_add(amount);
+            payout_info.push(PayoutInfo { payee: *payee, amount });
+        }
+
+        let remainder = total.saturating_sub(allocated);
+        match remainder_recipient {
+            Some(recipient) if remainder > 0 => {
+                payout_info.push(PayoutInfo { payee: recipient, amount: remainder });
+            }
+            Some(_) => {}
+            None => {
+                payout_info[0].amount = payout_info[0].amount.saturating_add(remainder);
+            }
+        }
+        Ok(payout_info)
+    }
+
+    /// Splits `total` among `tiers`, triples of a payee, its fixed amount (tier 1) or `None`
+    /// (tier 2), and its weight within tier 2.
+    ///
+    /// Tier-1 payees are paid their fixed amounts first, pro-rated down if `total` cannot cover
+    /// them all; the pro-rating remainder goes to `remainder_recipient` or the first tier-1
+    /// payee. Whatever is left is split among the tier-2 payees in proportion to their weights,
+    /// and dust too small to give each of them something is left out. Without tier-1 payees
+    /// this is `compute_weighted_distribution`.
+    pub fn compute_tiered_distribution(
+        total: Balance,
+        tiers: &[(AccountId, Option<Balance>, u128)],
+        remainder_recipient: Option<AccountId>
+    ) -> Result<Vec<PayoutInfo>, Error> {
+        if tiers.is_empty() {
+            return Err(Error::NoPayees);
+        }
+        if total == 0 {
+            return Err(Error::ZeroShare);
+        }
+
+        let mut fixed_total: Balance = 0;
+        let mut residual_payees = Vec::new();
+        for (payee, fixed_amount, weight) in tiers.iter() {
+            match fixed_amount {
+                Some(amount) => {
+                    fixed_total = fixed_total.checked_add(*amount).ok_or(Error::ZeroShare)?;
+                }
+                None => residual_payees.push((*payee, *weight)),
+            }
+        }
+
+        if fixed_total == 0 {
+            let weights: Vec<(AccountId, u128)> = tiers.iter().map(|(payee, _, weight)| (*payee, *weight)).collect();
+            return compute_weighted_distribution(total, &weights, remainder_recipient);
+        }
+
+        // Pay the fixed amounts, pro-rated down if the total cannot cover them.
+        let underfunded = total < fixed_total;
+        let mut fixed_payouts = Vec::new();
+        let mut allocated: Balance = 0;
+        for (payee, fixed_amount, _) in tiers.iter() {
+            if let Some(amount) = fixed_amount {
+                let to_transfer = if underfunded {
+                    amount
+                        .checked_mul(total)
+                        .and_then(|scaled| scaled.checked_div(fixed_total))
+                        .ok_or(Error::ZeroShare)?
+                } else {
+                    *amount
+                };
+                allocated = allocated.saturating_add(to_transfer);
+                fixed_payouts.push(PayoutInfo {
+                    payee: *payee,
+                    amount: to_transfer,
+                });
+            }
+        }
+
+        let mut residual = total.saturating_sub(allocated);
+        let mut remainder_payout = None;
+        if underfunded {
+            // The pro-rating remainder goes to the remainder recipient or the first tier-1 payee.
+            match remainder_recipient {
+                Some(recipient) if residual > 0 => {
+                    remainder_payout = Some(PayoutInfo { payee: recipient, amount: residual });
+                }
+                Some(_) => {}
+                None => {
+                    if let Some(first) = fixed_payouts.first_mut() {
+                        first.amount = first.amount.saturating_add(residual);
+                    }
+                }
+            }
+            residual = 0;
+        }
+
+        let mut residual_payouts = if residual > 0 && !residual_payees.is_empty() {
+            // Dust too small to give every tier-2 payee something stays undistributed.
+            compute_weighted_distribution(residual, &residual_payees, remainder_recipient)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let weighted_payees = residual_payees.iter().filter(|(_, weight)| *weight > 0).count();
+        if residual_payouts.len() > weighted_payees {
+            // The remainder entry appended for the remainder recipient.
+            remainder_payout = residual_payouts.pop();
+        }
+
+        // Report the payouts in payee order, skipping payees that receive nothing.
+        let mut payout_info = Vec::new();
+        for (payee, _, _) in tiers.iter() {
+            let amount = fixed_payouts
+                .iter()
+                .chain(residual_payouts.iter())
+                .find(|info| info.payee == *payee)
+                .map(|info| info.amount)
+                .unwrap_or(0);
+            if amount > 0 {
+                payout_info.push(PayoutInfo {
+                    payee: *payee,
+                    amount,
+                });
+            }
+        }
+        payout_info.extend(remainder_payout);
+
+        if payout_info.is_empty() {
+            return Err(Error::ZeroShare);
+        }
+        Ok(payout_info)
+    }
+
+    /// Splits the contributed part of `total` pro-rata to `contributions`, pairs of a payee and
+    /// its contribution.
+    ///
+    /// The rounding remainder, and whatever exceeds the sum of the contributions, go to
+    /// `remainder_recipient` or else the first payee.
+    pub fn compute_contribution_distribution(
+        total: Balance,
+        contributions: &[(AccountId, Balance)],
+        remainder_recipient: Option<AccountId>
+    ) -> Result<Vec<PayoutInfo>, Error> {
+        if contributions.is_empty() {
+            return Err(Error::NoPayees);
+        }
+        let total_contributions = contributions
+            .iter()
+            .fold(0, |sum: Balance, (_, contribution)| sum.saturating_add(*contribution));
+        if total == 0 || total_contributions == 0 {
+            return Err(Error::ZeroShare);
+        }
+
+        let contributed = total.min(total_contributions);
+        let mut amounts = Vec::new();
+        let mut allocated: Balance = 0;
+        for (_, contribution) in contributions.iter() {
+            let amount = contribution
+                .checked_mul(contributed)
+                .and_then(|scaled| scaled.checked_div(total_contributions))
+                .ok_or(Error::ZeroShare)?;
+            allocated = allocated.saturating_add(amount);
+            amounts.push(amount);
+        }
+        let remainder = total.saturating_sub(allocated);
+        if remainder_recipient.is_none() {
+            amounts[0] = amounts[0].saturating_add(remainder);
+        }
+
+        let mut payout_info: Vec<PayoutInfo> = contributions
+            .iter()
+            .zip(amounts)
+            .filter(|(_, amount)| *amount > 0)
+            .map(|((payee, _), amount)| PayoutInfo { payee: *payee, amount })
+            .collect();
+        if let Some(recipient) = remainder_recipient {
+            if remainder > 0 {
+                payout_info.push(PayoutInfo { payee: recipient, amount: remainder });
+            }
+        }
+        Ok(payout_info)
+    }
+
+    /// Pays each payee of `owed`, pairs of a payee and the amount it is owed, in list order
+    /// until `total` runs out.
+    ///
+    /// # Errors
+    ///
+    /// * `NoPayees`: If `owed` is empty.
+    /// * `NothingOwed`: If nothing is owed to anyone.
+    /// * `ZeroShare`: If `total` is zero.
+    ///
+    pub fn compute_owed_distribution(
+        total: Balance,
+        owed: &[(AccountId, Balance)]
+    ) -> Result<Vec<PayoutInfo>, Error> {
+        if owed.is_empty() {
+            return Err(Error::NoPayees);
+        }
+
+        let mut available = total;
+        let mut anything_owed = false;
+        let mut payout_info = Vec::new();
+        for (payee, amount) in owed.iter() {
+            anything_owed |= *amount > 0;
+            let to_transfer = (*amount).min(available);
+            if to_transfer > 0 {
+                payout_info.push(PayoutInfo {
+                    payee: *payee,
+                    amount: to_transfer,
+                });
+                available = available.saturating_sub(to_transfer);
+            }
+        }
+
+        if !anything_owed {
+            return Err(Error::NothingOwed);
+        }
+        if payout_info.is_empty() {
+            return Err(Error::ZeroShare);
         }
+        Ok(payout_info)
     }
 }